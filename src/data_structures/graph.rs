@@ -1,5 +1,8 @@
 use std::collections::{HashMap, HashSet};
 use std::fmt;
+use std::hash::Hash;
+
+use crate::graph::WeightedGraph;
 
 #[derive(Debug, Clone)]
 pub struct NodeNotInGraph;
@@ -10,104 +13,211 @@ impl fmt::Display for NodeNotInGraph {
     }
 }
 
-pub struct DirectedGraph {
-    adjacency_table: HashMap<String, Vec<(String, i32)>>,
+pub struct DirectedGraph<N, W> {
+    adjacency_table: HashMap<N, Vec<(N, W)>>,
 }
 
-impl Graph for DirectedGraph {
-    fn new() -> DirectedGraph {
+impl<N: Hash + Eq + Clone, W: Clone> Graph<N, W> for DirectedGraph<N, W> {
+    fn new() -> DirectedGraph<N, W> {
         DirectedGraph {
             adjacency_table: HashMap::new(),
         }
     }
-    fn adjacency_table_mutable(&mut self) -> &mut HashMap<String, Vec<(String, i32)>> {
+    fn adjacency_table_mutable(&mut self) -> &mut HashMap<N, Vec<(N, W)>> {
         &mut self.adjacency_table
     }
-    fn adjacency_table(&self) -> &HashMap<String, Vec<(String, i32)>> {
+    fn adjacency_table(&self) -> &HashMap<N, Vec<(N, W)>> {
         &self.adjacency_table
     }
 }
 
-pub struct UndirectedGraph {
-    adjacency_table: HashMap<String, Vec<(String, i32)>>,
+impl<N: Hash + Eq + Clone, W: Clone> DirectedGraph<N, W> {
+    /// The adjacency table of the graph with every edge reversed: for each
+    /// node, the nodes that have an edge towards it, with the edge weights.
+    pub fn reverse_adjacency_table(&self) -> HashMap<&N, Vec<(&N, &W)>> {
+        let mut reversed: HashMap<&N, Vec<(&N, &W)>> = self
+            .adjacency_table
+            .keys()
+            .map(|n| (n, Vec::new()))
+            .collect();
+        for (from_node, to_node, weight) in Graph::edges(self) {
+            reversed
+                .entry(to_node)
+                .or_default()
+                .push((from_node, weight));
+        }
+        reversed
+    }
+}
+
+pub struct UndirectedGraph<N, W> {
+    adjacency_table: HashMap<N, Vec<(N, W)>>,
 }
 
-impl Graph for UndirectedGraph {
-    fn new() -> UndirectedGraph {
+impl<N: Hash + Eq + Clone, W: Clone> Graph<N, W> for UndirectedGraph<N, W> {
+    fn new() -> UndirectedGraph<N, W> {
         UndirectedGraph {
             adjacency_table: HashMap::new(),
         }
     }
-    fn adjacency_table_mutable(&mut self) -> &mut HashMap<String, Vec<(String, i32)>> {
+    fn adjacency_table_mutable(&mut self) -> &mut HashMap<N, Vec<(N, W)>> {
         &mut self.adjacency_table
     }
-    fn adjacency_table(&self) -> &HashMap<String, Vec<(String, i32)>> {
+    fn adjacency_table(&self) -> &HashMap<N, Vec<(N, W)>> {
         &self.adjacency_table
     }
-    fn add_edge(&mut self, edge: (&str, &str, i32)) {
-        self.add_node(edge.0);
-        self.add_node(edge.1);
+    fn add_edge(&mut self, edge: (N, N, W)) {
+        self.add_node(edge.0.clone());
+        self.add_node(edge.1.clone());
+
+        self.adjacency_table.entry(edge.0.clone()).and_modify(|e| {
+            e.push((edge.1.clone(), edge.2.clone()));
+        });
+        self.adjacency_table.entry(edge.1).and_modify(|e| {
+            e.push((edge.0, edge.2));
+        });
+    }
+    fn remove_edge(&mut self, from_node: &N, to_node: &N) -> bool {
+        let mut removed = false;
+        for (node, other) in [(from_node, to_node), (to_node, from_node)] {
+            if let Some(neighbours) = self.adjacency_table.get_mut(node) {
+                let len = neighbours.len();
+                neighbours.retain(|(n, _)| n != other);
+                removed |= neighbours.len() != len;
+            }
+        }
+        removed
+    }
+}
+
+impl<N: Hash + Eq + Clone, W: Clone> WeightedGraph for DirectedGraph<N, W> {
+    type Vertex = N;
+    type Weight = W;
+
+    fn vertices(&self) -> impl Iterator<Item = N> + '_ {
+        self.adjacency_table.keys().cloned()
+    }
 
+    fn neighbors(&self, vertex: N) -> impl Iterator<Item = (N, W)> + '_ {
         self.adjacency_table
-            .entry(edge.0.to_string())
-            .and_modify(|e| {
-                e.push((edge.1.to_string(), edge.2));
-            });
+            .get(&vertex)
+            .into_iter()
+            .flatten()
+            .cloned()
+    }
+}
+
+impl<N: Hash + Eq + Clone, W: Clone> WeightedGraph for UndirectedGraph<N, W> {
+    type Vertex = N;
+    type Weight = W;
+
+    fn vertices(&self) -> impl Iterator<Item = N> + '_ {
+        self.adjacency_table.keys().cloned()
+    }
+
+    fn neighbors(&self, vertex: N) -> impl Iterator<Item = (N, W)> + '_ {
         self.adjacency_table
-            .entry(edge.1.to_string())
-            .and_modify(|e| {
-                e.push((edge.0.to_string(), edge.2));
-            });
+            .get(&vertex)
+            .into_iter()
+            .flatten()
+            .cloned()
     }
 }
 
-pub trait Graph {
+/// A graph whose nodes are of type `N` and whose edges have a weight of type `W`.
+/// Each node maps to the list of its outgoing edges, as `(destination, weight)`.
+pub trait Graph<N: Hash + Eq + Clone, W: Clone> {
     fn new() -> Self;
-    fn adjacency_table_mutable(&mut self) -> &mut HashMap<String, Vec<(String, i32)>>;
-    fn adjacency_table(&self) -> &HashMap<String, Vec<(String, i32)>>;
+    fn adjacency_table_mutable(&mut self) -> &mut HashMap<N, Vec<(N, W)>>;
+    fn adjacency_table(&self) -> &HashMap<N, Vec<(N, W)>>;
 
-    fn add_node(&mut self, node: &str) -> bool {
-        match self.adjacency_table().get(node) {
+    fn add_node(&mut self, node: N) -> bool {
+        match self.adjacency_table().get(&node) {
             None => {
-                self.adjacency_table_mutable()
-                    .insert((*node).to_string(), Vec::new());
+                self.adjacency_table_mutable().insert(node, Vec::new());
                 true
             }
             _ => false,
         }
     }
 
-    fn add_edge(&mut self, edge: (&str, &str, i32)) {
-        self.add_node(edge.0);
-        self.add_node(edge.1);
+    fn add_edge(&mut self, edge: (N, N, W)) {
+        self.add_node(edge.0.clone());
+        self.add_node(edge.1.clone());
 
         self.adjacency_table_mutable()
-            .entry(edge.0.to_string())
+            .entry(edge.0)
             .and_modify(|e| {
-                e.push((edge.1.to_string(), edge.2));
+                e.push((edge.1, edge.2));
             });
     }
 
-    fn neighbours(&self, node: &str) -> Result<&Vec<(String, i32)>, NodeNotInGraph> {
+    /// Removes `node` and every edge going to or coming from it.
+    /// Returns false if the node wasn't in the graph.
+    fn remove_node(&mut self, node: &N) -> bool {
+        if self.adjacency_table_mutable().remove(node).is_none() {
+            return false;
+        }
+        for neighbours in self.adjacency_table_mutable().values_mut() {
+            neighbours.retain(|(n, _)| n != node);
+        }
+        true
+    }
+
+    /// Removes every edge from `from_node` to `to_node`.
+    /// Returns false if there was no such edge.
+    fn remove_edge(&mut self, from_node: &N, to_node: &N) -> bool {
+        match self.adjacency_table_mutable().get_mut(from_node) {
+            None => false,
+            Some(neighbours) => {
+                let len = neighbours.len();
+                neighbours.retain(|(n, _)| n != to_node);
+                neighbours.len() != len
+            }
+        }
+    }
+
+    fn neighbours(&self, node: &N) -> Result<&Vec<(N, W)>, NodeNotInGraph> {
         match self.adjacency_table().get(node) {
             None => Err(NodeNotInGraph),
             Some(i) => Ok(i),
         }
     }
 
-    fn contains(&self, node: &str) -> bool {
+    /// The number of edges leaving `node`
+    fn out_degree(&self, node: &N) -> Result<usize, NodeNotInGraph> {
+        self.neighbours(node).map(Vec::len)
+    }
+
+    /// The number of edges arriving at `node`
+    fn in_degree(&self, node: &N) -> Result<usize, NodeNotInGraph> {
+        if !self.contains(node) {
+            return Err(NodeNotInGraph);
+        }
+        Ok(self
+            .adjacency_table()
+            .values()
+            .flatten()
+            .filter(|(n, _)| n == node)
+            .count())
+    }
+
+    fn contains(&self, node: &N) -> bool {
         self.adjacency_table().get(node).is_some()
     }
 
-    fn nodes(&self) -> HashSet<&String> {
+    fn nodes<'a>(&'a self) -> HashSet<&'a N>
+    where
+        W: 'a,
+    {
         self.adjacency_table().keys().collect()
     }
 
-    fn edges(&self) -> Vec<(&String, &String, i32)> {
+    fn edges(&self) -> Vec<(&N, &N, &W)> {
         let mut edges = Vec::new();
         for (from_node, from_node_neighbours) in self.adjacency_table() {
             for (to_node, weight) in from_node_neighbours {
-                edges.push((from_node, to_node, *weight));
+                edges.push((from_node, to_node, weight));
             }
         }
         edges
@@ -127,12 +237,12 @@ mod test_undirected_graph {
         graph.add_edge(("c", "a", 7));
 
         let expected_edges = [
-            (&String::from("a"), &String::from("b"), 5),
-            (&String::from("b"), &String::from("a"), 5),
-            (&String::from("c"), &String::from("a"), 7),
-            (&String::from("a"), &String::from("c"), 7),
-            (&String::from("b"), &String::from("c"), 10),
-            (&String::from("c"), &String::from("b"), 10),
+            (&"a", &"b", &5),
+            (&"b", &"a", &5),
+            (&"c", &"a", &7),
+            (&"a", &"c", &7),
+            (&"b", &"c", &10),
+            (&"c", &"b", &10),
         ];
         for edge in expected_edges.iter() {
             assert!(graph.edges().contains(edge));
@@ -147,10 +257,28 @@ mod test_undirected_graph {
         graph.add_edge(("b", "c", 10));
         graph.add_edge(("c", "a", 7));
 
-        assert_eq!(
-            graph.neighbours("a").unwrap(),
-            &vec![(String::from("b"), 5), (String::from("c"), 7)]
-        );
+        assert_eq!(graph.neighbours(&"a").unwrap(), &vec![("b", 5), ("c", 7)]);
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut graph = UndirectedGraph::new();
+
+        graph.add_edge((1, 2, 0.5));
+        graph.add_edge((2, 3, 1.5));
+        graph.add_edge((3, 1, 2.5));
+
+        assert!(graph.remove_edge(&2, &1));
+        assert!(!graph.remove_edge(&1, &2));
+        assert_eq!(graph.neighbours(&1).unwrap(), &vec![(3, 2.5)]);
+        assert_eq!(graph.neighbours(&2).unwrap(), &vec![(3, 1.5)]);
+
+        assert!(graph.remove_node(&3));
+        assert!(!graph.remove_node(&3));
+        assert!(graph.edges().is_empty());
+        assert_eq!(graph.nodes().len(), 2);
+        assert_eq!(graph.in_degree(&1).unwrap(), 0);
+        assert!(graph.in_degree(&3).is_err());
     }
 }
 
@@ -161,17 +289,11 @@ mod test_directed_graph {
 
     #[test]
     fn test_add_node() {
-        let mut graph = DirectedGraph::new();
+        let mut graph: DirectedGraph<&str, i32> = DirectedGraph::new();
         graph.add_node("a");
         graph.add_node("b");
         graph.add_node("c");
-        assert_eq!(
-            graph.nodes(),
-            [&String::from("a"), &String::from("b"), &String::from("c")]
-                .iter()
-                .cloned()
-                .collect()
-        );
+        assert_eq!(graph.nodes(), [&"a", &"b", &"c"].iter().cloned().collect());
     }
 
     #[test]
@@ -182,11 +304,7 @@ mod test_directed_graph {
         graph.add_edge(("c", "a", 7));
         graph.add_edge(("b", "c", 10));
 
-        let expected_edges = [
-            (&String::from("a"), &String::from("b"), 5),
-            (&String::from("c"), &String::from("a"), 7),
-            (&String::from("b"), &String::from("c"), 10),
-        ];
+        let expected_edges = [(&"a", &"b", &5), (&"c", &"a", &7), (&"b", &"c", &10)];
         for edge in expected_edges.iter() {
             assert!(graph.edges().contains(edge));
         }
@@ -200,21 +318,72 @@ mod test_directed_graph {
         graph.add_edge(("b", "c", 10));
         graph.add_edge(("c", "a", 7));
 
-        assert_eq!(
-            graph.neighbours("a").unwrap(),
-            &vec![(String::from("b"), 5)]
-        );
+        assert_eq!(graph.neighbours(&"a").unwrap(), &vec![("b", 5)]);
     }
 
     #[test]
     fn test_contains() {
-        let mut graph = DirectedGraph::new();
+        let mut graph: DirectedGraph<&str, i32> = DirectedGraph::new();
         graph.add_node("a");
         graph.add_node("b");
         graph.add_node("c");
-        assert!(graph.contains("a"));
-        assert!(graph.contains("b"));
-        assert!(graph.contains("c"));
-        assert!(!graph.contains("d"));
+        assert!(graph.contains(&"a"));
+        assert!(graph.contains(&"b"));
+        assert!(graph.contains(&"c"));
+        assert!(!graph.contains(&"d"));
+    }
+
+    #[test]
+    fn test_typed_nodes_and_degrees() {
+        let mut graph = DirectedGraph::new();
+        graph.add_edge((1u32, 2u32, 1.5));
+        graph.add_edge((1, 3, 2.0));
+        graph.add_edge((2, 3, 0.5));
+        graph.add_node(4);
+
+        assert_eq!(graph.out_degree(&1).unwrap(), 2);
+        assert_eq!(graph.in_degree(&1).unwrap(), 0);
+        assert_eq!(graph.in_degree(&3).unwrap(), 2);
+        assert_eq!(graph.out_degree(&4).unwrap(), 0);
+        assert!(graph.out_degree(&5).is_err());
+
+        assert!(graph.remove_edge(&1, &3));
+        assert!(!graph.remove_edge(&1, &3));
+        assert_eq!(graph.in_degree(&3).unwrap(), 1);
+
+        assert!(graph.remove_node(&2));
+        assert_eq!(graph.in_degree(&3).unwrap(), 0);
+        assert_eq!(graph.out_degree(&1).unwrap(), 0);
+        assert!(!graph.contains(&2));
+    }
+
+    #[test]
+    fn test_reverse_adjacency_table() {
+        let mut graph = DirectedGraph::new();
+        graph.add_edge(("a", "b", 5));
+        graph.add_edge(("c", "b", 7));
+        graph.add_edge(("b", "c", 10));
+
+        let reversed = graph.reverse_adjacency_table();
+        assert_eq!(reversed.len(), 3);
+        assert!(reversed[&"a"].is_empty());
+        let mut into_b = reversed[&"b"].clone();
+        into_b.sort();
+        assert_eq!(into_b, vec![(&"a", &5), (&"c", &7)]);
+        assert_eq!(reversed[&"c"], vec![(&"b", &10)]);
+    }
+
+    #[test]
+    fn test_weighted_graph() {
+        use crate::graph::WeightedGraph;
+
+        let mut graph = DirectedGraph::new();
+        graph.add_edge(("a", "b", 5));
+        graph.add_edge(("b", "c", 10));
+        graph.add_edge(("c", "a", 7));
+
+        assert_eq!(graph.num_vertices(), 3);
+        assert_eq!(graph.num_edges(), 3);
+        assert_eq!(graph.neighbors("b").collect::<Vec<_>>(), vec![("c", 10)]);
     }
 }
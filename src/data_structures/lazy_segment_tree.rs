@@ -1,23 +1,34 @@
-use std::fmt::{Debug, Display};
 use std::ops::Add;
-use std::ops::AddAssign;
 use std::ops::Range;
 
-pub struct LazySegmentTree<T: Debug + Default + Ord + Copy + Display + AddAssign + Add<Output = T>>
-{
+use crate::math::associative_power;
+
+/// A segment tree answering range queries, with two kinds of range updates.
+/// The merge function must be associative, and doesn't have to be commutative:
+/// the values of a range are merged from left to right.
+///
+/// `assign` sets every value of a range, lazily: a node that is covered by the
+/// range gets the aggregate of that many copies of the value (with O(log n)
+/// merges), and keeps the value to pass it on to its children when needed.
+/// `update` adds a value to every element of a range. The aggregate after an
+/// addition can't be derived from the merge function alone, so it is applied
+/// down to the leaves, in O(length of the range + log n).
+pub struct LazySegmentTree<T> {
     len: usize,
     tree: Vec<T>,
-    lazy: Vec<Option<T>>,
+    // the value assigned to the whole range of a node, not passed on to its children yet
+    assigned: Vec<Option<T>>,
     merge: fn(T, T) -> T,
 }
 
-impl<T: Debug + Default + Ord + Copy + Display + AddAssign + Add<Output = T>> LazySegmentTree<T> {
+impl<T: Copy> LazySegmentTree<T> {
     pub fn from_vec(arr: &[T], merge: fn(T, T) -> T) -> Self {
         let len = arr.len();
         let mut sgtr = LazySegmentTree {
             len,
-            tree: vec![T::default(); 4 * len],
-            lazy: vec![None; 4 * len],
+            // every node is overwritten by `build_recursive`
+            tree: arr.first().map_or(vec![], |&first| vec![first; 4 * len]),
+            assigned: vec![None; 4 * len],
             merge,
         };
         if len != 0 {
@@ -56,12 +67,10 @@ impl<T: Debug + Default + Ord + Copy + Display + AddAssign + Add<Output = T>> La
         if element_range.start >= query_range.end || element_range.end <= query_range.start {
             return None;
         }
-        if self.lazy[idx].is_some() {
-            self.propagation(idx, &element_range, T::default());
-        }
         if element_range.start >= query_range.start && element_range.end <= query_range.end {
             return Some(self.tree[idx]);
         }
+        self.push(idx, &element_range);
         let mid = element_range.start + (element_range.end - element_range.start) / 2;
         let left = self.query_recursive(idx * 2, element_range.start..mid, query_range);
         let right = self.query_recursive(idx * 2 + 1, mid..element_range.end, query_range);
@@ -73,11 +82,12 @@ impl<T: Debug + Default + Ord + Copy + Display + AddAssign + Add<Output = T>> La
         }
     }
 
-    pub fn update(&mut self, target_range: Range<usize>, val: T) {
-        self.update_recursive(1, 0..self.len, &target_range, val);
+    /// Sets every value of `target_range` to `val`
+    pub fn assign(&mut self, target_range: Range<usize>, val: T) {
+        self.assign_recursive(1, 0..self.len, &target_range, val);
     }
 
-    fn update_recursive(
+    fn assign_recursive(
         &mut self,
         idx: usize,
         element_range: Range<usize>,
@@ -87,39 +97,59 @@ impl<T: Debug + Default + Ord + Copy + Display + AddAssign + Add<Output = T>> La
         if element_range.start >= target_range.end || element_range.end <= target_range.start {
             return;
         }
-        if element_range.end - element_range.start == 1 {
-            self.tree[idx] += val;
-            return;
-        }
         if element_range.start >= target_range.start && element_range.end <= target_range.end {
-            self.lazy[idx] = match self.lazy[idx] {
-                Some(lazy) => Some(lazy + val),
-                None => Some(val),
-            };
+            self.apply(idx, &element_range, val);
             return;
         }
-        if self.lazy[idx].is_some() && self.lazy[idx].unwrap() != T::default() {
-            self.propagation(idx, &element_range, T::default());
-        }
+        self.push(idx, &element_range);
         let mid = element_range.start + (element_range.end - element_range.start) / 2;
-        self.update_recursive(idx * 2, element_range.start..mid, target_range, val);
-        self.update_recursive(idx * 2 + 1, mid..element_range.end, target_range, val);
+        self.assign_recursive(idx * 2, element_range.start..mid, target_range, val);
+        self.assign_recursive(idx * 2 + 1, mid..element_range.end, target_range, val);
         self.tree[idx] = (self.merge)(self.tree[idx * 2], self.tree[idx * 2 + 1]);
-        self.lazy[idx] = Some(T::default());
     }
 
-    fn propagation(&mut self, idx: usize, element_range: &Range<usize>, parent_lazy: T) {
-        if element_range.end - element_range.start == 1 {
-            self.tree[idx] += parent_lazy;
-            return;
+    fn apply(&mut self, idx: usize, element_range: &Range<usize>, val: T) {
+        let len = element_range.end - element_range.start;
+        self.tree[idx] = associative_power(self.merge, val, len);
+        if len > 1 {
+            self.assigned[idx] = Some(val);
         }
+    }
 
-        let lazy = self.lazy[idx].unwrap_or_default();
-        self.lazy[idx] = None;
+    // passes the value assigned to a node on to its children
+    fn push(&mut self, idx: usize, element_range: &Range<usize>) {
+        if let Some(val) = self.assigned[idx].take() {
+            let mid = element_range.start + (element_range.end - element_range.start) / 2;
+            self.apply(idx * 2, &(element_range.start..mid), val);
+            self.apply(idx * 2 + 1, &(mid..element_range.end), val);
+        }
+    }
+}
 
+impl<T: Copy + Add<Output = T>> LazySegmentTree<T> {
+    /// Adds `val` to every value of `target_range`
+    pub fn update(&mut self, target_range: Range<usize>, val: T) {
+        self.update_recursive(1, 0..self.len, &target_range, val);
+    }
+
+    fn update_recursive(
+        &mut self,
+        idx: usize,
+        element_range: Range<usize>,
+        target_range: &Range<usize>,
+        val: T,
+    ) {
+        if element_range.start >= target_range.end || element_range.end <= target_range.start {
+            return;
+        }
+        if element_range.end - element_range.start == 1 {
+            self.tree[idx] = self.tree[idx] + val;
+            return;
+        }
+        self.push(idx, &element_range);
         let mid = element_range.start + (element_range.end - element_range.start) / 2;
-        self.propagation(idx * 2, &(element_range.start..mid), parent_lazy + lazy);
-        self.propagation(idx * 2 + 1, &(mid..element_range.end), parent_lazy + lazy);
+        self.update_recursive(idx * 2, element_range.start..mid, target_range, val);
+        self.update_recursive(idx * 2 + 1, mid..element_range.end, target_range, val);
         self.tree[idx] = (self.merge)(self.tree[idx * 2], self.tree[idx * 2 + 1]);
     }
 }
@@ -211,6 +241,46 @@ mod tests {
         assert_eq!(Some(21), update_seg_tree.query(1..7));
     }
 
+    #[test]
+    fn test_assign_segments() {
+        let vec = vec![-30, 2, -4, 7, 3, -5, 6, 11, -20, 9, 14, 15, 5, 2, -8];
+        let mut sum_seg_tree = LazySegmentTree::from_vec(&vec, |x, y| x + y);
+        // -> [-30, 2, (1, 1, 1, 1, 1), 11, -20, 9, 14, 15, 5, 2, -8]
+        sum_seg_tree.assign(2..7, 1);
+        assert_eq!(Some(3), sum_seg_tree.query(3..6));
+        assert_eq!(Some(-26), sum_seg_tree.query(0..4));
+        // -> [-30, 2, 1, 1, (3, 3, 3, 13), -20, 9, 14, 15, 5, 2, -8]
+        sum_seg_tree.update(4..8, 2);
+        assert_eq!(Some(24), sum_seg_tree.query(2..8));
+        // -> [-30, 2, 1, 1, 3, (0, 0), 13, -20, 9, 14, 15, 5, 2, -8]
+        sum_seg_tree.assign(5..7, 0);
+        assert_eq!(Some(18), sum_seg_tree.query(2..8));
+        assert_eq!(Some(13), sum_seg_tree.query(7..8));
+    }
+
+    #[test]
+    fn test_assign_non_commutative() {
+        // composition of affine maps x -> a * x + b, applying the left one first
+        let compose = |f: (i64, i64), g: (i64, i64)| (f.0 * g.0, f.1 * g.0 + g.1);
+        let mut vec: Vec<(i64, i64)> = (0..11).map(|i| (1 - i % 2, i)).collect();
+        let mut seg_tree = LazySegmentTree::from_vec(&vec, compose);
+        for (range, val) in [
+            (2..9, (1, 2)),
+            (0..4, (2, 1)),
+            (6..7, (-1, 0)),
+            (3..11, (1, -1)),
+        ] {
+            seg_tree.assign(range.clone(), val);
+            vec[range].fill(val);
+            for start in 0..vec.len() {
+                for end in start + 1..=vec.len() {
+                    let expected = vec[start..end].iter().copied().reduce(compose);
+                    assert_eq!(seg_tree.query(start..end), expected);
+                }
+            }
+        }
+    }
+
     // Some properties over segment trees:
     //  When asking for the range of the overall array, return the same as iter().min() or iter().max(), etc.
     //  When asking for an interval containing a single value, return this value, no matter the merge function
@@ -28,6 +28,8 @@ pub use self::binary_search_tree::BinarySearchTree;
 pub use self::fenwick_tree::FenwickTree;
 pub use self::floyds_algorithm::{detect_cycle, has_cycle};
 pub use self::graph::DirectedGraph;
+pub use self::graph::Graph;
+pub use self::graph::NodeNotInGraph;
 pub use self::graph::UndirectedGraph;
 pub use self::hash_table::HashTable;
 pub use self::heap::Heap;
@@ -45,5 +47,5 @@ pub use self::segment_tree_recursive::SegmentTree as SegmentTreeRecursive;
 pub use self::stack_using_singly_linked_list::Stack;
 pub use self::treap::Treap;
 pub use self::trie::Trie;
-pub use self::union_find::UnionFind;
+pub use self::union_find::{DiffConstraintError, UnionFind, WeightedUnionFind};
 pub use self::veb_tree::VebTree;
@@ -1,25 +1,26 @@
 use std::cmp::min;
-use std::fmt::Debug;
 use std::ops::Range;
 
 /// This data structure implements a segment-tree that can efficiently answer range (interval) queries on arrays.
 /// It represents this array as a binary tree of merged intervals. From top to bottom: [aggregated value for the overall array], then [left-hand half, right hand half], etc. until [each individual value, ...]
 /// It is generic over a reduction function for each segment or interval: basically, to describe how we merge two intervals together.
-/// Note that this function should be commutative and associative
+/// Note that this function should be associative
 ///     It could be `std::cmp::min(interval_1, interval_2)` or `std::cmp::max(interval_1, interval_2)`, or `|a, b| a + b`, `|a, b| a * b`
-pub struct SegmentTree<T: Debug + Default + Ord + Copy> {
+/// It doesn't have to be commutative: the values of a range are merged from left to right.
+pub struct SegmentTree<T: Copy> {
     len: usize,           // length of the represented
     tree: Vec<T>, // represents a binary tree of intervals as an array (as a BinaryHeap does, for instance)
     merge: fn(T, T) -> T, // how we merge two values together
 }
 
-impl<T: Debug + Default + Ord + Copy> SegmentTree<T> {
+impl<T: Copy> SegmentTree<T> {
     /// Builds a SegmentTree from an array and a merge function
     pub fn from_vec(arr: &[T], merge: fn(T, T) -> T) -> Self {
         let len = arr.len();
-        let mut buf: Vec<T> = vec![T::default(); 2 * len];
         // Populate the tree bottom-up, from right to left
-        buf[len..(2 * len)].clone_from_slice(&arr[0..len]); // last len pos is the bottom of the tree -> every individual value
+        // the first len positions are overwritten below (position 0 is unused),
+        // and the last len pos is the bottom of the tree -> every individual value
+        let mut buf: Vec<T> = [arr, arr].concat();
         for i in (1..len).rev() {
             // a nice property of this "flat" representation of a tree: the parent of an element at index i is located at index i/2
             buf[i] = merge(buf[2 * i], buf[2 * i + 1]);
@@ -37,11 +38,13 @@ impl<T: Debug + Default + Ord + Copy> SegmentTree<T> {
     pub fn query(&self, range: Range<usize>) -> Option<T> {
         let mut l = range.start + self.len;
         let mut r = min(self.len, range.end) + self.len;
-        let mut res = None;
+        // the aggregates of the left and right parts of the range, merged at the end to keep the order
+        let mut left = None;
+        let mut right = None;
         // Check Wikipedia or other detailed explanations here for how to navigate the tree bottom-up to limit the number of operations
         while l < r {
             if l % 2 == 1 {
-                res = Some(match res {
+                left = Some(match left {
                     None => self.tree[l],
                     Some(old) => (self.merge)(old, self.tree[l]),
                 });
@@ -49,15 +52,19 @@ impl<T: Debug + Default + Ord + Copy> SegmentTree<T> {
             }
             if r % 2 == 1 {
                 r -= 1;
-                res = Some(match res {
+                right = Some(match right {
                     None => self.tree[r],
-                    Some(old) => (self.merge)(old, self.tree[r]),
+                    Some(old) => (self.merge)(self.tree[r], old),
                 });
             }
             l /= 2;
             r /= 2;
         }
-        res
+        match (left, right) {
+            (Some(left), Some(right)) => Some((self.merge)(left, right)),
+            (left, None) => left,
+            (None, right) => right,
+        }
     }
 
     /// Updates the value at index `idx` in the original array with a new value `val`
@@ -125,6 +132,22 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_non_commutative_merge() {
+        // composition of affine maps x -> a * x + b, applying the left one first
+        let compose = |f: (i64, i64), g: (i64, i64)| (f.0 * g.0, f.1 * g.0 + g.1);
+        let mut vec: Vec<(i64, i64)> = (0..13).map(|i| (i % 3 - 1, i)).collect();
+        let mut seg_tree = SegmentTree::from_vec(&vec, compose);
+        vec[5] = (2, -3);
+        seg_tree.update(5, vec[5]);
+        for start in 0..vec.len() {
+            for end in start + 1..=vec.len() {
+                let expected = vec[start..end].iter().copied().reduce(compose);
+                assert_eq!(seg_tree.query(start..end), expected);
+            }
+        }
+    }
+
     // Some properties over segment trees:
     //  When asking for the range of the overall array, return the same as iter().min() or iter().max(), etc.
     //  When asking for an interval containing a single value, return this value, no matter the merge function
@@ -1,6 +1,7 @@
 use std::collections::HashMap;
 use std::fmt::Debug;
 use std::hash::Hash;
+use std::ops::{Add, Neg, Sub};
 
 /// UnionFind data structure
 /// It acts by holding an array of pointers to parents, together with the size of each subset
@@ -10,6 +11,7 @@ pub struct UnionFind<T: Debug + Eq + Hash> {
     parent_links: Vec<usize>, // holds the relationship between an item and its parent. The root of a set is denoted by parent_links[i] == i
     sizes: Vec<usize>,        // holds the size
     count: usize,
+    history: Vec<(usize, usize)>, // the (child, parent) roots linked by each union, so that it can be undone
 }
 
 impl<T: Debug + Eq + Hash> UnionFind<T> {
@@ -28,6 +30,7 @@ impl<T: Debug + Eq + Hash> UnionFind<T> {
             sizes: Vec::with_capacity(capacity),
             payloads: HashMap::with_capacity(capacity),
             count: 0,
+            history: Vec::new(),
         }
     }
 
@@ -94,17 +97,67 @@ impl<T: Debug + Eq + Hash> UnionFind<T> {
             return false; // they belong to the same set already, no-op
         }
         // Attach the smaller set to the larger one
-        if self.sizes[root1] < self.sizes[root2] {
-            self.parent_links[root1] = root2;
-            self.sizes[root2] += self.sizes[root1];
+        let (child, parent) = if self.sizes[root1] < self.sizes[root2] {
+            (root1, root2)
         } else {
-            self.parent_links[root2] = root1;
-            self.sizes[root1] += self.sizes[root2];
-        }
+            (root2, root1)
+        };
+        self.parent_links[child] = parent;
+        self.sizes[parent] += self.sizes[child];
+        self.history.push((child, parent));
         self.count -= 1; // we had 2 disjoint sets, now merged as one
         true
     }
 
+    /// Returns the number of items in the set of `item`, or None if it hasn't been inserted
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use the_algorithms_rust::data_structures::UnionFind;
+    /// let mut uf = UnionFind::from_iter(["A", "B", "C"]);
+    /// uf.union(&"A", &"B");
+    /// assert_eq!(Some(2), uf.set_size(&"B"));
+    /// assert_eq!(Some(1), uf.set_size(&"C"));
+    /// assert_eq!(None, uf.set_size(&"D"));
+    /// ```
+    pub fn set_size(&self, item: &T) -> Option<usize> {
+        self.find(item).map(|root| self.sizes[root])
+    }
+
+    /// Returns the current point in the history of unions, to be given to `rollback`
+    pub fn snapshot(&self) -> usize {
+        self.history.len()
+    }
+
+    /// Undoes every union done since `snapshot` was taken, the most recent first.
+    /// Items inserted in the meantime are kept, as singletons.
+    /// Since paths are never compressed, each union is undone in O(1).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use the_algorithms_rust::data_structures::UnionFind;
+    /// let mut uf = UnionFind::from_iter(["A", "B", "C"]);
+    /// uf.union(&"A", &"B");
+    /// let snapshot = uf.snapshot();
+    /// uf.union(&"B", &"C");
+    /// assert_eq!(1, uf.count());
+    ///
+    /// uf.rollback(snapshot);
+    /// assert_eq!(2, uf.count());
+    /// assert!(uf.is_same_set(&"A", &"B"));
+    /// assert!(!uf.is_same_set(&"A", &"C"));
+    /// ```
+    pub fn rollback(&mut self, snapshot: usize) {
+        while self.history.len() > snapshot {
+            let (child, parent) = self.history.pop().unwrap();
+            self.parent_links[child] = child;
+            self.sizes[parent] -= self.sizes[child];
+            self.count += 1;
+        }
+    }
+
     /// Checks if two items belong to the same set
     ///
     /// #_Examples:
@@ -153,6 +206,7 @@ impl<T: Debug + Eq + Hash> Default for UnionFind<T> {
             sizes: Vec::default(),
             payloads: HashMap::default(),
             count: 0,
+            history: Vec::default(),
         }
     }
 }
@@ -168,6 +222,168 @@ impl<T: Debug + Eq + Hash> FromIterator<T> for UnionFind<T> {
     }
 }
 
+/// Error returned by `WeightedUnionFind::union_with_diff`
+#[derive(Debug, PartialEq, Eq)]
+pub enum DiffConstraintError<W> {
+    /// One of the items hasn't been inserted in the data structure
+    MissingItem,
+    /// The items are already in the same set, with a different difference
+    Contradiction { current: W, requested: W },
+}
+
+/// Weighted (or potential) UnionFind data structure
+/// Besides the sets, it keeps track of the difference `value(b) - value(a)` between any two
+/// items a and b of the same set, as given by constraints `value(b) - value(a) = d`.
+/// Each item stores the difference between its value and the value of its parent.
+#[derive(Debug)]
+pub struct WeightedUnionFind<T: Debug + Eq + Hash, W> {
+    payloads: HashMap<T, usize>,
+    parent_links: Vec<usize>,
+    offsets: Vec<W>, // offsets[i] = value(i) - value(parent_links[i])
+    sizes: Vec<usize>,
+    count: usize,
+}
+
+impl<T, W> WeightedUnionFind<T, W>
+where
+    T: Debug + Eq + Hash,
+    W: Copy + Default + PartialEq + Add<Output = W> + Sub<Output = W> + Neg<Output = W>,
+{
+    /// Inserts a new item (disjoint) in the data structure
+    pub fn insert(&mut self, item: T) {
+        let key = self.payloads.len();
+        self.parent_links.push(key);
+        self.offsets.push(W::default());
+        self.sizes.push(1);
+        self.payloads.insert(item, key);
+        self.count += 1;
+    }
+
+    /// Returns the root of the set of an item, and `value(item) - value(root)`
+    fn find(&self, value: &T) -> Option<(usize, W)> {
+        let mut id = *self.payloads.get(value)?;
+        let mut offset = W::default();
+        while id != self.parent_links[id] {
+            offset = offset + self.offsets[id];
+            id = self.parent_links[id];
+        }
+        Some((id, offset))
+    }
+
+    /// Records the constraint `value(item2) - value(item1) = diff`
+    /// returns Ok(true) if two disjoint sets have been merged
+    /// returns Ok(false) if the constraint was already implied by the previous ones
+    /// returns an error if an item is missing, or if the constraint contradicts the previous ones
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use the_algorithms_rust::data_structures::{DiffConstraintError, WeightedUnionFind};
+    /// let mut uf = WeightedUnionFind::from_iter(["A", "B", "C"]);
+    /// assert_eq!(Ok(true), uf.union_with_diff(&"A", &"B", 3));
+    /// assert_eq!(Ok(true), uf.union_with_diff(&"B", &"C", 4));
+    /// assert_eq!(Ok(false), uf.union_with_diff(&"C", &"A", -7));
+    /// assert_eq!(
+    ///     Err(DiffConstraintError::Contradiction { current: 7, requested: 6 }),
+    ///     uf.union_with_diff(&"A", &"C", 6)
+    /// );
+    /// assert_eq!(Err(DiffConstraintError::MissingItem), uf.union_with_diff(&"A", &"D", 1));
+    /// ```
+    pub fn union_with_diff(
+        &mut self,
+        item1: &T,
+        item2: &T,
+        diff: W,
+    ) -> Result<bool, DiffConstraintError<W>> {
+        let (Some((root1, offset1)), Some((root2, offset2))) = (self.find(item1), self.find(item2))
+        else {
+            return Err(DiffConstraintError::MissingItem);
+        };
+        if root1 == root2 {
+            let current = offset2 - offset1;
+            return if current == diff {
+                Ok(false)
+            } else {
+                Err(DiffConstraintError::Contradiction {
+                    current,
+                    requested: diff,
+                })
+            };
+        }
+        // value(root2) - value(root1)
+        let root_diff = diff + offset1 - offset2;
+        // Attach the smaller set to the larger one
+        if self.sizes[root1] < self.sizes[root2] {
+            self.parent_links[root1] = root2;
+            self.offsets[root1] = -root_diff;
+            self.sizes[root2] += self.sizes[root1];
+        } else {
+            self.parent_links[root2] = root1;
+            self.offsets[root2] = root_diff;
+            self.sizes[root1] += self.sizes[root2];
+        }
+        self.count -= 1;
+        Ok(true)
+    }
+
+    /// Returns `value(item2) - value(item1)` if both items belong to the same set
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use the_algorithms_rust::data_structures::WeightedUnionFind;
+    /// let mut uf = WeightedUnionFind::from_iter([1, 2, 3]);
+    /// uf.union_with_diff(&1, &2, 10).unwrap();
+    /// assert_eq!(Some(-10), uf.diff(&2, &1));
+    /// assert_eq!(None, uf.diff(&1, &3));
+    /// ```
+    pub fn diff(&self, item1: &T, item2: &T) -> Option<W> {
+        match (self.find(item1), self.find(item2)) {
+            (Some((root1, offset1)), Some((root2, offset2))) if root1 == root2 => {
+                Some(offset2 - offset1)
+            }
+            _ => None,
+        }
+    }
+
+    /// Checks if two items belong to the same set
+    pub fn is_same_set(&self, item1: &T, item2: &T) -> bool {
+        self.diff(item1, item2).is_some()
+    }
+
+    /// Returns the number of disjoint sets
+    pub fn count(&self) -> usize {
+        self.count
+    }
+}
+
+impl<T: Debug + Eq + Hash, W> Default for WeightedUnionFind<T, W> {
+    fn default() -> Self {
+        Self {
+            payloads: HashMap::default(),
+            parent_links: Vec::default(),
+            offsets: Vec::default(),
+            sizes: Vec::default(),
+            count: 0,
+        }
+    }
+}
+
+impl<T, W> FromIterator<T> for WeightedUnionFind<T, W>
+where
+    T: Debug + Eq + Hash,
+    W: Copy + Default + PartialEq + Add<Output = W> + Sub<Output = W> + Neg<Output = W>,
+{
+    /// Creates a new WeightedUnionFind data structure from an iterable of disjoint elements
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut uf = WeightedUnionFind::default();
+        for i in iter {
+            uf.insert(i);
+        }
+        uf
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -216,4 +432,89 @@ mod tests {
         uf.union(&"F", &"G");
         assert_eq!(3, uf.count());
     }
+
+    #[test]
+    fn test_rollback() {
+        let mut uf = UnionFind::from_iter(0..6);
+        uf.union(&0, &1);
+        let first = uf.snapshot();
+        uf.union(&2, &3);
+        uf.union(&1, &3);
+        let second = uf.snapshot();
+        uf.union(&4, &5);
+        uf.union(&5, &0);
+        assert_eq!(Some(false), uf.union(&3, &4));
+        assert_eq!(1, uf.count());
+        assert_eq!(Some(6), uf.set_size(&2));
+
+        uf.rollback(second);
+        assert_eq!(3, uf.count());
+        assert_eq!(Some(4), uf.set_size(&0));
+        assert!(!uf.is_same_set(&0, &4));
+
+        uf.insert(6);
+        uf.rollback(first);
+        assert_eq!(6, uf.count());
+        assert!(uf.is_same_set(&0, &1));
+        assert!(!uf.is_same_set(&1, &2));
+        assert_eq!(Some(2), uf.set_size(&1));
+        assert_eq!(Some(1), uf.set_size(&6));
+    }
+
+    #[test]
+    fn test_weighted_union_find() {
+        // Relative heights measured between pairs of survey points
+        let mut uf: WeightedUnionFind<&str, i64> =
+            WeightedUnionFind::from_iter(["A", "B", "C", "D", "E"]);
+        assert_eq!(Ok(true), uf.union_with_diff(&"A", &"B", 5));
+        assert_eq!(Ok(true), uf.union_with_diff(&"D", &"C", -2));
+        assert_eq!(Ok(true), uf.union_with_diff(&"B", &"C", 1));
+        assert_eq!(2, uf.count());
+        assert_eq!(Some(8), uf.diff(&"A", &"D"));
+        assert_eq!(Some(-6), uf.diff(&"C", &"A"));
+        assert_eq!(Some(0), uf.diff(&"E", &"E"));
+        assert!(!uf.is_same_set(&"A", &"E"));
+
+        assert_eq!(Ok(false), uf.union_with_diff(&"D", &"A", -8));
+        assert_eq!(
+            Err(DiffConstraintError::Contradiction {
+                current: 8,
+                requested: 9
+            }),
+            uf.union_with_diff(&"A", &"D", 9)
+        );
+        // A failed constraint leaves the structure unchanged
+        assert_eq!(2, uf.count());
+        assert_eq!(Some(8), uf.diff(&"A", &"D"));
+    }
+
+    #[test]
+    fn test_weighted_union_find_random() {
+        use rand::rngs::StdRng;
+        use rand::{Rng, SeedableRng};
+
+        let mut rng = StdRng::seed_from_u64(10);
+        let n = 50;
+        let values: Vec<i64> = (0..n).map(|_| rng.gen_range(-1000..=1000)).collect();
+        let mut uf: WeightedUnionFind<usize, i64> = WeightedUnionFind::from_iter(0..n);
+        let mut plain = UnionFind::from_iter(0..n);
+        for _ in 0..200 {
+            let (a, b) = (rng.gen_range(0..n), rng.gen_range(0..n));
+            let merged = plain.union(&a, &b).unwrap();
+            assert_eq!(
+                Ok(merged),
+                uf.union_with_diff(&a, &b, values[b] - values[a])
+            );
+            // Any other difference between connected items is a contradiction
+            let wrong = values[b] - values[a] + rng.gen_range(1..=10);
+            assert!(uf.union_with_diff(&a, &b, wrong).is_err());
+            assert_eq!(plain.count(), uf.count());
+        }
+        for a in 0..n {
+            for b in 0..n {
+                let expected = plain.is_same_set(&a, &b).then(|| values[b] - values[a]);
+                assert_eq!(expected, uf.diff(&a, &b));
+            }
+        }
+    }
 }
@@ -5,7 +5,7 @@ use std::{
 
 use num_traits::Zero;
 
-type Graph<V, E> = BTreeMap<V, BTreeMap<V, E>>;
+use super::WeightedGraph;
 
 #[derive(Clone, Debug, Eq, PartialEq)]
 struct Candidate<V, E> {
@@ -30,18 +30,33 @@ impl<V: Ord + Copy, E: Ord + Copy> Ord for Candidate<V, E> {
     }
 }
 
+// finds a shortest path from start to target, guided by a heuristic that must never
+// overestimate the remaining distance to target
+// returns the distance and the vertices of the path, from start to target
 pub fn astar<V: Ord + Copy, E: Ord + Copy + Add<Output = E> + Zero>(
-    graph: &Graph<V, E>,
+    graph: &impl WeightedGraph<Vertex = V, Weight = E>,
     start: V,
     target: V,
     heuristic: impl Fn(V) -> E,
 ) -> Option<(E, Vec<V>)> {
+    astar_with_expanded_count(graph, start, target, heuristic).0
+}
+
+// same as `astar`, but also returns the number of expanded nodes, i.e. nodes whose
+// neighbors were visited, to measure how well the heuristic guides the search
+pub fn astar_with_expanded_count<V: Ord + Copy, E: Ord + Copy + Add<Output = E> + Zero>(
+    graph: &impl WeightedGraph<Vertex = V, Weight = E>,
+    start: V,
+    target: V,
+    heuristic: impl Fn(V) -> E,
+) -> (Option<(E, Vec<V>)>, usize) {
     // traversal front
     let mut queue = BinaryHeap::new();
     // maps each node to its predecessor in the final path
     let mut previous = BTreeMap::new();
     // weights[v] is the accumulated weight from start to v
     let mut weights = BTreeMap::new();
+    let mut expanded = 0;
     // initialize traversal
     weights.insert(start, E::zero());
     queue.push(Candidate {
@@ -58,7 +73,12 @@ pub fn astar<V: Ord + Copy, E: Ord + Copy + Add<Output = E> + Zero>(
         if current == target {
             break;
         }
-        for (&next, &weight) in &graph[&current] {
+        // a shorter way to current was found after this candidate was pushed
+        if weights[&current] < real_weight {
+            continue;
+        }
+        expanded += 1;
+        for (next, weight) in graph.neighbors(current) {
             let real_weight = real_weight + weight;
             if weights
                 .get(&next)
@@ -82,7 +102,7 @@ pub fn astar<V: Ord + Copy, E: Ord + Copy + Add<Output = E> + Zero>(
         weight
     } else {
         // we did not reach target from start
-        return None;
+        return (None, expanded);
     };
     // build path in reverse
     let mut current = target;
@@ -96,15 +116,17 @@ pub fn astar<V: Ord + Copy, E: Ord + Copy + Add<Output = E> + Zero>(
         path.push(current);
     }
     path.reverse();
-    Some((weight, path))
+    (Some((weight, path)), expanded)
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{astar, Graph};
+    use super::{astar, astar_with_expanded_count};
     use num_traits::Zero;
     use std::collections::BTreeMap;
 
+    type Graph<V, E> = BTreeMap<V, BTreeMap<V, E>>;
+
     // the null heuristic make A* equivalent to Dijkstra
     fn null_heuristic<V, E: Zero>(_v: V) -> E {
         E::zero()
@@ -259,4 +281,25 @@ mod tests {
         assert_eq!(weight, 100);
         assert_eq!(path.len(), 101);
     }
+
+    #[test]
+    fn expanded_count() {
+        let mut graph = BTreeMap::new();
+        for i in 0..10 {
+            add_edge(&mut graph, i, i + 1, 1);
+            add_edge(&mut graph, i + 1, i, 2);
+        }
+        // without a heuristic, both directions from 5 are explored
+        let (res, expanded) = astar_with_expanded_count(&graph, 5, 8, null_heuristic);
+        assert_eq!(res, Some((3, vec![5, 6, 7, 8])));
+        assert_eq!(expanded, 4);
+        // with the exact distance, only the path is expanded
+        let (res, expanded) = astar_with_expanded_count(&graph, 5, 8, |v: i32| (8 - v).abs());
+        assert_eq!(res, Some((3, vec![5, 6, 7, 8])));
+        assert_eq!(expanded, 3);
+
+        let (res, expanded) = astar_with_expanded_count(&graph, 5, 20, null_heuristic);
+        assert_eq!(res, None);
+        assert_eq!(expanded, 11);
+    }
 }
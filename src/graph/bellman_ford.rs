@@ -1,94 +1,162 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt::{self, Debug, Display};
 use std::ops::Add;
 
 use std::ops::Neg;
 
-type Graph<V, E> = BTreeMap<V, BTreeMap<V, E>>;
+use num_traits::Zero;
+
+use super::WeightedGraph;
+
+/// The error returned when the graph has a cycle of negative weight, and
+/// shortest paths are not defined. `cycle` lists the vertices of one such
+/// cycle in the order of its edges: `cycle[i] -> cycle[i + 1]`, and the last
+/// vertex goes back to the first one.
+///
+/// For `bellman_ford`, the cycle is reachable from the start, and `unbounded`
+/// lists in increasing order every vertex whose distance is -∞, i.e. that can
+/// be reached from such a cycle. `johnson` has no start and leaves it empty.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NegativeCycle<V> {
+    pub cycle: Vec<V>,
+    pub unbounded: Vec<V>,
+}
+
+impl<V: Debug> Display for NegativeCycle<V> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "the graph has a cycle of negative weight through {:?}",
+            self.cycle
+        )
+    }
+}
+
+impl<V: Debug> std::error::Error for NegativeCycle<V> {}
+
+// Finds a negative cycle from the predecessors left by Bellman-Ford, given a
+// vertex whose distance decreased in the n-th round: going back n times from
+// it always ends on the cycle. Returns its vertices in the order of its edges.
+pub(crate) fn negative_cycle(pred: &[Option<usize>], mut v: usize) -> Vec<usize> {
+    for _ in 0..pred.len() {
+        v = pred[v].unwrap();
+    }
+    let mut cycle = vec![v];
+    let mut u = pred[v].unwrap();
+    while u != v {
+        cycle.push(u);
+        u = pred[u].unwrap();
+    }
+    cycle.reverse();
+    cycle
+}
+
+// for each reachable vertex, its predecessor and distance; None for the start
+type Distances<V, E> = BTreeMap<V, Option<(V, E)>>;
 
 // performs the Bellman-Ford algorithm on the given graph from the given start
-// the graph is an undirected graph
+// the graph is any `WeightedGraph`, its edges may have negative weights
 //
-// if there is a negative weighted loop it returns None
+// if there is a negative weighted loop reachable from start, it returns it in the error
 // else it returns a map that for each reachable vertex associates the distance and the predecessor
 // since the start has no predecessor but is reachable, map[start] will be None
 pub fn bellman_ford<
     V: Ord + Copy,
-    E: Ord + Copy + Add<Output = E> + Neg<Output = E> + std::ops::Sub<Output = E>,
+    E: Ord + Copy + Add<Output = E> + Neg<Output = E> + std::ops::Sub<Output = E> + Zero,
 >(
-    graph: &Graph<V, E>,
+    graph: &impl WeightedGraph<Vertex = V, Weight = E>,
     start: &V,
-) -> Option<BTreeMap<V, Option<(V, E)>>> {
-    let mut ans: BTreeMap<V, Option<(V, E)>> = BTreeMap::new();
-
+) -> Result<Distances<V, E>, NegativeCycle<V>> {
+    let vertices: Vec<V> = graph.vertices().collect();
+    let index: BTreeMap<V, usize> = vertices.iter().enumerate().map(|(i, &v)| (v, i)).collect();
+    let edges: Vec<(usize, usize, E)> = graph
+        .edges()
+        .map(|(u, v, d)| (index[&u], index[&v], d))
+        .collect();
+    let mut ans = BTreeMap::new();
     ans.insert(*start, None);
-
-    for _ in 1..(graph.len()) {
-        for (u, edges) in graph {
-            let dist_u = match ans.get(u) {
-                Some(Some((_, d))) => Some(*d),
-                Some(None) => None,
-                None => continue,
-            };
-
-            for (v, d) in edges {
-                match ans.get(v) {
-                    Some(Some((_, dist)))
-                        // if this is a longer path, do nothing
-                        if match dist_u {
-                            Some(dist_u) => dist_u + *d >= *dist,
-                            None => d >= dist,
-                        } => {}
-                    Some(None) => {
-                        match dist_u {
-                            // if dist_u + d < 0 there is a negative loop going by start
-                            // else it's just a longer path
-                            Some(dist_u) if dist_u >= -*d => {}
-                            // negative self edge or negative loop
-                            _ => {
-                                if *d > *d + *d {
-                                    return None;
-                                }
-                            }
-                        };
-                    }
-                    // it's a shorter path: either dist_v was infinite or it was longer than dist_u + d
-                    _ => {
-                        ans.insert(
-                            *v,
-                            Some((
-                                *u,
-                                match dist_u {
-                                    Some(dist) => dist + *d,
-                                    None => *d,
-                                },
-                            )),
-                        );
-                    }
+    let (Some(&start), false) = (index.get(start), edges.is_empty()) else {
+        // without edges, only start can be reached
+        return Ok(ans);
+    };
+
+    let n = vertices.len();
+    let mut dist: Vec<Option<E>> = vec![None; n];
+    let mut pred = vec![None; n];
+    dist[start] = Some(E::zero());
+
+    // relaxes every edge once, returns the vertices whose distance decreased
+    let mut relax_all = |dist: &mut Vec<Option<E>>| {
+        let mut changed = vec![];
+        for &(u, v, d) in &edges {
+            if let Some(dist_u) = dist[u] {
+                if dist[v].is_none_or(|dist_v| dist_u + d < dist_v) {
+                    dist[v] = Some(dist_u + d);
+                    pred[v] = Some(u);
+                    changed.push(v);
                 }
             }
         }
+        changed
+    };
+
+    for _ in 1..n {
+        if relax_all(&mut dist).is_empty() {
+            break;
+        }
     }
 
-    for (u, edges) in graph {
-        for (v, d) in edges {
-            match (ans.get(u), ans.get(v)) {
-                (Some(None), Some(None)) if *d > *d + *d => return None,
-                (Some(None), Some(Some((_, dv)))) if d < dv => return None,
-                (Some(Some((_, du))), Some(None)) if *du < -*d => return None,
-                (Some(Some((_, du))), Some(Some((_, dv)))) if *du + *d < *dv => return None,
-                (_, _) => {}
+    // any distance that can still decrease is -∞
+    let changed = relax_all(&mut dist);
+    if let Some(&last) = changed.last() {
+        let cycle = negative_cycle(&pred, last)
+            .into_iter()
+            .map(|v| vertices[v])
+            .collect();
+
+        let mut unbounded = vec![false; n];
+        let mut queue = VecDeque::new();
+        for v in changed {
+            if !unbounded[v] {
+                unbounded[v] = true;
+                queue.push_back(v);
             }
         }
+        while let Some(u) = queue.pop_front() {
+            for (v, _) in graph.neighbors(vertices[u]) {
+                let v = index[&v];
+                if !unbounded[v] {
+                    unbounded[v] = true;
+                    queue.push_back(v);
+                }
+            }
+        }
+        return Err(NegativeCycle {
+            cycle,
+            unbounded: (0..n)
+                .filter(|&v| unbounded[v])
+                .map(|v| vertices[v])
+                .collect(),
+        });
     }
 
-    Some(ans)
+    for v in 0..n {
+        if let (Some(u), Some(dist_v)) = (pred[v], dist[v]) {
+            if v != start {
+                ans.insert(vertices[v], Some((vertices[u], dist_v)));
+            }
+        }
+    }
+    Ok(ans)
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{bellman_ford, Graph};
+    use super::*;
     use std::collections::BTreeMap;
 
+    type Graph<V, E> = BTreeMap<V, BTreeMap<V, E>>;
+
     fn add_edge<V: Ord + Copy, E: Ord>(graph: &mut Graph<V, E>, v1: V, v2: V, c: E) {
         graph.entry(v1).or_default().insert(v2, c);
         graph.entry(v2).or_default();
@@ -102,7 +170,7 @@ mod tests {
         let mut dists = BTreeMap::new();
         dists.insert(0, None);
 
-        assert_eq!(bellman_ford(&graph, &0), Some(dists));
+        assert_eq!(bellman_ford(&graph, &0), Ok(dists));
     }
 
     #[test]
@@ -114,12 +182,25 @@ mod tests {
         dists_0.insert(0, None);
         dists_0.insert(1, Some((0, 2)));
 
-        assert_eq!(bellman_ford(&graph, &0), Some(dists_0));
+        assert_eq!(bellman_ford(&graph, &0), Ok(dists_0));
 
         let mut dists_1 = BTreeMap::new();
         dists_1.insert(1, None);
 
-        assert_eq!(bellman_ford(&graph, &1), Some(dists_1));
+        assert_eq!(bellman_ford(&graph, &1), Ok(dists_1));
+    }
+
+    #[test]
+    fn smallest_weight() {
+        let mut graph = BTreeMap::new();
+        add_edge(&mut graph, 0, 1, i32::MIN);
+        add_edge(&mut graph, 1, 2, 5);
+
+        let mut dists = BTreeMap::new();
+        dists.insert(0, None);
+        dists.insert(1, Some((0, i32::MIN)));
+        dists.insert(2, Some((1, i32::MIN + 5)));
+        assert_eq!(bellman_ford(&graph, &0), Ok(dists));
     }
 
     #[test]
@@ -143,7 +224,7 @@ mod tests {
             }
         }
 
-        assert_eq!(bellman_ford(&graph, &1), Some(dists));
+        assert_eq!(bellman_ford(&graph, &1), Ok(dists));
     }
 
     #[test]
@@ -161,25 +242,25 @@ mod tests {
         dists_a.insert('c', Some(('a', 12)));
         dists_a.insert('d', Some(('c', 44)));
         dists_a.insert('b', Some(('c', 32)));
-        assert_eq!(bellman_ford(&graph, &'a'), Some(dists_a));
+        assert_eq!(bellman_ford(&graph, &'a'), Ok(dists_a));
 
         let mut dists_b = BTreeMap::new();
         dists_b.insert('b', None);
         dists_b.insert('a', Some(('b', 10)));
         dists_b.insert('c', Some(('a', 22)));
         dists_b.insert('d', Some(('c', 54)));
-        assert_eq!(bellman_ford(&graph, &'b'), Some(dists_b));
+        assert_eq!(bellman_ford(&graph, &'b'), Ok(dists_b));
 
         let mut dists_c = BTreeMap::new();
         dists_c.insert('c', None);
         dists_c.insert('b', Some(('c', 20)));
         dists_c.insert('d', Some(('c', 32)));
         dists_c.insert('a', Some(('b', 30)));
-        assert_eq!(bellman_ford(&graph, &'c'), Some(dists_c));
+        assert_eq!(bellman_ford(&graph, &'c'), Ok(dists_c));
 
         let mut dists_d = BTreeMap::new();
         dists_d.insert('d', None);
-        assert_eq!(bellman_ford(&graph, &'d'), Some(dists_d));
+        assert_eq!(bellman_ford(&graph, &'d'), Ok(dists_d));
 
         let mut dists_e = BTreeMap::new();
         dists_e.insert('e', None);
@@ -187,7 +268,7 @@ mod tests {
         dists_e.insert('c', Some(('a', 19)));
         dists_e.insert('d', Some(('c', 51)));
         dists_e.insert('b', Some(('c', 39)));
-        assert_eq!(bellman_ford(&graph, &'e'), Some(dists_e));
+        assert_eq!(bellman_ford(&graph, &'e'), Ok(dists_e));
     }
 
     #[test]
@@ -210,7 +291,7 @@ mod tests {
         dists_0.insert(2, Some((3, 4)));
         dists_0.insert(3, Some((0, 7)));
         dists_0.insert(4, Some((1, -2)));
-        assert_eq!(bellman_ford(&graph, &0), Some(dists_0));
+        assert_eq!(bellman_ford(&graph, &0), Ok(dists_0));
 
         let mut dists_1 = BTreeMap::new();
         dists_1.insert(0, Some((4, -1)));
@@ -218,7 +299,7 @@ mod tests {
         dists_1.insert(2, Some((4, 3)));
         dists_1.insert(3, Some((0, 6)));
         dists_1.insert(4, Some((1, -4)));
-        assert_eq!(bellman_ford(&graph, &1), Some(dists_1));
+        assert_eq!(bellman_ford(&graph, &1), Ok(dists_1));
 
         let mut dists_2 = BTreeMap::new();
         dists_2.insert(0, Some((4, -3)));
@@ -226,7 +307,7 @@ mod tests {
         dists_2.insert(2, None);
         dists_2.insert(3, Some((0, 4)));
         dists_2.insert(4, Some((1, -6)));
-        assert_eq!(bellman_ford(&graph, &2), Some(dists_2));
+        assert_eq!(bellman_ford(&graph, &2), Ok(dists_2));
 
         let mut dists_3 = BTreeMap::new();
         dists_3.insert(0, Some((4, -6)));
@@ -234,7 +315,7 @@ mod tests {
         dists_3.insert(2, Some((3, -3)));
         dists_3.insert(3, None);
         dists_3.insert(4, Some((1, -9)));
-        assert_eq!(bellman_ford(&graph, &3), Some(dists_3));
+        assert_eq!(bellman_ford(&graph, &3), Ok(dists_3));
 
         let mut dists_4 = BTreeMap::new();
         dists_4.insert(0, Some((4, 3)));
@@ -242,7 +323,7 @@ mod tests {
         dists_4.insert(2, Some((4, 7)));
         dists_4.insert(3, Some((0, 10)));
         dists_4.insert(4, None);
-        assert_eq!(bellman_ford(&graph, &4), Some(dists_4));
+        assert_eq!(bellman_ford(&graph, &4), Ok(dists_4));
     }
 
     #[test]
@@ -259,10 +340,52 @@ mod tests {
         add_edge(&mut graph, 4, 0, 3);
         add_edge(&mut graph, 4, 2, 7);
 
-        assert_eq!(bellman_ford(&graph, &0), None);
-        assert_eq!(bellman_ford(&graph, &1), None);
-        assert_eq!(bellman_ford(&graph, &2), None);
-        assert_eq!(bellman_ford(&graph, &3), None);
-        assert_eq!(bellman_ford(&graph, &4), None);
+        for start in 0..5 {
+            let err = bellman_ford(&graph, &start).unwrap_err();
+            assert_eq!(err.unbounded, vec![0, 1, 2, 3, 4]);
+            assert_eq!(cycle_weight(&graph, &err.cycle), -1);
+        }
+    }
+
+    // the weight of a cycle given by its vertices, in order
+    fn cycle_weight(graph: &Graph<i32, i32>, cycle: &[i32]) -> i32 {
+        (0..cycle.len())
+            .map(|i| graph[&cycle[i]][&cycle[(i + 1) % cycle.len()]])
+            .sum()
+    }
+
+    #[test]
+    fn arbitrage() {
+        // exchange rates as -log(rate) in thousandths: a loop of trades that
+        // ends with more than it started with is a negative cycle
+        let mut graph = BTreeMap::new();
+        add_edge(&mut graph, 0, 1, -100);
+        add_edge(&mut graph, 1, 2, 60);
+        add_edge(&mut graph, 2, 0, 30);
+        add_edge(&mut graph, 2, 3, 5);
+        add_edge(&mut graph, 3, 4, 5);
+        add_edge(&mut graph, 5, 0, 1);
+
+        let err = bellman_ford(&graph, &5).unwrap_err();
+        let mut cycle = err.cycle.clone();
+        let first = cycle.iter().position(|&v| v == 0).unwrap();
+        cycle.rotate_left(first);
+        assert_eq!(cycle, vec![0, 1, 2]);
+        assert_eq!(cycle_weight(&graph, &err.cycle), -10);
+        // 5 leads to the cycle but is not reachable from it
+        assert_eq!(err.unbounded, vec![0, 1, 2, 3, 4]);
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "the graph has a cycle of negative weight through {:?}",
+                err.cycle
+            )
+        );
+
+        // the cycle can't be reached from 3
+        let mut dists_3 = BTreeMap::new();
+        dists_3.insert(3, None);
+        dists_3.insert(4, Some((3, 5)));
+        assert_eq!(bellman_ford(&graph, &3), Ok(dists_3));
     }
 }
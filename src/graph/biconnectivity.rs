@@ -0,0 +1,405 @@
+/*
+Bridges, articulation points and biconnected components of an undirected graph:
+A bridge is an edge, and an articulation point (or cut vertex) a vertex, whose
+removal disconnects its connected component: they are the single points of
+failure of a network.
+
+Tarjan's lowlink algorithm finds them all with one DFS. tin[v] is the time v
+is discovered, and low[v] the smallest tin reachable from the subtree of v
+using at most one back edge. For a tree edge v -> to:
+- it is a bridge if low[to] > tin[v]: nothing below it goes back above it.
+- v is an articulation point if low[to] >= tin[v], unless v is the root,
+  which is one if it has several children in the DFS tree.
+The biconnected components (blocks) are the maximal sets of vertices that
+stay connected after the removal of any single vertex; they overlap on the
+articulation points. The 2-edge-connected components are the connected
+components left once the bridges are removed.
+
+The block-cut tree has a node for every block and every articulation point,
+and links each articulation point to the blocks it belongs to.
+
+Complexity: O(V + E). The graph is undirected: every edge must be stored in
+both directions. Parallel edges are allowed, and self-loops are ignored.
+*/
+
+use std::collections::BTreeMap;
+
+use super::weighted_graph::index_graph;
+use super::WeightedGraph;
+
+/// A node of the block-cut tree: the `i`-th block of `biconnected_components`,
+/// or an articulation point.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum BlockCutNode<V> {
+    Block(usize),
+    Cut(V),
+}
+
+pub struct Biconnectivity<V> {
+    // The bridges (u, v) with u < v, sorted
+    pub bridges: Vec<(V, V)>,
+    // The articulation points, sorted
+    pub articulation_points: Vec<V>,
+    // The vertices of each block, sorted. An isolated vertex is a block on its own.
+    pub biconnected_components: Vec<Vec<V>>,
+    // The vertices of each 2-edge-connected component, sorted
+    pub two_edge_connected_components: Vec<Vec<V>>,
+}
+
+// The state of the DFS, over vertex numbers
+struct Lowlink<'a> {
+    adj: &'a [Vec<usize>],
+    tin: Vec<Option<usize>>,
+    low: Vec<usize>,
+    timer: usize,
+    stack: Vec<usize>,
+    is_cut: Vec<bool>,
+    bridges: Vec<(usize, usize)>,
+    blocks: Vec<Vec<usize>>,
+}
+
+impl Lowlink<'_> {
+    fn dfs(&mut self, v: usize, parent: Option<usize>) {
+        self.tin[v] = Some(self.timer);
+        self.low[v] = self.timer;
+        self.timer += 1;
+        self.stack.push(v);
+        let mut children = 0;
+        // only one copy of the edge to the parent is the tree edge
+        let mut skipped_parent = false;
+        for &to in &self.adj[v] {
+            if to == v {
+                continue;
+            }
+            if Some(to) == parent && !skipped_parent {
+                skipped_parent = true;
+                continue;
+            }
+            if let Some(tin_to) = self.tin[to] {
+                self.low[v] = self.low[v].min(tin_to);
+                continue;
+            }
+            self.dfs(to, Some(v));
+            children += 1;
+            self.low[v] = self.low[v].min(self.low[to]);
+            let tin_v = self.tin[v].unwrap();
+            if self.low[to] > tin_v {
+                self.bridges.push((v, to));
+            }
+            if self.low[to] >= tin_v {
+                // v separates the subtree of to (still on the stack) from the rest
+                if parent.is_some() {
+                    self.is_cut[v] = true;
+                }
+                let mut block = vec![v];
+                loop {
+                    let u = self.stack.pop().unwrap();
+                    block.push(u);
+                    if u == to {
+                        break;
+                    }
+                }
+                self.blocks.push(block);
+            }
+        }
+        if parent.is_none() {
+            self.is_cut[v] = children > 1;
+            self.stack.pop();
+            if children == 0 {
+                self.blocks.push(vec![v]);
+            }
+        }
+    }
+}
+
+impl<V: Ord + Copy> Biconnectivity<V> {
+    pub fn new(graph: &impl WeightedGraph<Vertex = V>) -> Self {
+        let (vertices, adj) = index_graph(graph);
+        let n = vertices.len();
+        let mut state = Lowlink {
+            adj: &adj,
+            tin: vec![None; n],
+            low: vec![0; n],
+            timer: 0,
+            stack: vec![],
+            is_cut: vec![false; n],
+            bridges: vec![],
+            blocks: vec![],
+        };
+        for v in 0..n {
+            if state.tin[v].is_none() {
+                state.dfs(v, None);
+            }
+        }
+
+        // the components of the graph without its bridges
+        let mut is_bridge = vec![vec![]; n];
+        for &(u, v) in &state.bridges {
+            is_bridge[u].push(v);
+            is_bridge[v].push(u);
+        }
+        let mut component = vec![None; n];
+        let mut two_edge_connected_components = vec![];
+        for root in 0..n {
+            if component[root].is_some() {
+                continue;
+            }
+            component[root] = Some(two_edge_connected_components.len());
+            let mut members = vec![root];
+            let mut i = 0;
+            while i < members.len() {
+                let u = members[i];
+                i += 1;
+                for &v in &adj[u] {
+                    if component[v].is_none() && !is_bridge[u].contains(&v) {
+                        component[v] = Some(two_edge_connected_components.len());
+                        members.push(v);
+                    }
+                }
+            }
+            two_edge_connected_components.push(members);
+        }
+
+        let to_vertices = |sets: Vec<Vec<usize>>| {
+            let mut sets: Vec<Vec<V>> = sets
+                .into_iter()
+                .map(|set| {
+                    let mut set: Vec<V> = set.into_iter().map(|v| vertices[v]).collect();
+                    set.sort();
+                    set
+                })
+                .collect();
+            sets.sort();
+            sets
+        };
+        let mut bridges: Vec<(V, V)> = state
+            .bridges
+            .iter()
+            .map(|&(u, v)| {
+                let (u, v) = (vertices[u], vertices[v]);
+                (u.min(v), u.max(v))
+            })
+            .collect();
+        bridges.sort();
+        let mut articulation_points: Vec<V> = (0..n)
+            .filter(|&v| state.is_cut[v])
+            .map(|v| vertices[v])
+            .collect();
+        articulation_points.sort();
+
+        Biconnectivity {
+            bridges,
+            articulation_points,
+            biconnected_components: to_vertices(state.blocks),
+            two_edge_connected_components: to_vertices(two_edge_connected_components),
+        }
+    }
+
+    // The block-cut tree (a forest if the graph is not connected), with every edge
+    // stored in both directions
+    pub fn block_cut_tree(&self) -> BTreeMap<BlockCutNode<V>, Vec<BlockCutNode<V>>> {
+        let mut tree = BTreeMap::new();
+        for &v in &self.articulation_points {
+            tree.insert(BlockCutNode::Cut(v), vec![]);
+        }
+        for (i, block) in self.biconnected_components.iter().enumerate() {
+            let mut cuts = vec![];
+            for v in block {
+                if let Some(neighbors) = tree.get_mut(&BlockCutNode::Cut(*v)) {
+                    neighbors.push(BlockCutNode::Block(i));
+                    cuts.push(BlockCutNode::Cut(*v));
+                }
+            }
+            tree.insert(BlockCutNode::Block(i), cuts);
+        }
+        tree
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    fn undirected(n: usize, edges: &[(usize, usize)]) -> Vec<Vec<usize>> {
+        let mut adj = vec![vec![]; n];
+        for &(u, v) in edges {
+            adj[u].push(v);
+            adj[v].push(u);
+        }
+        adj
+    }
+
+    #[test]
+    fn two_triangles_and_a_tail() {
+        // 0-1-2 and 3-4-5 are triangles joined by the bridge 2-3, and 5-6 is a tail
+        let adj = undirected(
+            7,
+            &[
+                (0, 1),
+                (1, 2),
+                (2, 0),
+                (2, 3),
+                (3, 4),
+                (4, 5),
+                (5, 3),
+                (5, 6),
+            ],
+        );
+        let bc = Biconnectivity::new(&adj);
+        assert_eq!(bc.bridges, vec![(2, 3), (5, 6)]);
+        assert_eq!(bc.articulation_points, vec![2, 3, 5]);
+        assert_eq!(
+            bc.biconnected_components,
+            vec![vec![0, 1, 2], vec![2, 3], vec![3, 4, 5], vec![5, 6]]
+        );
+        assert_eq!(
+            bc.two_edge_connected_components,
+            vec![vec![0, 1, 2], vec![3, 4, 5], vec![6]]
+        );
+
+        use BlockCutNode::*;
+        let tree = bc.block_cut_tree();
+        assert_eq!(tree[&Cut(2)], vec![Block(0), Block(1)]);
+        assert_eq!(tree[&Cut(5)], vec![Block(2), Block(3)]);
+        assert_eq!(tree[&Block(1)], vec![Cut(2), Cut(3)]);
+        assert_eq!(tree[&Block(3)], vec![Cut(5)]);
+        assert_eq!(tree.len(), 7);
+    }
+
+    #[test]
+    fn parallel_edges_and_isolated_vertices() {
+        // the double edge 0-1 is not a bridge, 3 is isolated and 4 has a self-loop
+        let adj = undirected(5, &[(0, 1), (0, 1), (1, 2), (4, 4)]);
+        let bc = Biconnectivity::new(&adj);
+        assert_eq!(bc.bridges, vec![(1, 2)]);
+        assert_eq!(bc.articulation_points, vec![1]);
+        assert_eq!(
+            bc.biconnected_components,
+            vec![vec![0, 1], vec![1, 2], vec![3], vec![4]]
+        );
+        assert_eq!(
+            bc.two_edge_connected_components,
+            vec![vec![0, 1], vec![2], vec![3], vec![4]]
+        );
+    }
+
+    #[test]
+    fn map_graph() {
+        let mut graph: BTreeMap<&str, BTreeMap<&str, ()>> = BTreeMap::new();
+        for (u, v) in [
+            ("router", "a"),
+            ("router", "b"),
+            ("a", "b"),
+            ("b", "server"),
+        ] {
+            graph.entry(u).or_default().insert(v, ());
+            graph.entry(v).or_default().insert(u, ());
+        }
+        let bc = Biconnectivity::new(&graph);
+        assert_eq!(bc.bridges, vec![("b", "server")]);
+        assert_eq!(bc.articulation_points, vec!["b"]);
+    }
+
+    // The number of connected components, ignoring a vertex and an edge
+    fn count_components(
+        adj: &[Vec<usize>],
+        removed_vertex: Option<usize>,
+        removed_edge: Option<(usize, usize)>,
+    ) -> usize {
+        let n = adj.len();
+        let mut seen = vec![false; n];
+        let mut count = 0;
+        for root in 0..n {
+            if seen[root] || Some(root) == removed_vertex {
+                continue;
+            }
+            count += 1;
+            seen[root] = true;
+            let mut stack = vec![root];
+            while let Some(u) = stack.pop() {
+                let mut skipped = false;
+                for &v in &adj[u] {
+                    let is_removed = removed_edge == Some((u, v)) || removed_edge == Some((v, u));
+                    if is_removed && !skipped {
+                        skipped = true;
+                        continue;
+                    }
+                    if !seen[v] && Some(v) != removed_vertex {
+                        seen[v] = true;
+                        stack.push(v);
+                    }
+                }
+            }
+        }
+        count
+    }
+
+    #[test]
+    fn random_graphs_match_brute_force() {
+        let mut rng = StdRng::seed_from_u64(16);
+        for _ in 0..300 {
+            let n = rng.gen_range(1..=10);
+            let edges: Vec<(usize, usize)> = (0..rng.gen_range(0..=n + 4))
+                .map(|_| (rng.gen_range(0..n), rng.gen_range(0..n)))
+                .filter(|&(u, v)| u != v)
+                .collect();
+            let adj = undirected(n, &edges);
+            let bc = Biconnectivity::new(&adj);
+            let components = count_components(&adj, None, None);
+
+            let mut bridges: Vec<(usize, usize)> = edges
+                .iter()
+                .filter(|&&e| count_components(&adj, None, Some(e)) > components)
+                .map(|&(u, v)| (u.min(v), u.max(v)))
+                .collect();
+            bridges.sort();
+            bridges.dedup();
+            assert_eq!(bc.bridges, bridges);
+
+            let cuts: Vec<usize> = (0..n)
+                .filter(|&v| {
+                    let alone = adj[v].is_empty() as usize;
+                    count_components(&adj, Some(v), None) + alone > components
+                })
+                .collect();
+            assert_eq!(bc.articulation_points, cuts);
+
+            // every edge is in exactly one block, and every vertex in one block
+            // unless it is an articulation point
+            for &(u, v) in &edges {
+                let blocks = bc
+                    .biconnected_components
+                    .iter()
+                    .filter(|b| b.contains(&u) && b.contains(&v))
+                    .count();
+                assert_eq!(blocks, 1);
+            }
+            for v in 0..n {
+                let blocks = bc
+                    .biconnected_components
+                    .iter()
+                    .filter(|b| b.contains(&v))
+                    .count();
+                assert_eq!(blocks > 1, cuts.contains(&v));
+            }
+
+            // the block-cut tree is a forest with one tree per component
+            let tree = bc.block_cut_tree();
+            let tree_edges: usize = tree.values().map(Vec::len).sum::<usize>() / 2;
+            assert_eq!(tree.len() - tree_edges, components);
+
+            // u and v are 2-edge-connected iff no single edge removal separates them
+            for component in &bc.two_edge_connected_components {
+                for &(u, v) in &bridges {
+                    assert!(!(component.contains(&u) && component.contains(&v)));
+                }
+            }
+            assert_eq!(
+                bc.two_edge_connected_components.len(),
+                components + bridges.len()
+            );
+        }
+    }
+}
@@ -0,0 +1,252 @@
+/*
+Bidirectional Dijkstra:
+Answers point-to-point shortest path queries by running two Dijkstra searches
+at the same time, one forward from the start and one backward (on the reversed
+edges) from the target. Each step advances the search whose next vertex is the
+closest. Every time a vertex gets a label from both searches, the path through
+it is a candidate; once the two closest unsettled vertices are farther apart
+than the best candidate, it can't be improved anymore.
+
+Both searches explore a ball of about half the radius of a single search, which
+is far fewer vertices on road-like graphs.
+
+The graph is indexed once by `new`, so that queries don't pay for building the
+reversed graph. Weights must not be negative.
+*/
+
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BinaryHeap, HashMap, HashSet};
+use std::ops::Add;
+
+use num_traits::Zero;
+
+use super::WeightedGraph;
+
+// adjacency lists over the vertex numbers
+pub(crate) type IndexedGraph<E> = Vec<Vec<(usize, E)>>;
+
+pub struct BidirectionalDijkstra<V, E> {
+    vertices: Vec<V>,
+    index: BTreeMap<V, usize>,
+    forward: IndexedGraph<E>,
+    backward: IndexedGraph<E>,
+}
+
+impl<V, E> BidirectionalDijkstra<V, E>
+where
+    V: Ord + Copy,
+    E: Ord + Copy + Add<Output = E> + Zero,
+{
+    pub fn new(graph: &impl WeightedGraph<Vertex = V, Weight = E>) -> Self {
+        let vertices: Vec<V> = graph.vertices().collect();
+        let index: BTreeMap<V, usize> = vertices.iter().enumerate().map(|(i, &v)| (v, i)).collect();
+        let mut forward = vec![vec![]; vertices.len()];
+        let mut backward = vec![vec![]; vertices.len()];
+        for (u, v, weight) in graph.edges() {
+            forward[index[&u]].push((index[&v], weight));
+            backward[index[&v]].push((index[&u], weight));
+        }
+        BidirectionalDijkstra {
+            vertices,
+            index,
+            forward,
+            backward,
+        }
+    }
+
+    // returns the distance from start to target and the vertices of a shortest path,
+    // or None if target can't be reached
+    pub fn query(&self, start: V, target: V) -> Option<(E, Vec<V>)> {
+        let (start, target) = (*self.index.get(&start)?, *self.index.get(&target)?);
+        let (dist, path) = bidirectional_search(
+            &self.forward,
+            &self.backward,
+            start,
+            target,
+            StoppingRule::SumOfFronts,
+        )?;
+        Some((dist, path.into_iter().map(|v| self.vertices[v]).collect()))
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum StoppingRule {
+    // stop when the two closest unsettled vertices are farther apart than the best path
+    SumOfFronts,
+    // run each search until its closest unsettled vertex is farther than the best path,
+    // needed when the searches don't see the whole graph (contraction hierarchies)
+    EachFront,
+}
+
+// a search from one side: the distance and predecessor of every labelled vertex
+struct Search<E> {
+    labels: HashMap<usize, (E, Option<usize>)>,
+    queue: BinaryHeap<Reverse<(E, usize)>>,
+    settled: HashSet<usize>,
+}
+
+impl<E: Ord + Copy> Search<E> {
+    fn new(source: usize, zero: E) -> Self {
+        Search {
+            labels: HashMap::from([(source, (zero, None))]),
+            queue: BinaryHeap::from([Reverse((zero, source))]),
+            settled: HashSet::new(),
+        }
+    }
+
+    // the distance of the closest unsettled vertex, dropping outdated queue entries
+    fn front(&mut self) -> Option<E> {
+        while let Some(&Reverse((dist, v))) = self.queue.peek() {
+            if !self.settled.contains(&v) {
+                return Some(dist);
+            }
+            self.queue.pop();
+        }
+        None
+    }
+
+    // the vertices from the source to v, following the predecessors
+    fn path_to(&self, mut v: usize) -> Vec<usize> {
+        let mut path = vec![v];
+        while let Some(prev) = self.labels[&v].1 {
+            path.push(prev);
+            v = prev;
+        }
+        path.reverse();
+        path
+    }
+}
+
+// Finds a shortest path from start to target over vertex numbers, searching forward on
+// `forward` and backward on `backward`, where `backward[v]` lists the edges u -> v as (u, w).
+// The path is returned as the vertices of the edges used, which belong to either graph.
+pub(crate) fn bidirectional_search<E>(
+    forward: &[Vec<(usize, E)>],
+    backward: &[Vec<(usize, E)>],
+    start: usize,
+    target: usize,
+    rule: StoppingRule,
+) -> Option<(E, Vec<usize>)>
+where
+    E: Ord + Copy + Add<Output = E> + Zero,
+{
+    let mut searches = [
+        Search::new(start, E::zero()),
+        Search::new(target, E::zero()),
+    ];
+    let graphs = [forward, backward];
+    // the length of the best path found and the vertex where its two halves meet
+    let mut best: Option<(E, usize)> = if start == target {
+        Some((E::zero(), start))
+    } else {
+        None
+    };
+
+    loop {
+        let fronts = [searches[0].front(), searches[1].front()];
+        let is_open =
+            |front: Option<E>| front.is_some_and(|front| best.is_none_or(|(best, _)| front < best));
+        let side = match rule {
+            StoppingRule::SumOfFronts => match fronts {
+                [Some(f), Some(b)] if best.is_none_or(|(best, _)| f + b < best) => {
+                    usize::from(b < f)
+                }
+                // once a search has settled every vertex it can reach, its last edges
+                // have been checked against the labels of the other one
+                _ => break,
+            },
+            StoppingRule::EachFront => match (is_open(fronts[0]), is_open(fronts[1])) {
+                (false, false) => break,
+                (true, true) => usize::from(fronts[1] < fronts[0]),
+                (open, _) => usize::from(!open),
+            },
+        };
+
+        let Reverse((dist, vertex)) = searches[side].queue.pop().unwrap();
+        searches[side].settled.insert(vertex);
+        for &(next, weight) in &graphs[side][vertex] {
+            let new_dist = dist + weight;
+            if searches[side]
+                .labels
+                .get(&next)
+                .is_some_and(|&(old, _)| old <= new_dist)
+            {
+                continue;
+            }
+            searches[side].labels.insert(next, (new_dist, Some(vertex)));
+            searches[side].queue.push(Reverse((new_dist, next)));
+            if let Some(&(other, _)) = searches[1 - side].labels.get(&next) {
+                if best.is_none_or(|(best, _)| new_dist + other < best) {
+                    best = Some((new_dist + other, next));
+                }
+            }
+        }
+    }
+
+    let (dist, meeting) = best?;
+    let mut path = searches[0].path_to(meeting);
+    let mut second_half = searches[1].path_to(meeting);
+    second_half.pop();
+    path.extend(second_half.into_iter().rev());
+    Some((dist, path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::dijkstra;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    type Graph<V, E> = BTreeMap<V, BTreeMap<V, E>>;
+
+    fn add_edge<V: Ord + Copy, E: Ord>(graph: &mut Graph<V, E>, v1: V, v2: V, c: E) {
+        graph.entry(v1).or_default().insert(v2, c);
+        graph.entry(v2).or_default();
+    }
+
+    #[test]
+    fn small_graph() {
+        let mut graph = BTreeMap::new();
+        add_edge(&mut graph, 'a', 'c', 12);
+        add_edge(&mut graph, 'a', 'd', 60);
+        add_edge(&mut graph, 'b', 'a', 10);
+        add_edge(&mut graph, 'c', 'b', 20);
+        add_edge(&mut graph, 'c', 'd', 32);
+        add_edge(&mut graph, 'e', 'a', 7);
+        let bd = BidirectionalDijkstra::new(&graph);
+
+        assert_eq!(bd.query('e', 'd'), Some((51, vec!['e', 'a', 'c', 'd'])));
+        assert_eq!(bd.query('c', 'a'), Some((30, vec!['c', 'b', 'a'])));
+        assert_eq!(bd.query('b', 'b'), Some((0, vec!['b'])));
+        assert_eq!(bd.query('d', 'a'), None);
+        assert_eq!(bd.query('a', 'z'), None);
+    }
+
+    #[test]
+    fn random_graphs_match_dijkstra() {
+        let mut rng = StdRng::seed_from_u64(12);
+        for _ in 0..100 {
+            let n = rng.gen_range(1..=30);
+            let mut graph: Graph<usize, u32> = (0..n).map(|v| (v, BTreeMap::new())).collect();
+            for _ in 0..rng.gen_range(0..=n * 3) {
+                let (u, v) = (rng.gen_range(0..n), rng.gen_range(0..n));
+                add_edge(&mut graph, u, v, rng.gen_range(0..=20));
+            }
+            let bd = BidirectionalDijkstra::new(&graph);
+            for _ in 0..10 {
+                let (start, target) = (rng.gen_range(0..n), rng.gen_range(0..n));
+                let expected = dijkstra(&graph, start)
+                    .get(&target)
+                    .map(|e| e.map_or(0, |(_, w)| w));
+                let res = bd.query(start, target);
+                assert_eq!(res.as_ref().map(|(w, _)| *w), expected);
+                if let Some((weight, path)) = res {
+                    assert_eq!((path[0], *path.last().unwrap()), (start, target));
+                    let total: u32 = path.windows(2).map(|e| graph[&e[0]][&e[1]]).sum();
+                    assert_eq!(total, weight);
+                }
+            }
+        }
+    }
+}
@@ -50,12 +50,17 @@ impl BipartiteMatching {
             self.try_kuhn(v);
         }
     }
+    // Returns the matched pairs (vertex in grp1, vertex in grp2), ordered by
+    // the vertex in grp2. Works after either kuhn or hopcroft_karp
+    pub fn get_matching(&self) -> Vec<(usize, usize)> {
+        (1..self.num_vertices_grp2 + 1)
+            .filter(|&i| self.mt2[i] > 0)
+            .map(|i| (self.mt2[i] as usize, i))
+            .collect()
+    }
     pub fn print_matching(&self) {
-        for i in 1..self.num_vertices_grp2 + 1 {
-            if self.mt2[i] == -1 {
-                continue;
-            }
-            println!("Vertex {} in grp1 matched with {} grp2", self.mt2[i], i)
+        for (u, v) in self.get_matching() {
+            println!("Vertex {} in grp1 matched with {} grp2", u, v)
         }
     }
     fn bfs(&self, dist: &mut [i32]) -> bool {
@@ -267,4 +272,23 @@ mod tests {
             assert!(g.mt2[i] == -1);
         }
     }
+    #[test]
+    fn get_matching() {
+        let mut g = BipartiteMatching::new(3, 2);
+        g.add_edge(1, 1);
+        g.add_edge(2, 1);
+        g.add_edge(2, 2);
+        g.add_edge(3, 2);
+        assert!(g.get_matching().is_empty());
+        assert_eq!(g.hopcroft_karp(), 2);
+        let matching = g.get_matching();
+        assert_eq!(matching.len(), 2);
+        for &(u, v) in &matching {
+            assert!(g.adj[u].contains(&v));
+            assert_eq!(g.mt1[u], v as i32);
+        }
+
+        g.kuhn();
+        assert_eq!(g.get_matching(), vec![(1, 1), (2, 2)]);
+    }
 }
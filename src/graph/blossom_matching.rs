@@ -0,0 +1,279 @@
+/*
+Edmonds' blossom algorithm:
+Maximum cardinality matching in a general (not necessarily bipartite)
+undirected graph.
+
+Like Kuhn's algorithm, the matching is grown one augmenting path at a time,
+found with a BFS from an unmatched vertex that alternates between unmatched and
+matched edges. In a general graph this search can run into an odd cycle (a
+"blossom"); the cycle is then contracted into its base vertex and the search
+goes on, and an augmenting path found in the contracted graph can always be
+extended to one in the original graph.
+
+Complexity: O(V^3)
+
+Like `BipartiteMatching`, vertices are numbered from 1 to n and unmatched
+vertices have a mate of -1.
+*/
+use std::collections::VecDeque;
+type Graph = Vec<Vec<usize>>;
+
+pub struct BlossomMatching {
+    pub adj: Graph,
+    pub num_vertices: usize,
+    // mate[u] = v if the edge u-v is in the matching, -1 if u is unmatched
+    pub mate: Vec<i32>,
+    // base[v] is the base of the blossom that contains v, v itself if none
+    base: Vec<usize>,
+    // the vertex that reached v in the alternating tree of the current search
+    parent: Vec<Option<usize>>,
+    used: Vec<bool>,
+}
+
+impl BlossomMatching {
+    pub fn new(num_vertices: usize) -> Self {
+        BlossomMatching {
+            adj: vec![vec![]; num_vertices + 1],
+            num_vertices,
+            mate: vec![-1; num_vertices + 1],
+            base: vec![0; num_vertices + 1],
+            parent: vec![None; num_vertices + 1],
+            used: vec![false; num_vertices + 1],
+        }
+    }
+    #[inline]
+    // Add an undirected edge u-v in the graph
+    pub fn add_edge(&mut self, u: usize, v: usize) {
+        self.adj[u].push(v);
+        self.adj[v].push(u);
+    }
+
+    fn mate_of(&self, v: usize) -> Option<usize> {
+        usize::try_from(self.mate[v]).ok()
+    }
+
+    // The lowest common ancestor of the blossoms of a and b in the alternating tree
+    fn lca(&self, mut a: usize, mut b: usize) -> usize {
+        let mut on_path = vec![false; self.num_vertices + 1];
+        loop {
+            a = self.base[a];
+            on_path[a] = true;
+            match self.mate_of(a) {
+                None => break,
+                Some(m) => a = self.parent[m].unwrap(),
+            }
+        }
+        loop {
+            b = self.base[b];
+            if on_path[b] {
+                return b;
+            }
+            b = self.parent[self.mate_of(b).unwrap()].unwrap();
+        }
+    }
+
+    // Marks the blossoms on the path from v to the base b of the new blossom,
+    // making the path usable in the other direction
+    fn mark_path(&mut self, mut v: usize, b: usize, mut child: usize, blossom: &mut [bool]) {
+        while self.base[v] != b {
+            let m = self.mate_of(v).unwrap();
+            blossom[self.base[v]] = true;
+            blossom[self.base[m]] = true;
+            self.parent[v] = Some(child);
+            child = m;
+            v = self.parent[m].unwrap();
+        }
+    }
+
+    // Searches for an augmenting path from root, returning its other end
+    fn find_path(&mut self, root: usize) -> Option<usize> {
+        self.used.fill(false);
+        self.parent.fill(None);
+        for (v, base) in self.base.iter_mut().enumerate() {
+            *base = v;
+        }
+        self.used[root] = true;
+        let mut q = VecDeque::from([root]);
+        while let Some(v) = q.pop_front() {
+            for i in 0..self.adj[v].len() {
+                let to = self.adj[v][i];
+                if self.base[v] == self.base[to] || self.mate_of(v) == Some(to) {
+                    continue;
+                }
+                let to_is_outer =
+                    to == root || self.mate_of(to).is_some_and(|m| self.parent[m].is_some());
+                if to_is_outer {
+                    // v and to close an odd cycle: contract it
+                    let new_base = self.lca(v, to);
+                    let mut blossom = vec![false; self.num_vertices + 1];
+                    self.mark_path(v, new_base, to, &mut blossom);
+                    self.mark_path(to, new_base, v, &mut blossom);
+                    for u in 1..=self.num_vertices {
+                        if blossom[self.base[u]] {
+                            self.base[u] = new_base;
+                            if !self.used[u] {
+                                self.used[u] = true;
+                                q.push_back(u);
+                            }
+                        }
+                    }
+                } else if self.parent[to].is_none() {
+                    self.parent[to] = Some(v);
+                    match self.mate_of(to) {
+                        None => return Some(to),
+                        Some(m) => {
+                            self.used[m] = true;
+                            q.push_back(m);
+                        }
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    // Returns the size of a maximum matching, and stores it in self.mate
+    pub fn maximum_matching(&mut self) -> i32 {
+        self.mate = vec![-1; self.num_vertices + 1];
+        let mut res = 0;
+        for v in 1..=self.num_vertices {
+            if self.mate[v] != -1 {
+                continue;
+            }
+            let mut end = self.find_path(v);
+            if end.is_some() {
+                res += 1;
+            }
+            // Flip the edges along the augmenting path
+            while let Some(t) = end {
+                let pv = self.parent[t].unwrap();
+                let next = self.mate_of(pv);
+                self.mate[t] = pv as i32;
+                self.mate[pv] = t as i32;
+                end = next;
+            }
+        }
+        res
+    }
+
+    // Returns the matched pairs (u, v) with u < v, ordered by u
+    pub fn get_matching(&self) -> Vec<(usize, usize)> {
+        (1..=self.num_vertices)
+            .filter_map(|u| self.mate_of(u).filter(|&v| u < v).map(|v| (u, v)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    fn check_matching(g: &BlossomMatching, size: i32) {
+        let matching = g.get_matching();
+        assert_eq!(matching.len() as i32, size);
+        for &(u, v) in &matching {
+            assert!(g.adj[u].contains(&v));
+            assert_eq!(g.mate[u], v as i32);
+            assert_eq!(g.mate[v], u as i32);
+        }
+        let matched = g.mate.iter().filter(|&&m| m != -1).count();
+        assert_eq!(matched as i32, 2 * size);
+    }
+
+    #[test]
+    fn odd_cycle() {
+        // A triangle with a tail on each corner: Kuhn's algorithm would need
+        // the graph to be bipartite
+        let mut g = BlossomMatching::new(6);
+        g.add_edge(1, 2);
+        g.add_edge(2, 3);
+        g.add_edge(3, 1);
+        g.add_edge(1, 4);
+        g.add_edge(2, 5);
+        g.add_edge(3, 6);
+        let size = g.maximum_matching();
+        assert_eq!(size, 3);
+        check_matching(&g, size);
+        assert_eq!(g.get_matching(), vec![(1, 4), (2, 5), (3, 6)]);
+    }
+
+    #[test]
+    fn blossom_needed() {
+        // A tail 1-2 on the odd cycle 2-3-4-6-5, whose vertices 4, 5 and 6
+        // also form a triangle
+        let mut g = BlossomMatching::new(6);
+        for (u, v) in [(1, 2), (2, 3), (3, 4), (4, 5), (5, 2), (4, 6), (5, 6)] {
+            g.add_edge(u, v);
+        }
+        let size = g.maximum_matching();
+        assert_eq!(size, 3);
+        check_matching(&g, size);
+    }
+
+    #[test]
+    fn petersen_graph() {
+        let mut g = BlossomMatching::new(10);
+        for i in 0..5 {
+            g.add_edge(i + 1, (i + 1) % 5 + 1);
+            g.add_edge(i + 1, i + 6);
+            g.add_edge(i + 6, (i + 2) % 5 + 6);
+        }
+        let size = g.maximum_matching();
+        assert_eq!(size, 5);
+        check_matching(&g, size);
+    }
+
+    #[test]
+    fn empty_and_unmatched() {
+        let mut g = BlossomMatching::new(3);
+        assert_eq!(g.maximum_matching(), 0);
+        assert_eq!(g.mate, vec![-1; 4]);
+        assert!(g.get_matching().is_empty());
+    }
+
+    // The size of a maximum matching, trying every subset of edges
+    fn brute_force(n: usize, edges: &[(usize, usize)]) -> i32 {
+        let mut best = 0;
+        for mask in 0u32..1 << edges.len() {
+            let mut used = vec![false; n + 1];
+            let mut size = 0;
+            let mut valid = true;
+            for (i, &(u, v)) in edges.iter().enumerate() {
+                if mask & (1 << i) != 0 {
+                    valid &= !used[u] && !used[v];
+                    used[u] = true;
+                    used[v] = true;
+                    size += 1;
+                }
+            }
+            if valid {
+                best = best.max(size);
+            }
+        }
+        best
+    }
+
+    #[test]
+    fn random_graphs_match_brute_force() {
+        let mut rng = StdRng::seed_from_u64(8);
+        for _ in 0..300 {
+            let n = rng.gen_range(1..=9);
+            let mut edges = Vec::new();
+            for _ in 0..rng.gen_range(0..=12) {
+                let (u, v) = (rng.gen_range(1..=n), rng.gen_range(1..=n));
+                if u != v {
+                    edges.push((u, v));
+                }
+            }
+            let mut g = BlossomMatching::new(n);
+            for &(u, v) in &edges {
+                g.add_edge(u, v);
+            }
+            let size = g.maximum_matching();
+            assert_eq!(size, brute_force(n, &edges), "{edges:?}");
+            check_matching(&g, size);
+        }
+    }
+}
@@ -1,7 +1,10 @@
 use std::collections::HashSet;
 use std::collections::VecDeque;
+use std::hash::Hash;
 
-/// Perform a breadth-first search on Graph `graph`.
+use super::{CsrGraph, WeightedGraph};
+
+/// Perform a breadth-first search on any `WeightedGraph`, ignoring the weights.
 ///
 /// # Parameters
 ///
@@ -17,15 +20,19 @@ use std::collections::VecDeque;
 /// If the target is not found or there is no path from the root,
 /// `None` is returned.
 ///
-pub fn breadth_first_search(graph: &Graph, root: Node, target: Node) -> Option<Vec<u32>> {
-    let mut visited: HashSet<Node> = HashSet::new();
-    let mut history: Vec<u32> = Vec::new();
+pub fn breadth_first_search<V: Hash + Eq + Copy>(
+    graph: &impl WeightedGraph<Vertex = V>,
+    root: V,
+    target: V,
+) -> Option<Vec<V>> {
+    let mut visited: HashSet<V> = HashSet::new();
+    let mut history: Vec<V> = Vec::new();
     let mut queue = VecDeque::new();
 
     visited.insert(root);
     queue.push_back(root);
     while let Some(currentnode) = queue.pop_front() {
-        history.push(currentnode.value());
+        history.push(currentnode);
 
         // If we reach the goal, return our travel history.
         if currentnode == target {
@@ -33,7 +40,7 @@ pub fn breadth_first_search(graph: &Graph, root: Node, target: Node) -> Option<V
         }
 
         // Check the neighboring nodes for any that we've not visited yet.
-        for neighbor in currentnode.neighbors(graph) {
+        for (neighbor, _) in graph.neighbors(currentnode) {
             if !visited.contains(&neighbor) {
                 visited.insert(neighbor);
                 queue.push_back(neighbor);
@@ -45,57 +52,52 @@ pub fn breadth_first_search(graph: &Graph, root: Node, target: Node) -> Option<V
     None
 }
 
-// Data Structures
-
-#[derive(Copy, Clone, PartialEq, Eq, Hash)]
-pub struct Node(u32);
-
-#[derive(Copy, Clone, PartialEq, Eq, Hash)]
-pub struct Edge(u32, u32);
-
-#[derive(Clone)]
-pub struct Graph {
-    #[allow(dead_code)]
-    nodes: Vec<Node>,
-    edges: Vec<Edge>,
-}
-
-impl Graph {
-    pub fn new(nodes: Vec<Node>, edges: Vec<Edge>) -> Self {
-        Graph { nodes, edges }
-    }
-}
+/// Same as `breadth_first_search` for a `CsrGraph`, whose vertices are `0..n`:
+/// the visited vertices are marked in a `Vec<bool>` instead of a `HashSet`.
+pub fn breadth_first_search_csr<E: Clone>(
+    graph: &CsrGraph<E>,
+    root: usize,
+    target: usize,
+) -> Option<Vec<usize>> {
+    let mut visited = vec![false; graph.num_vertices()];
+    let mut history: Vec<usize> = Vec::new();
+    let mut queue = VecDeque::new();
 
-impl From<u32> for Node {
-    fn from(item: u32) -> Self {
-        Node(item)
-    }
-}
+    visited[root] = true;
+    queue.push_back(root);
+    while let Some(currentnode) = queue.pop_front() {
+        history.push(currentnode);
 
-impl Node {
-    pub fn value(&self) -> u32 {
-        self.0
-    }
+        if currentnode == target {
+            return Some(history);
+        }
 
-    pub fn neighbors(&self, graph: &Graph) -> Vec<Node> {
-        graph
-            .edges
-            .iter()
-            .filter(|e| e.0 == self.0)
-            .map(|e| e.1.into())
-            .collect()
+        for &neighbor in graph.targets(currentnode) {
+            if !visited[neighbor] {
+                visited[neighbor] = true;
+                queue.push_back(neighbor);
+            }
+        }
     }
-}
 
-impl From<(u32, u32)> for Edge {
-    fn from(item: (u32, u32)) -> Self {
-        Edge(item.0, item.1)
-    }
+    None
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::collections::BTreeMap;
+
+    type Graph = BTreeMap<u32, Vec<u32>>;
+
+    fn build_graph(nodes: Vec<u32>, edges: Vec<(u32, u32)>) -> Graph {
+        let mut graph: Graph = nodes.into_iter().map(|v| (v, vec![])).collect();
+        for (source, destination) in edges {
+            graph.entry(source).or_default().push(destination);
+            graph.entry(destination).or_default();
+        }
+        graph
+    }
 
     /* Example graph #1:
      *
@@ -111,10 +113,7 @@ mod tests {
         let nodes = vec![1, 2, 3, 4, 5, 6, 7];
         let edges = vec![(1, 2), (1, 3), (2, 4), (2, 5), (3, 6), (3, 7), (5, 8)];
 
-        Graph::new(
-            nodes.into_iter().map(|v| v.into()).collect(),
-            edges.into_iter().map(|e| e.into()).collect(),
-        )
+        build_graph(nodes, edges)
     }
 
     #[test]
@@ -123,10 +122,7 @@ mod tests {
         let root = 1;
         let target = 10;
 
-        assert_eq!(
-            breadth_first_search(&graph, root.into(), target.into()),
-            None
-        );
+        assert_eq!(breadth_first_search(&graph, root, target), None);
     }
 
     #[test]
@@ -138,7 +134,7 @@ mod tests {
         let expected_path = vec![1, 2, 3, 4, 5, 6, 7, 8];
 
         assert_eq!(
-            breadth_first_search(&graph, root.into(), target.into()),
+            breadth_first_search(&graph, root, target),
             Some(expected_path)
         );
     }
@@ -170,10 +166,7 @@ mod tests {
             (7, 6),
         ];
 
-        Graph::new(
-            nodes.into_iter().map(|v| v.into()).collect(),
-            undirected_edges.into_iter().map(|e| e.into()).collect(),
-        )
+        build_graph(nodes, undirected_edges)
     }
 
     #[test]
@@ -182,10 +175,7 @@ mod tests {
         let root = 8;
         let target = 4;
 
-        assert_eq!(
-            breadth_first_search(&graph, root.into(), target.into()),
-            None
-        );
+        assert_eq!(breadth_first_search(&graph, root, target), None);
     }
 
     #[test]
@@ -197,7 +187,7 @@ mod tests {
         let expected_path = vec![4, 3, 7, 6, 2, 1];
 
         assert_eq!(
-            breadth_first_search(&graph, root.into(), target.into()),
+            breadth_first_search(&graph, root, target),
             Some(expected_path)
         );
     }
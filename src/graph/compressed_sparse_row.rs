@@ -0,0 +1,266 @@
+/*
+Compressed Sparse Row (CSR) graph:
+An immutable directed graph that stores all the edges in two flat arrays. The
+outgoing edges of vertex `v` are `targets[offsets[v]..offsets[v + 1]]` (with the
+matching `weights`). Compared to a `BTreeMap<V, BTreeMap<V, E>>` it uses
+O(V + E) memory with no per-node allocation, and iterating over the neighbours
+of a vertex is a linear scan of a contiguous slice.
+
+Vertices are numbered from 0 to n - 1. Algorithms that number their vertices
+from 1 (e.g. `StronglyConnectedComponents`) can be given a graph built with
+n + 1 vertices, leaving vertex 0 isolated.
+
+Building takes O(V + E) time using a counting sort on the source vertices, and
+the edges of each vertex keep the order in which they were given.
+
+The generic algorithms taking any `WeightedGraph` keep their state in maps or
+sets keyed by vertex, which costs far more than the graph itself on large
+inputs. `topological_sort_csr`, `dijkstra_csr` and `breadth_first_search_csr`
+index their state by vertex in `Vec`s instead.
+*/
+
+use super::WeightedGraph;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CsrGraph<E> {
+    offsets: Vec<usize>,
+    targets: Vec<usize>,
+    weights: Vec<E>,
+}
+
+impl<E: Clone> CsrGraph<E> {
+    /// Builds the graph from `(source, destination, weight)` triples.
+    /// Panics if a vertex is not smaller than `num_vertices`.
+    pub fn from_edges(num_vertices: usize, edges: &[(usize, usize, E)]) -> Self {
+        let mut offsets = vec![0; num_vertices + 1];
+        for &(source, destination, _) in edges {
+            assert!(
+                source < num_vertices && destination < num_vertices,
+                "edge ({source}, {destination}) is out of bounds for {num_vertices} vertices"
+            );
+            offsets[source + 1] += 1;
+        }
+        for v in 0..num_vertices {
+            offsets[v + 1] += offsets[v];
+        }
+        // `next[v]` is where the next edge of `v` should be written, and
+        // `order[i]` is the index in `edges` of the i'th edge of the graph
+        let mut next = offsets.clone();
+        let mut order = vec![0; edges.len()];
+        for (i, &(source, _, _)) in edges.iter().enumerate() {
+            order[next[source]] = i;
+            next[source] += 1;
+        }
+        CsrGraph {
+            offsets,
+            targets: order.iter().map(|&i| edges[i].1).collect(),
+            weights: order.iter().map(|&i| edges[i].2.clone()).collect(),
+        }
+    }
+
+    /// Copies a graph whose vertices are `0..n`, keeping its edge order.
+    pub fn from_graph(graph: &impl WeightedGraph<Vertex = usize, Weight = E>) -> Self {
+        let num_vertices = graph.vertices().max().map_or(0, |v| v + 1);
+        let edges: Vec<(usize, usize, E)> = graph.edges().collect();
+        Self::from_edges(num_vertices, &edges)
+    }
+
+    /// Returns the graph with every edge reversed.
+    pub fn transpose(&self) -> Self {
+        let edges: Vec<(usize, usize, E)> = self
+            .edges()
+            .map(|(source, destination, weight)| (destination, source, weight))
+            .collect();
+        Self::from_edges(self.num_vertices(), &edges)
+    }
+}
+
+impl CsrGraph<()> {
+    /// Builds an unweighted graph from `(source, destination)` pairs.
+    pub fn from_unweighted_edges(num_vertices: usize, edges: &[(usize, usize)]) -> Self {
+        let edges: Vec<(usize, usize, ())> = edges.iter().map(|&(u, v)| (u, v, ())).collect();
+        Self::from_edges(num_vertices, &edges)
+    }
+}
+
+impl<E> CsrGraph<E> {
+    /// The destinations of the outgoing edges of `vertex`
+    pub fn targets(&self, vertex: usize) -> &[usize] {
+        &self.targets[self.offsets[vertex]..self.offsets[vertex + 1]]
+    }
+
+    /// The weights of the outgoing edges of `vertex`, in the order of `targets`
+    pub fn weights(&self, vertex: usize) -> &[E] {
+        &self.weights[self.offsets[vertex]..self.offsets[vertex + 1]]
+    }
+}
+
+impl<E: Clone> WeightedGraph for CsrGraph<E> {
+    type Vertex = usize;
+    type Weight = E;
+
+    fn vertices(&self) -> impl Iterator<Item = usize> + '_ {
+        0..self.offsets.len() - 1
+    }
+
+    fn neighbors(&self, vertex: usize) -> impl Iterator<Item = (usize, E)> + '_ {
+        let edges = if vertex + 1 < self.offsets.len() {
+            self.offsets[vertex]..self.offsets[vertex + 1]
+        } else {
+            0..0
+        };
+        self.targets[edges.clone()]
+            .iter()
+            .copied()
+            .zip(self.weights[edges].iter().cloned())
+    }
+
+    fn num_vertices(&self) -> usize {
+        self.offsets.len() - 1
+    }
+
+    fn out_degree(&self, vertex: usize) -> usize {
+        if vertex < self.num_vertices() {
+            self.offsets[vertex + 1] - self.offsets[vertex]
+        } else {
+            0
+        }
+    }
+
+    fn num_edges(&self) -> usize {
+        self.targets.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{
+        breadth_first_search, breadth_first_search_csr, dijkstra, dijkstra_csr,
+        topological_sort_csr, topological_sort_graph, StronglyConnectedComponents,
+        TopoligicalSortError,
+    };
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    use std::collections::BTreeMap;
+
+    type Graph = BTreeMap<usize, BTreeMap<usize, u64>>;
+
+    // A random graph with vertices 1..=n (vertex 0 is kept isolated), and its
+    // CSR copy with the edges in the same order
+    fn random_graphs(n: usize, m: usize, acyclic: bool, seed: u64) -> (Graph, CsrGraph<u64>) {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut graph: Graph = (0..=n).map(|v| (v, BTreeMap::new())).collect();
+        for _ in 0..m {
+            let mut u = rng.gen_range(1..=n);
+            let mut v = rng.gen_range(1..=n);
+            if acyclic && u > v {
+                std::mem::swap(&mut u, &mut v);
+            }
+            if !acyclic || u != v {
+                graph.get_mut(&u).unwrap().insert(v, rng.gen_range(1..100));
+            }
+        }
+        let csr = CsrGraph::from_graph(&graph);
+        (graph, csr)
+    }
+
+    #[test]
+    fn small_graph() {
+        let csr = CsrGraph::from_edges(4, &[(2, 3, 'c'), (0, 1, 'a'), (0, 2, 'b'), (3, 0, 'd')]);
+        assert_eq!(csr.num_vertices(), 4);
+        assert_eq!(csr.num_edges(), 4);
+        assert_eq!(csr.targets(0), &[1, 2]);
+        assert_eq!(csr.weights(0), &['a', 'b']);
+        assert_eq!(csr.out_degree(1), 0);
+        assert_eq!(
+            csr.edges().collect::<Vec<_>>(),
+            vec![(0, 1, 'a'), (0, 2, 'b'), (2, 3, 'c'), (3, 0, 'd')]
+        );
+        assert_eq!(csr.neighbors(10).count(), 0);
+
+        let transposed = csr.transpose();
+        assert_eq!(transposed.targets(0), &[3]);
+        assert_eq!(transposed.targets(2), &[0]);
+        assert_eq!(transposed.transpose(), csr);
+
+        let empty = CsrGraph::from_unweighted_edges(0, &[]);
+        assert_eq!(empty.num_vertices(), 0);
+        assert_eq!(empty.vertices().count(), 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn out_of_bounds_edge() {
+        CsrGraph::from_unweighted_edges(2, &[(0, 2)]);
+    }
+
+    #[test]
+    fn dijkstra_matches_map_graph() {
+        let (graph, csr) = random_graphs(5_000, 50_000, false, 2024);
+        for start in [1, 17, 2_500, 4_999] {
+            let expected = dijkstra(&graph, start);
+            assert_eq!(dijkstra(&csr, start), expected);
+            let dense = dijkstra_csr(&csr, start);
+            // the predecessors of equally distant vertices may differ
+            for (v, answer) in dense.into_iter().enumerate() {
+                let distance =
+                    |answer: Option<Option<(usize, u64)>>| answer.map(|a| a.map(|a| a.1));
+                assert_eq!(distance(answer), distance(expected.get(&v).copied()));
+            }
+        }
+    }
+
+    #[test]
+    fn breadth_first_search_matches_map_graph() {
+        let (graph, csr) = random_graphs(5_000, 20_000, false, 7);
+        for (root, target) in [(1, 5_000), (42, 43), (3_000, 1)] {
+            let expected = breadth_first_search(&graph, root, target);
+            assert_eq!(breadth_first_search(&csr, root, target), expected);
+            assert_eq!(breadth_first_search_csr(&csr, root, target), expected);
+        }
+    }
+
+    #[test]
+    fn strongly_connected_components_match_map_graph() {
+        let n = 5_000;
+        let (graph, csr) = random_graphs(n, 6_000, false, 99);
+        let adj: Vec<Vec<usize>> = (0..=n)
+            .map(|u| graph[&u].keys().copied().collect())
+            .collect();
+
+        let mut expected = StronglyConnectedComponents::new(n);
+        expected.find_components(&adj);
+        let mut sccs = StronglyConnectedComponents::new(n);
+        sccs.find_components(&csr);
+        assert_eq!(sccs.component, expected.component);
+        assert_eq!(sccs.num_components, expected.num_components);
+        assert!(sccs.num_components > 1 && sccs.num_components < n);
+    }
+
+    #[test]
+    fn topological_sort_matches_map_graph() {
+        let (graph, csr) = random_graphs(5_000, 50_000, true, 1);
+        for sort in [
+            topological_sort_graph(&csr).unwrap(),
+            topological_sort_csr(&csr).unwrap(),
+        ] {
+            assert_eq!(sort.len(), csr.num_vertices());
+            let mut position = vec![0; sort.len()];
+            for (i, &v) in sort.iter().enumerate() {
+                position[v] = i;
+            }
+            assert!(csr.edges().all(|(u, v, _)| position[u] < position[v]));
+        }
+        assert!(topological_sort_graph(&graph).is_ok());
+
+        let cyclic = CsrGraph::from_unweighted_edges(4, &[(0, 1), (1, 2), (2, 1), (2, 3)]);
+        assert!(topological_sort_graph(&cyclic).is_err());
+        let Err(TopoligicalSortError::CycleDetected(mut cycle)) = topological_sort_csr(&cyclic)
+        else {
+            panic!("the graph has a cycle");
+        };
+        cycle.sort();
+        assert_eq!(cycle, vec![1, 2]);
+    }
+}
@@ -0,0 +1,261 @@
+/*
+Condensation of a directed graph:
+Contracting every strongly connected component (SCC) into a single vertex
+gives a directed acyclic graph, the condensation. Its sources are the
+components that can't be reached from any other, and its sinks the ones from
+which no other can be reached.
+
+A graph with more than one SCC is made strongly connected by adding an edge
+from every sink to a source, chaining them so that every component lies on
+one big cycle. With s sources and t sinks, max(s, t) edges are needed (every
+source needs an incoming edge, every sink an outgoing one) and are enough.
+
+Such edges are found by pairing sources with sinks they can reach
+(Eswaran and Tarjan): the pairs are chained into a cycle, and the remaining
+sources and sinks are attached to it.
+
+Tarjan's algorithm finds the SCCs in reverse topological order, so the
+components are numbered by reversing it. Complexity: O(V + E log V).
+*/
+
+use std::collections::BTreeMap;
+
+use super::{tarjan_scc, WeightedGraph};
+
+pub struct Condensation<V> {
+    // The vertices of each component; the components are in topological
+    // order, so every edge of `dag` goes from a component to a later one
+    pub components: Vec<Vec<V>>,
+    // The component of each vertex
+    pub component_of: BTreeMap<V, usize>,
+    // The edges between the components, sorted and without duplicates
+    pub dag: Vec<Vec<usize>>,
+}
+
+impl<V: Ord + Copy> Condensation<V> {
+    pub fn new(graph: &impl WeightedGraph<Vertex = V>) -> Self {
+        let mut components = tarjan_scc(graph);
+        components.reverse();
+        let component_of: BTreeMap<V, usize> = components
+            .iter()
+            .enumerate()
+            .flat_map(|(i, component)| component.iter().map(move |&v| (v, i)))
+            .collect();
+        let mut dag = vec![vec![]; components.len()];
+        for (u, v, _) in graph.edges() {
+            let (a, b) = (component_of[&u], component_of[&v]);
+            if a != b {
+                dag[a].push(b);
+            }
+        }
+        for edges in &mut dag {
+            edges.sort_unstable();
+            edges.dedup();
+        }
+        Condensation {
+            components,
+            component_of,
+            dag,
+        }
+    }
+
+    pub fn num_components(&self) -> usize {
+        self.components.len()
+    }
+
+    // The components without incoming edges, in increasing order
+    pub fn sources(&self) -> Vec<usize> {
+        let mut has_incoming = vec![false; self.num_components()];
+        for &b in self.dag.iter().flatten() {
+            has_incoming[b] = true;
+        }
+        (0..self.num_components())
+            .filter(|&c| !has_incoming[c])
+            .collect()
+    }
+
+    // The components without outgoing edges, in increasing order
+    pub fn sinks(&self) -> Vec<usize> {
+        (0..self.num_components())
+            .filter(|&c| self.dag[c].is_empty())
+            .collect()
+    }
+
+    // The minimum number of edges to add to make the graph strongly connected
+    pub fn edges_to_strongly_connect(&self) -> usize {
+        if self.num_components() <= 1 {
+            0
+        } else {
+            self.sources().len().max(self.sinks().len())
+        }
+    }
+
+    // A minimum set of edges that makes the graph strongly connected, between
+    // one vertex of each component (Eswaran and Tarjan's construction)
+    pub fn strongly_connecting_edges(&self) -> Vec<(V, V)> {
+        if self.num_components() <= 1 {
+            return vec![];
+        }
+        // Pair sources with sinks they reach, greedily with a DFS from each
+        // source that never enters a component seen before. Then every source
+        // reaches a paired sink, and every sink is reached from a paired source.
+        let sinks = self.sinks();
+        let mut is_sink = vec![false; self.num_components()];
+        for &t in &sinks {
+            is_sink[t] = true;
+        }
+        let mut seen = vec![false; self.num_components()];
+        let mut pairs = vec![];
+        let mut unpaired_sources = vec![];
+        for s in self.sources() {
+            match self.find_unseen_sink(s, &is_sink, &mut seen) {
+                Some(t) => pairs.push((s, t)),
+                None => unpaired_sources.push(s),
+            }
+        }
+        let unpaired_sinks: Vec<usize> = sinks.into_iter().filter(|&t| !seen[t]).collect();
+
+        // The pairs form a cycle s1 ~> t1 -> s2 ~> t2 ... ~> tp -> s1, the other
+        // sinks and sources are attached to it
+        let mut edges = vec![];
+        for i in 0..pairs.len() {
+            edges.push((pairs[i].1, pairs[(i + 1) % pairs.len()].0));
+        }
+        let (first_source, first_sink) = pairs[0];
+        for i in 0..unpaired_sources.len().max(unpaired_sinks.len()) {
+            let t = unpaired_sinks.get(i).copied().unwrap_or(first_sink);
+            let s = unpaired_sources.get(i).copied().unwrap_or(first_source);
+            edges.push((t, s));
+        }
+        edges
+            .into_iter()
+            .map(|(a, b)| (self.components[a][0], self.components[b][0]))
+            .collect()
+    }
+
+    fn find_unseen_sink(&self, c: usize, is_sink: &[bool], seen: &mut [bool]) -> Option<usize> {
+        seen[c] = true;
+        if is_sink[c] {
+            return Some(c);
+        }
+        for &next in &self.dag[c] {
+            if !seen[next] {
+                if let Some(t) = self.find_unseen_sink(next, is_sink, seen) {
+                    return Some(t);
+                }
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn small_graph() {
+        // {0, 1, 2} -> {3} -> {4, 5} and {0, 1, 2} -> {6}
+        let adj = vec![
+            vec![1],
+            vec![2, 3],
+            vec![0, 6],
+            vec![4],
+            vec![5],
+            vec![4],
+            vec![],
+        ];
+        let cond = Condensation::new(&adj);
+        assert_eq!(cond.num_components(), 4);
+        let c = |v: usize| cond.component_of[&v];
+        assert_eq!(c(0), 0);
+        assert_eq!(c(1), c(0));
+        assert_eq!(c(2), c(0));
+        assert_eq!(c(4), c(5));
+        assert!(c(3) < c(4));
+        assert_eq!(cond.dag[c(0)].len(), 2);
+        assert_eq!(cond.sources(), vec![0]);
+        assert_eq!(cond.sinks().len(), 2);
+        assert_eq!(cond.edges_to_strongly_connect(), 2);
+        assert_eq!(cond.strongly_connecting_edges().len(), 2);
+
+        let mut members = cond.components[c(4)].clone();
+        members.sort();
+        assert_eq!(members, vec![4, 5]);
+    }
+
+    #[test]
+    fn strongly_connected_and_empty() {
+        let cycle = vec![vec![1], vec![2], vec![0]];
+        let cond = Condensation::new(&cycle);
+        assert_eq!(cond.num_components(), 1);
+        assert_eq!(cond.dag, vec![vec![]]);
+        assert_eq!(cond.edges_to_strongly_connect(), 0);
+
+        let empty: Vec<Vec<usize>> = vec![];
+        assert_eq!(Condensation::new(&empty).edges_to_strongly_connect(), 0);
+
+        // isolated vertices are both sources and sinks
+        let isolated = vec![vec![], vec![], vec![]];
+        let cond = Condensation::new(&isolated);
+        assert_eq!(cond.sources(), vec![0, 1, 2]);
+        assert_eq!(cond.sinks(), vec![0, 1, 2]);
+        assert_eq!(cond.edges_to_strongly_connect(), 3);
+        assert_eq!(cond.strongly_connecting_edges().len(), 3);
+    }
+
+    // Whether v can be reached from u
+    fn reaches(adj: &[Vec<usize>], u: usize, v: usize) -> bool {
+        let mut seen = vec![false; adj.len()];
+        let mut stack = vec![u];
+        seen[u] = true;
+        while let Some(x) = stack.pop() {
+            for &y in &adj[x] {
+                if !seen[y] {
+                    seen[y] = true;
+                    stack.push(y);
+                }
+            }
+        }
+        seen[v]
+    }
+
+    #[test]
+    fn random_graphs() {
+        let mut rng = StdRng::seed_from_u64(17);
+        for _ in 0..200 {
+            let n = rng.gen_range(1..=9);
+            let mut adj = vec![vec![]; n];
+            for _ in 0..rng.gen_range(0..=2 * n) {
+                adj[rng.gen_range(0..n)].push(rng.gen_range(0..n));
+            }
+            let cond = Condensation::new(&adj);
+            for u in 0..n {
+                for v in 0..n {
+                    let same = reaches(&adj, u, v) && reaches(&adj, v, u);
+                    let (a, b) = (cond.component_of[&u], cond.component_of[&v]);
+                    assert_eq!(a == b, same);
+                    // topological order
+                    if adj[u].contains(&v) && a != b {
+                        assert!(a < b);
+                        assert!(cond.dag[a].contains(&b));
+                    }
+                }
+            }
+
+            // the added edges make the graph strongly connected
+            let edges = cond.strongly_connecting_edges();
+            assert_eq!(edges.len(), cond.edges_to_strongly_connect());
+            if cond.num_components() > 1 {
+                assert_eq!(edges.len(), cond.sources().len().max(cond.sinks().len()));
+            }
+            let mut extended = adj.clone();
+            for &(u, v) in &edges {
+                extended[u].push(v);
+            }
+            assert!((0..n).all(|v| reaches(&extended, 0, v) && reaches(&extended, v, 0)));
+        }
+    }
+}
@@ -0,0 +1,349 @@
+/*
+Contraction hierarchies:
+A preprocessing of a fixed graph after which point-to-point shortest path
+queries only explore a few hundred vertices, even on graphs with millions of
+them (road networks in particular).
+
+Preprocessing orders the vertices by "importance" and contracts them one by
+one, least important first. Contracting v removes it from the graph; for every
+pair of remaining neighbors u -> v -> w whose only shortest path goes through v
+(no "witness" path avoids it), a shortcut u -> w is added with the weight of
+the two edges. The order is chosen greedily with lazy updates, by the number
+of shortcuts a contraction would add minus the number of edges it removes.
+
+Every shortest path then has a version that first only goes up in the order,
+then only goes down. A query is a bidirectional Dijkstra where the forward
+search only follows edges going up from the start, and the backward search
+only follows edges going down to the target; the shortcuts of the path found
+are then unpacked into the edges of the original graph.
+
+Weights must not be negative.
+*/
+
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BinaryHeap, HashMap};
+use std::ops::Add;
+
+use num_traits::Zero;
+
+use super::bidirectional_dijkstra::{bidirectional_search, IndexedGraph, StoppingRule};
+use super::WeightedGraph;
+
+// A witness search gives up after settling this many vertices, and the shortcut
+// is added even if it may not be needed. This only costs a few more edges.
+const WITNESS_SEARCH_LIMIT: usize = 64;
+
+pub struct ContractionHierarchy<V, E> {
+    vertices: Vec<V>,
+    index: BTreeMap<V, usize>,
+    // rank[v] is the position of v in the contraction order
+    rank: Vec<usize>,
+    // the edges u -> w with rank[u] < rank[w]
+    upward: IndexedGraph<E>,
+    // the edges u -> w with rank[u] > rank[w], stored as w -> u
+    downward: IndexedGraph<E>,
+    // the weight of every edge u -> w of the hierarchy, and the vertex it
+    // shortcuts, if any
+    middle: HashMap<(usize, usize), (E, Option<usize>)>,
+}
+
+// the graph during the contraction
+struct Contraction<E> {
+    outgoing: Vec<BTreeMap<usize, E>>,
+    incoming: Vec<BTreeMap<usize, E>>,
+    contracted: Vec<bool>,
+    middle: HashMap<(usize, usize), (E, Option<usize>)>,
+}
+
+impl<E> Contraction<E>
+where
+    E: Ord + Copy + Add<Output = E> + Zero,
+{
+    fn add_edge(&mut self, u: usize, w: usize, weight: E, middle: Option<usize>) {
+        if self.outgoing[u].get(&w).is_some_and(|&old| old <= weight) {
+            return;
+        }
+        self.outgoing[u].insert(w, weight);
+        self.incoming[w].insert(u, weight);
+        self.middle.insert((u, w), (weight, middle));
+    }
+
+    // The shortcuts needed to contract v
+    fn shortcuts(&self, v: usize) -> Vec<(usize, usize, E)> {
+        let mut shortcuts = vec![];
+        for (&u, &in_weight) in &self.incoming[v] {
+            if self.contracted[u] {
+                continue;
+            }
+            let targets: Vec<(usize, E)> = self.outgoing[v]
+                .iter()
+                .filter(|&(&w, _)| w != u && !self.contracted[w])
+                .map(|(&w, &out_weight)| (w, in_weight + out_weight))
+                .collect();
+            let Some(limit) = targets.iter().map(|&(_, weight)| weight).max() else {
+                continue;
+            };
+            let witness = self.witness_search(u, v, limit);
+            for (w, weight) in targets {
+                if witness.get(&w).is_none_or(|&dist| dist > weight) {
+                    shortcuts.push((u, w, weight));
+                }
+            }
+        }
+        shortcuts
+    }
+
+    // the distances from u in the remaining graph without v, up to limit
+    fn witness_search(&self, u: usize, v: usize, limit: E) -> HashMap<usize, E> {
+        let mut dist = HashMap::from([(u, E::zero())]);
+        let mut queue = BinaryHeap::from([Reverse((E::zero(), u))]);
+        let mut settled = 0;
+        while let Some(Reverse((d, x))) = queue.pop() {
+            if d > dist[&x] {
+                continue;
+            }
+            settled += 1;
+            if d > limit || settled > WITNESS_SEARCH_LIMIT {
+                break;
+            }
+            for (&y, &weight) in &self.outgoing[x] {
+                if y == v || self.contracted[y] {
+                    continue;
+                }
+                let new_dist = d + weight;
+                if dist.get(&y).is_none_or(|&old| new_dist < old) {
+                    dist.insert(y, new_dist);
+                    queue.push(Reverse((new_dist, y)));
+                }
+            }
+        }
+        dist
+    }
+
+    fn edges_removed(&self, v: usize) -> usize {
+        let remaining =
+            |edges: &BTreeMap<usize, E>| edges.keys().filter(|&&u| !self.contracted[u]).count();
+        remaining(&self.incoming[v]) + remaining(&self.outgoing[v])
+    }
+}
+
+impl<V, E> ContractionHierarchy<V, E>
+where
+    V: Ord + Copy,
+    E: Ord + Copy + Add<Output = E> + Zero,
+{
+    // Preprocesses the graph. Parallel edges keep their lowest weight and self-loops are dropped.
+    pub fn new(graph: &impl WeightedGraph<Vertex = V, Weight = E>) -> Self {
+        let vertices: Vec<V> = graph.vertices().collect();
+        let n = vertices.len();
+        let index: BTreeMap<V, usize> = vertices.iter().enumerate().map(|(i, &v)| (v, i)).collect();
+        let mut graph_state = Contraction {
+            outgoing: vec![BTreeMap::new(); n],
+            incoming: vec![BTreeMap::new(); n],
+            contracted: vec![false; n],
+            middle: HashMap::new(),
+        };
+        for (u, w, weight) in graph.edges() {
+            let (u, w) = (index[&u], index[&w]);
+            if u != w {
+                graph_state.add_edge(u, w, weight, None);
+            }
+        }
+
+        // the contracted neighbors of each vertex, to spread contractions over the graph
+        let mut contracted_neighbors = vec![0; n];
+        let priority = |state: &Contraction<E>, contracted_neighbors: &[i64], v: usize| {
+            state.shortcuts(v).len() as i64 - state.edges_removed(v) as i64
+                + contracted_neighbors[v]
+        };
+        let mut queue: BinaryHeap<Reverse<(i64, usize)>> = (0..n)
+            .map(|v| Reverse((priority(&graph_state, &contracted_neighbors, v), v)))
+            .collect();
+        let mut rank = vec![0; n];
+        let mut next_rank = 0;
+        while let Some(Reverse((_, v))) = queue.pop() {
+            if graph_state.contracted[v] {
+                continue;
+            }
+            // the priority may be outdated: if it got worse, v goes back in the queue
+            let current = priority(&graph_state, &contracted_neighbors, v);
+            if queue
+                .peek()
+                .is_some_and(|&Reverse((next, _))| current > next)
+            {
+                queue.push(Reverse((current, v)));
+                continue;
+            }
+            for (u, w, weight) in graph_state.shortcuts(v) {
+                graph_state.add_edge(u, w, weight, Some(v));
+            }
+            graph_state.contracted[v] = true;
+            rank[v] = next_rank;
+            next_rank += 1;
+            let neighbors = graph_state.incoming[v]
+                .keys()
+                .chain(graph_state.outgoing[v].keys());
+            for &u in neighbors {
+                contracted_neighbors[u] += 1;
+            }
+        }
+
+        let mut upward = vec![vec![]; n];
+        let mut downward = vec![vec![]; n];
+        for (u, edges) in graph_state.outgoing.iter().enumerate() {
+            for (&w, &weight) in edges {
+                if rank[u] < rank[w] {
+                    upward[u].push((w, weight));
+                } else {
+                    downward[w].push((u, weight));
+                }
+            }
+        }
+        ContractionHierarchy {
+            vertices,
+            index,
+            rank,
+            upward,
+            downward,
+            middle: graph_state.middle,
+        }
+    }
+
+    // The number of edges of the hierarchy, original edges and shortcuts
+    pub fn num_edges(&self) -> usize {
+        self.middle.len()
+    }
+
+    // The position of v in the contraction order, from the least important vertex
+    pub fn rank(&self, v: V) -> Option<usize> {
+        self.index.get(&v).map(|&v| self.rank[v])
+    }
+
+    // returns the distance from start to target and the vertices of a shortest path
+    // in the original graph, or None if target can't be reached
+    pub fn query(&self, start: V, target: V) -> Option<(E, Vec<V>)> {
+        let (start, target) = (*self.index.get(&start)?, *self.index.get(&target)?);
+        let (dist, path) = bidirectional_search(
+            &self.upward,
+            &self.downward,
+            start,
+            target,
+            StoppingRule::EachFront,
+        )?;
+        let mut unpacked = vec![start];
+        for edge in path.windows(2) {
+            self.unpack(edge[0], edge[1], &mut unpacked);
+        }
+        Some((
+            dist,
+            unpacked.into_iter().map(|v| self.vertices[v]).collect(),
+        ))
+    }
+
+    // appends the original vertices of the edge u -> w, but u, to path
+    fn unpack(&self, u: usize, w: usize, path: &mut Vec<usize>) {
+        match self.middle[&(u, w)].1 {
+            Some(v) => {
+                self.unpack(u, v, path);
+                self.unpack(v, w, path);
+            }
+            None => path.push(w),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::dijkstra;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    type Graph<V, E> = BTreeMap<V, BTreeMap<V, E>>;
+
+    fn add_edge<V: Ord + Copy, E: Ord>(graph: &mut Graph<V, E>, v1: V, v2: V, c: E) {
+        graph.entry(v1).or_default().insert(v2, c);
+        graph.entry(v2).or_default();
+    }
+
+    fn check(graph: &Graph<usize, u32>, ch: &ContractionHierarchy<usize, u32>, start: usize) {
+        let dists = dijkstra(graph, start);
+        for &target in graph.keys() {
+            let expected = dists.get(&target).map(|e| e.map_or(0, |(_, w)| w));
+            let res = ch.query(start, target);
+            assert_eq!(res.as_ref().map(|(w, _)| *w), expected);
+            if let Some((weight, path)) = res {
+                // the path only uses edges of the original graph
+                assert_eq!((path[0], *path.last().unwrap()), (start, target));
+                let total: u32 = path.windows(2).map(|e| graph[&e[0]][&e[1]]).sum();
+                assert_eq!(total, weight);
+            }
+        }
+    }
+
+    #[test]
+    fn small_graph() {
+        let mut graph = BTreeMap::new();
+        add_edge(&mut graph, 'a', 'c', 12);
+        add_edge(&mut graph, 'a', 'd', 60);
+        add_edge(&mut graph, 'b', 'a', 10);
+        add_edge(&mut graph, 'c', 'b', 20);
+        add_edge(&mut graph, 'c', 'd', 32);
+        add_edge(&mut graph, 'e', 'a', 7);
+        let ch = ContractionHierarchy::new(&graph);
+
+        assert_eq!(ch.query('e', 'd'), Some((51, vec!['e', 'a', 'c', 'd'])));
+        assert_eq!(ch.query('c', 'a'), Some((30, vec!['c', 'b', 'a'])));
+        assert_eq!(ch.query('b', 'b'), Some((0, vec!['b'])));
+        assert_eq!(ch.query('d', 'a'), None);
+        assert_eq!(ch.query('a', 'z'), None);
+        let mut ranks: Vec<usize> = "abcde".chars().map(|v| ch.rank(v).unwrap()).collect();
+        ranks.sort();
+        assert_eq!(ranks, vec![0, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn grid_needs_shortcuts() {
+        // an undirected grid, like a street map
+        let side = 12;
+        let mut graph: Graph<usize, u32> = BTreeMap::new();
+        let mut rng = StdRng::seed_from_u64(1);
+        for r in 0..side {
+            for c in 0..side {
+                let v = r * side + c;
+                if c + 1 < side {
+                    let w = rng.gen_range(1..=9);
+                    add_edge(&mut graph, v, v + 1, w);
+                    add_edge(&mut graph, v + 1, v, w);
+                }
+                if r + 1 < side {
+                    let w = rng.gen_range(1..=9);
+                    add_edge(&mut graph, v, v + side, w);
+                    add_edge(&mut graph, v + side, v, w);
+                }
+            }
+        }
+        let ch = ContractionHierarchy::new(&graph);
+        assert!(ch.num_edges() > graph.num_edges());
+        for start in [0, 17, 70, 143] {
+            check(&graph, &ch, start);
+        }
+    }
+
+    #[test]
+    fn random_graphs_match_dijkstra() {
+        let mut rng = StdRng::seed_from_u64(12);
+        for _ in 0..100 {
+            let n = rng.gen_range(1..=30);
+            let mut graph: Graph<usize, u32> = (0..n).map(|v| (v, BTreeMap::new())).collect();
+            for _ in 0..rng.gen_range(0..=n * 3) {
+                let (u, v) = (rng.gen_range(0..n), rng.gen_range(0..n));
+                add_edge(&mut graph, u, v, rng.gen_range(0..=20));
+            }
+            let ch = ContractionHierarchy::new(&graph);
+            for start in 0..n {
+                check(&graph, &ch, start);
+            }
+        }
+    }
+}
@@ -0,0 +1,290 @@
+/*
+Longest paths and the critical path method:
+In a directed acyclic graph, the heaviest path is found by dynamic programming
+in topological order: the best path ending at v extends the best path ending
+at one of its predecessors.
+
+The critical path method schedules tasks that depend on each other: a task
+can only start once all the tasks it depends on are finished. Going forward in
+topological order gives the earliest start of every task, and the length of
+the whole project. Going backward from the end of the project gives the latest
+start of every task that doesn't delay the project. The difference is the
+slack of the task; the tasks without slack form the critical chain, the
+longest path through the tasks weighted by their durations.
+
+Complexity: O(V + E) after the topological sort.
+*/
+
+use std::collections::BTreeMap;
+use std::hash::Hash;
+use std::ops::{Add, Sub};
+
+use num_traits::Zero;
+
+use super::topological_sort::TopoligicalSortError;
+use super::{topological_sort_graph, WeightedGraph};
+
+/// Returns the heaviest path of a weighted directed acyclic graph, with its
+/// weight, or a cycle of the graph if it isn't acyclic. A path may have a
+/// single vertex, so the weight is never negative; an empty graph gives an
+/// empty path.
+pub fn longest_path<V, E>(
+    graph: &impl WeightedGraph<Vertex = V, Weight = E>,
+) -> Result<(E, Vec<V>), TopoligicalSortError<V>>
+where
+    V: Ord + Hash + Copy,
+    E: Ord + Copy + Add<Output = E> + Zero,
+{
+    let order = topological_sort_graph(graph)?;
+    // the heaviest path ending at each vertex, and the vertex before it
+    let mut best: BTreeMap<V, (E, Option<V>)> =
+        order.iter().map(|&v| (v, (E::zero(), None))).collect();
+    for &u in &order {
+        let d = best[&u].0;
+        for (v, w) in graph.neighbors(u) {
+            if d + w > best[&v].0 {
+                best.insert(v, (d + w, Some(u)));
+            }
+        }
+    }
+    let Some((&end, &(weight, _))) = best.iter().max_by_key(|(_, (d, _))| *d) else {
+        return Ok((E::zero(), vec![]));
+    };
+    let mut path = vec![end];
+    while let Some(prev) = best[path.last().unwrap()].1 {
+        path.push(prev);
+    }
+    path.reverse();
+    Ok((weight, path))
+}
+
+/// The schedule computed by `critical_path`. Times are counted from the start
+/// of the project.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Schedule<V, E> {
+    // The time the whole project takes
+    pub makespan: E,
+    pub earliest_start: BTreeMap<V, E>,
+    pub latest_start: BTreeMap<V, E>,
+    // How much each task can be delayed without delaying the project
+    pub slack: BTreeMap<V, E>,
+    // A chain of tasks without slack, each depending on the previous one, that
+    // starts at time zero and ends with the project
+    pub critical_chain: Vec<V>,
+}
+
+impl<V: Ord, E: Ord + Zero> Schedule<V, E> {
+    /// Whether delaying this task delays the whole project
+    pub fn is_critical(&self, task: V) -> bool {
+        self.slack.get(&task).is_some_and(|slack| slack.is_zero())
+    }
+}
+
+/// Schedules the tasks of `dependencies`, where an edge u -> v means that v
+/// can only start when u is finished (edge weights are ignored), and `duration`
+/// gives the time each task takes. Returns a cycle of dependencies if there is one.
+pub fn critical_path<V, E>(
+    dependencies: &impl WeightedGraph<Vertex = V>,
+    duration: impl Fn(V) -> E,
+) -> Result<Schedule<V, E>, TopoligicalSortError<V>>
+where
+    V: Ord + Hash + Copy,
+    E: Ord + Copy + Add<Output = E> + Sub<Output = E> + Zero,
+{
+    let order = topological_sort_graph(dependencies)?;
+    let duration: BTreeMap<V, E> = order.iter().map(|&v| (v, duration(v))).collect();
+
+    let mut earliest_start: BTreeMap<V, E> = order.iter().map(|&v| (v, E::zero())).collect();
+    for &u in &order {
+        let finish = earliest_start[&u] + duration[&u];
+        for (v, _) in dependencies.neighbors(u) {
+            if finish > earliest_start[&v] {
+                earliest_start.insert(v, finish);
+            }
+        }
+    }
+    let makespan = order
+        .iter()
+        .map(|v| earliest_start[v] + duration[v])
+        .max()
+        .unwrap_or_else(E::zero);
+
+    let mut latest_start = BTreeMap::new();
+    for &u in order.iter().rev() {
+        let latest_finish = dependencies
+            .neighbors(u)
+            .map(|(v, _)| latest_start[&v])
+            .min()
+            .unwrap_or(makespan);
+        latest_start.insert(u, latest_finish - duration[&u]);
+    }
+    let slack: BTreeMap<V, E> = order
+        .iter()
+        .map(|&v| (v, latest_start[&v] - earliest_start[&v]))
+        .collect();
+
+    // Follow the critical tasks that start right when the previous one finishes
+    let mut critical_chain = vec![];
+    let mut next = order
+        .iter()
+        .copied()
+        .find(|v| slack[v].is_zero() && earliest_start[v].is_zero());
+    while let Some(u) = next {
+        critical_chain.push(u);
+        let finish = earliest_start[&u] + duration[&u];
+        next = dependencies
+            .neighbors(u)
+            .map(|(v, _)| v)
+            .find(|v| slack[v].is_zero() && earliest_start[v] == finish);
+    }
+
+    Ok(Schedule {
+        makespan,
+        earliest_start,
+        latest_start,
+        slack,
+        critical_chain,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    type Graph<V, E> = BTreeMap<V, BTreeMap<V, E>>;
+
+    fn add_edge<V: Ord + Copy, E: Ord>(graph: &mut Graph<V, E>, v1: V, v2: V, c: E) {
+        graph.entry(v1).or_default().insert(v2, c);
+        graph.entry(v2).or_default();
+    }
+
+    #[test]
+    fn longest_path_in_dag() {
+        let mut graph = BTreeMap::new();
+        add_edge(&mut graph, 'r', 's', 5);
+        add_edge(&mut graph, 'r', 't', 3);
+        add_edge(&mut graph, 's', 't', 2);
+        add_edge(&mut graph, 's', 'x', 6);
+        add_edge(&mut graph, 't', 'x', 7);
+        add_edge(&mut graph, 't', 'y', 4);
+        add_edge(&mut graph, 't', 'z', 2);
+        add_edge(&mut graph, 'x', 'y', -1);
+        add_edge(&mut graph, 'x', 'z', 1);
+        add_edge(&mut graph, 'y', 'z', -2);
+        assert_eq!(
+            longest_path(&graph),
+            Ok((15, vec!['r', 's', 't', 'x', 'z']))
+        );
+
+        assert_eq!(longest_path(&Graph::<u8, i32>::new()), Ok((0, vec![])));
+        add_edge(&mut graph, 'z', 's', 1);
+        assert!(longest_path(&graph).is_err());
+    }
+
+    #[test]
+    fn project_schedule() {
+        // design -> {backend, frontend} -> integration -> release, docs on the side
+        let tasks = [
+            ("design", 3),
+            ("backend", 5),
+            ("frontend", 4),
+            ("integration", 2),
+            ("release", 1),
+            ("docs", 2),
+        ];
+        let durations: BTreeMap<&str, u32> = tasks.into_iter().collect();
+        let mut dependencies: BTreeMap<&str, Vec<&str>> =
+            tasks.iter().map(|&(task, _)| (task, vec![])).collect();
+        for (u, v) in [
+            ("design", "backend"),
+            ("design", "frontend"),
+            ("backend", "integration"),
+            ("frontend", "integration"),
+            ("integration", "release"),
+            ("design", "docs"),
+            ("docs", "release"),
+        ] {
+            dependencies.get_mut(u).unwrap().push(v);
+        }
+        let schedule = critical_path(&dependencies, |task| durations[task]).unwrap();
+        assert_eq!(schedule.makespan, 11);
+        assert_eq!(
+            schedule.critical_chain,
+            vec!["design", "backend", "integration", "release"]
+        );
+        assert_eq!(schedule.earliest_start["frontend"], 3);
+        assert_eq!(schedule.latest_start["frontend"], 4);
+        assert_eq!(schedule.slack["frontend"], 1);
+        assert_eq!(schedule.slack["docs"], 5);
+        assert_eq!(schedule.latest_start["release"], 10);
+        assert!(schedule.is_critical("backend"));
+        assert!(!schedule.is_critical("docs"));
+
+        dependencies.get_mut("release").unwrap().push("design");
+        let TopoligicalSortError::CycleDetected(cycle) =
+            critical_path(&dependencies, |task| durations[task]).unwrap_err();
+        assert!(cycle.contains(&"release") && cycle.contains(&"design"));
+    }
+
+    // The heaviest path ending at each vertex, by trying every path
+    fn brute_force(graph: &Graph<usize, i32>, u: usize, weight: i32, best: &mut Vec<i32>) {
+        best[u] = best[u].max(weight);
+        for (&v, &w) in &graph[&u] {
+            brute_force(graph, v, weight + w, best);
+        }
+    }
+
+    #[test]
+    fn random_dags() {
+        let mut rng = StdRng::seed_from_u64(20);
+        for _ in 0..100 {
+            let n = rng.gen_range(1..=8);
+            // edges only go from a vertex to a larger one
+            let mut graph: Graph<usize, i32> = (0..n).map(|v| (v, BTreeMap::new())).collect();
+            for _ in 0..rng.gen_range(0..=2 * n) {
+                let (u, v) = (rng.gen_range(0..n), rng.gen_range(0..n));
+                if u < v {
+                    add_edge(&mut graph, u, v, rng.gen_range(-3..=10));
+                }
+            }
+            let mut best = vec![0; n];
+            for u in 0..n {
+                brute_force(&graph, u, 0, &mut best);
+            }
+            let (weight, path) = longest_path(&graph).unwrap();
+            assert_eq!(weight, *best.iter().max().unwrap());
+            let path_weight: i32 = path.windows(2).map(|e| graph[&e[0]][&e[1]]).sum();
+            assert_eq!(path_weight, weight);
+
+            // a task weighs its duration, so the makespan is the heaviest
+            // path when each edge weighs the duration of its source
+            let durations: Vec<i32> = (0..n).map(|_| rng.gen_range(0..=5)).collect();
+            let schedule = critical_path(&graph, |v| durations[v]).unwrap();
+            let mut weighted: Graph<usize, i32> = (0..n).map(|v| (v, BTreeMap::new())).collect();
+            weighted.insert(n, BTreeMap::new());
+            for (u, v, _) in graph.edges() {
+                add_edge(&mut weighted, u, v, durations[u]);
+            }
+            for (u, &duration) in durations.iter().enumerate() {
+                add_edge(&mut weighted, u, n, duration);
+            }
+            assert_eq!(schedule.makespan, longest_path(&weighted).unwrap().0);
+            for (u, v, _) in graph.edges() {
+                let finish = schedule.earliest_start[&u] + durations[u];
+                assert!(finish <= schedule.earliest_start[&v]);
+                assert!(schedule.latest_start[&u] + durations[u] <= schedule.latest_start[&v]);
+            }
+            for (v, &duration) in durations.iter().enumerate() {
+                assert!(schedule.slack[&v] >= 0);
+                assert!(schedule.latest_start[&v] + duration <= schedule.makespan);
+            }
+            let chain = &schedule.critical_chain;
+            let chain_length: i32 = chain.iter().map(|&v| durations[v]).sum();
+            assert_eq!(chain_length, schedule.makespan);
+            assert!(chain.iter().all(|&v| schedule.is_critical(v)));
+            assert!(chain.windows(2).all(|e| graph[&e[0]].contains_key(&e[1])));
+        }
+    }
+}
@@ -1,13 +1,21 @@
 use std::collections::HashSet;
 use std::collections::VecDeque;
+use std::hash::Hash;
+
+use super::WeightedGraph;
 
 // Perform a Depth First Search Algorithm to find a element in a graph
+// (any `WeightedGraph`, the weights are ignored)
 //
 // Return a Optional with a vector with history of vertex visiteds
 // or a None if the element not exists on the graph
-pub fn depth_first_search(graph: &Graph, root: Vertex, objective: Vertex) -> Option<Vec<u32>> {
-    let mut visited: HashSet<Vertex> = HashSet::new();
-    let mut history: Vec<u32> = Vec::new();
+pub fn depth_first_search<V: Hash + Eq + Copy>(
+    graph: &impl WeightedGraph<Vertex = V>,
+    root: V,
+    objective: V,
+) -> Option<Vec<V>> {
+    let mut visited: HashSet<V> = HashSet::new();
+    let mut history: Vec<V> = Vec::new();
     let mut queue = VecDeque::new();
     queue.push_back(root);
 
@@ -15,7 +23,7 @@ pub fn depth_first_search(graph: &Graph, root: Vertex, objective: Vertex) -> Opt
     // get the first element of the vertex queue
     while let Some(current_vertex) = queue.pop_front() {
         // Added current vertex in the history of visiteds vertex
-        history.push(current_vertex.value());
+        history.push(current_vertex);
 
         // Verify if this vertex is the objective
         if current_vertex == objective {
@@ -24,7 +32,8 @@ pub fn depth_first_search(graph: &Graph, root: Vertex, objective: Vertex) -> Opt
         }
 
         // For each over the neighbors of current vertex
-        for neighbor in current_vertex.neighbors(graph).into_iter().rev() {
+        let neighbors: Vec<V> = graph.neighbors(current_vertex).map(|(v, _)| v).collect();
+        for neighbor in neighbors.into_iter().rev() {
             // Insert in the HashSet of visiteds if this value not exist yet
             if visited.insert(neighbor) {
                 // Add the neighbor on front of queue
@@ -38,55 +47,21 @@ pub fn depth_first_search(graph: &Graph, root: Vertex, objective: Vertex) -> Opt
     None
 }
 
-// Data Structures
-
-#[derive(Copy, Clone, PartialEq, Eq, Hash)]
-pub struct Vertex(u32);
-#[derive(Copy, Clone, PartialEq, Eq, Hash)]
-pub struct Edge(u32, u32);
-#[derive(Clone)]
-pub struct Graph {
-    #[allow(dead_code)]
-    vertices: Vec<Vertex>,
-    edges: Vec<Edge>,
-}
-
-impl Graph {
-    pub fn new(vertices: Vec<Vertex>, edges: Vec<Edge>) -> Self {
-        Graph { vertices, edges }
-    }
-}
-
-impl From<u32> for Vertex {
-    fn from(item: u32) -> Self {
-        Vertex(item)
-    }
-}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
 
-impl Vertex {
-    pub fn value(&self) -> u32 {
-        self.0
-    }
+    type Graph = BTreeMap<u32, Vec<u32>>;
 
-    pub fn neighbors(&self, graph: &Graph) -> VecDeque<Vertex> {
+    fn build_graph(vertices: Vec<u32>, edges: Vec<(u32, u32)>) -> Graph {
+        let mut graph: Graph = vertices.into_iter().map(|v| (v, vec![])).collect();
+        for (source, destination) in edges {
+            graph.entry(source).or_default().push(destination);
+            graph.entry(destination).or_default();
+        }
         graph
-            .edges
-            .iter()
-            .filter(|e| e.0 == self.0)
-            .map(|e| e.1.into())
-            .collect()
     }
-}
-
-impl From<(u32, u32)> for Edge {
-    fn from(item: (u32, u32)) -> Self {
-        Edge(item.0, item.1)
-    }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
 
     #[test]
     fn find_1_fail() {
@@ -96,15 +71,9 @@ mod tests {
         let root = 1;
         let objective = 99;
 
-        let graph = Graph::new(
-            vertices.into_iter().map(|v| v.into()).collect(),
-            edges.into_iter().map(|e| e.into()).collect(),
-        );
+        let graph = build_graph(vertices, edges);
 
-        assert_eq!(
-            depth_first_search(&graph, root.into(), objective.into()),
-            None
-        );
+        assert_eq!(depth_first_search(&graph, root, objective), None);
     }
 
     #[test]
@@ -117,13 +86,10 @@ mod tests {
 
         let correct_path = vec![1, 2, 4, 5, 3, 6, 7];
 
-        let graph = Graph::new(
-            vertices.into_iter().map(|v| v.into()).collect(),
-            edges.into_iter().map(|e| e.into()).collect(),
-        );
+        let graph = build_graph(vertices, edges);
 
         assert_eq!(
-            depth_first_search(&graph, root.into(), objective.into()),
+            depth_first_search(&graph, root, objective),
             Some(correct_path)
         );
     }
@@ -148,13 +114,10 @@ mod tests {
 
         let correct_path = vec![0, 1, 3, 2, 4, 5, 7, 6];
 
-        let graph = Graph::new(
-            vertices.into_iter().map(|v| v.into()).collect(),
-            edges.into_iter().map(|e| e.into()).collect(),
-        );
+        let graph = build_graph(vertices, edges);
 
         assert_eq!(
-            depth_first_search(&graph, root.into(), objective.into()),
+            depth_first_search(&graph, root, objective),
             Some(correct_path)
         );
     }
@@ -179,13 +142,10 @@ mod tests {
 
         let correct_path = vec![0, 1, 3, 2, 4];
 
-        let graph = Graph::new(
-            vertices.into_iter().map(|v| v.into()).collect(),
-            edges.into_iter().map(|e| e.into()).collect(),
-        );
+        let graph = build_graph(vertices, edges);
 
         assert_eq!(
-            depth_first_search(&graph, root.into(), objective.into()),
+            depth_first_search(&graph, root, objective),
             Some(correct_path)
         );
     }
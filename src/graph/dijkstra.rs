@@ -1,44 +1,98 @@
-use std::collections::BTreeMap;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet, BinaryHeap};
 use std::ops::Add;
 
-type Graph<V, E> = BTreeMap<V, BTreeMap<V, E>>;
+use num_traits::Zero;
+
+use super::{CsrGraph, WeightedGraph};
 
 // performs Dijsktra's algorithm on the given graph from the given start
-// the graph is a positively-weighted undirected graph
+// the graph is any positively-weighted `WeightedGraph`, e.g. an undirected `BTreeMap<V, BTreeMap<V, E>>`
 //
 // returns a map that for each reachable vertex associates the distance and the predecessor
 // since the start has no predecessor but is reachable, map[start] will be None
 //
 // Time: O(E * logV). For each vertex, we traverse each edge, resulting in O(E). For each edge, we
-// insert a new shortest path for a vertex into the tree, resulting in O(E * logV).
+// insert a new shortest path for a vertex into the heap, resulting in O(E * logV).
 // Space: O(V). The tree holds up to V vertices.
-pub fn dijkstra<V: Ord + Copy, E: Ord + Copy + Add<Output = E>>(
-    graph: &Graph<V, E>,
+pub fn dijkstra<V, E>(
+    graph: &impl WeightedGraph<Vertex = V, Weight = E>,
     start: V,
-) -> BTreeMap<V, Option<(V, E)>> {
-    let mut ans = BTreeMap::new();
-    let mut prio = BTreeMap::new();
+) -> BTreeMap<V, Option<(V, E)>>
+where
+    V: Ord + Copy,
+    E: Ord + Copy + Add<Output = E>,
+{
+    settle(graph, [start], None)
+}
+
+// same as `dijkstra`, but the distances are measured from the closest of several sources
+// every source maps to None, and following the predecessors from a vertex leads to its closest source
+pub fn dijkstra_multi_source<V, E>(
+    graph: &impl WeightedGraph<Vertex = V, Weight = E>,
+    sources: impl IntoIterator<Item = V>,
+) -> BTreeMap<V, Option<(V, E)>>
+where
+    V: Ord + Copy,
+    E: Ord + Copy + Add<Output = E>,
+{
+    settle(graph, sources, None)
+}
 
-    // start is the special case that doesn't have a predecessor
-    ans.insert(start, None);
+// finds a shortest path from start to target, stopping as soon as the distance to target is known
+// returns the distance and the vertices of the path, from start to target, or None if target
+// can't be reached
+pub fn dijkstra_path<V, E>(
+    graph: &impl WeightedGraph<Vertex = V, Weight = E>,
+    start: V,
+    target: V,
+) -> Option<(E, Vec<V>)>
+where
+    V: Ord + Copy,
+    E: Ord + Copy + Add<Output = E> + Zero,
+{
+    let ans = settle(graph, [start], Some(target));
+    let path = reconstruct_path(&ans, target)?;
+    let dist = match ans[&target] {
+        Some((_, dist)) => dist,
+        None => E::zero(),
+    };
+    Some((dist, path))
+}
 
-    for (new, weight) in &graph[&start] {
-        ans.insert(*new, Some((start, *weight)));
-        prio.insert(*new, *weight);
-    }
+// same as `dijkstra` for a `CsrGraph`, whose vertices are 0..n: the answer and the settled
+// vertices are kept in `Vec`s instead of maps, so besides the graph it only uses O(V) memory
+// and the heap
+//
+// returns for each vertex None if it is unreachable, and otherwise the same value as `dijkstra`
+pub fn dijkstra_csr<E>(graph: &CsrGraph<E>, start: usize) -> Vec<Option<Option<(usize, E)>>>
+where
+    E: Ord + Copy + Add<Output = E>,
+{
+    let n = graph.num_vertices();
+    let mut ans = vec![None; n];
+    let mut prio = BinaryHeap::new();
+    let mut settled = vec![false; n];
 
-    while let Some((vertex, path_weight)) = prio.pop_first() {
-        for (next, weight) in &graph[&vertex] {
-            let new_weight = path_weight + *weight;
-            match ans.get(next) {
-                // if ans[next] is a lower dist than the alternative one, we do nothing
-                Some(Some((_, dist_next))) if new_weight >= *dist_next => {}
-                // if ans[next] is None then next is start and so the distance won't be changed, it won't be added again in prio
+    ans[start] = Some(None);
+    prio.push(Reverse((None, start)));
+    while let Some(Reverse((path_weight, vertex))) = prio.pop() {
+        if settled[vertex] {
+            continue;
+        }
+        settled[vertex] = true;
+        for (&next, &weight) in graph.targets(vertex).iter().zip(graph.weights(vertex)) {
+            let new_weight = match path_weight {
+                Some(path_weight) => path_weight + weight,
+                None => weight,
+            };
+            match ans[next] {
+                Some(Some((_, dist_next))) if new_weight >= dist_next => {}
+                // next is the start
                 Some(None) => {}
-                // the new path is shorter, either new was not in ans or it was farther
                 _ => {
-                    ans.insert(*next, Some((vertex, new_weight)));
-                    prio.insert(*next, new_weight);
+                    ans[next] = Some(Some((vertex, new_weight)));
+                    prio.push(Reverse((Some(new_weight), next)));
                 }
             }
         }
@@ -47,10 +101,100 @@ pub fn dijkstra<V: Ord + Copy, E: Ord + Copy + Add<Output = E>>(
     ans
 }
 
+// rebuilds the path to target from the map returned by `dijkstra` or `dijkstra_multi_source`
+// the path starts at the source it comes from and ends at target, None if target is unreachable
+pub fn reconstruct_path<V: Ord + Copy, E>(
+    predecessors: &BTreeMap<V, Option<(V, E)>>,
+    target: V,
+) -> Option<Vec<V>> {
+    let mut path = vec![target];
+    let mut current = predecessors.get(&target)?;
+    while let Some((prev, _)) = current {
+        path.push(*prev);
+        current = &predecessors[prev];
+    }
+    path.reverse();
+    Some(path)
+}
+
+// settles the vertices in increasing order of distance to the sources, until target
+// (if there is one) is settled
+fn settle<V, E>(
+    graph: &impl WeightedGraph<Vertex = V, Weight = E>,
+    sources: impl IntoIterator<Item = V>,
+    target: Option<V>,
+) -> BTreeMap<V, Option<(V, E)>>
+where
+    V: Ord + Copy,
+    E: Ord + Copy + Add<Output = E>,
+{
+    let mut ans = BTreeMap::new();
+    let mut prio = BinaryHeap::new();
+    let mut settled = BTreeSet::new();
+
+    // the sources are the special case that doesn't have a predecessor
+    let sources: Vec<V> = sources.into_iter().collect();
+    for &source in &sources {
+        ans.insert(source, None);
+    }
+    if target.is_some_and(|target| ans.contains_key(&target)) {
+        return ans;
+    }
+    for &source in &sources {
+        settled.insert(source);
+        relax(graph, source, None, &mut ans, &mut prio);
+    }
+
+    while let Some(Reverse((path_weight, vertex))) = prio.pop() {
+        // the vertex may have been pushed several times, only its first pop is up to date
+        if !settled.insert(vertex) {
+            continue;
+        }
+        if Some(vertex) == target {
+            break;
+        }
+        relax(graph, vertex, Some(path_weight), &mut ans, &mut prio);
+    }
+
+    ans
+}
+
+fn relax<V, E>(
+    graph: &impl WeightedGraph<Vertex = V, Weight = E>,
+    vertex: V,
+    path_weight: Option<E>,
+    ans: &mut BTreeMap<V, Option<(V, E)>>,
+    prio: &mut BinaryHeap<Reverse<(E, V)>>,
+) where
+    V: Ord + Copy,
+    E: Ord + Copy + Add<Output = E>,
+{
+    for (next, weight) in graph.neighbors(vertex) {
+        let new_weight = match path_weight {
+            Some(path_weight) => path_weight + weight,
+            None => weight,
+        };
+        match ans.get(&next) {
+            // if ans[next] is a lower dist than the alternative one, we do nothing
+            Some(Some((_, dist_next))) if new_weight >= *dist_next => {}
+            // if ans[next] is None then next is a source and so the distance won't be changed, it won't be added again in prio
+            Some(None) => {}
+            // the new path is shorter, either new was not in ans or it was farther
+            _ => {
+                ans.insert(next, Some((vertex, new_weight)));
+                prio.push(Reverse((new_weight, next)));
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{dijkstra, Graph};
-    use std::collections::BTreeMap;
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    type Graph<V, E> = BTreeMap<V, BTreeMap<V, E>>;
 
     fn add_edge<V: Ord + Copy, E: Ord>(graph: &mut Graph<V, E>, v1: V, v2: V, c: E) {
         graph.entry(v1).or_default().insert(v2, c);
@@ -152,4 +296,106 @@ mod tests {
         dists_e.insert('b', Some(('c', 39)));
         assert_eq!(dijkstra(&graph, 'e'), dists_e);
     }
+
+    #[test]
+    fn path_to_target() {
+        let mut graph = BTreeMap::new();
+        add_edge(&mut graph, 'a', 'c', 12);
+        add_edge(&mut graph, 'a', 'd', 60);
+        add_edge(&mut graph, 'b', 'a', 10);
+        add_edge(&mut graph, 'c', 'b', 20);
+        add_edge(&mut graph, 'c', 'd', 32);
+        add_edge(&mut graph, 'e', 'a', 7);
+
+        assert_eq!(
+            dijkstra_path(&graph, 'e', 'd'),
+            Some((51, vec!['e', 'a', 'c', 'd']))
+        );
+        assert_eq!(
+            dijkstra_path(&graph, 'c', 'a'),
+            Some((30, vec!['c', 'b', 'a']))
+        );
+        assert_eq!(dijkstra_path(&graph, 'b', 'b'), Some((0, vec!['b'])));
+        assert_eq!(dijkstra_path(&graph, 'd', 'a'), None);
+        assert_eq!(dijkstra_path(&graph, 'a', 'z'), None);
+
+        let dists = dijkstra(&graph, 'e');
+        assert_eq!(
+            reconstruct_path(&dists, 'b'),
+            Some(vec!['e', 'a', 'c', 'b'])
+        );
+        assert_eq!(reconstruct_path(&dists, 'e'), Some(vec!['e']));
+        assert_eq!(reconstruct_path(&dijkstra(&graph, 'd'), 'e'), None);
+    }
+
+    #[test]
+    fn stops_at_target() {
+        // a long chain behind the target is never explored
+        let mut graph = BTreeMap::new();
+        add_edge(&mut graph, 0, 1, 1);
+        add_edge(&mut graph, 1, 2, 1);
+        add_edge(&mut graph, 0, 3, 5);
+        let ans = settle(&graph, [0], Some(1));
+        assert_eq!(ans.keys().copied().collect::<Vec<_>>(), vec![0, 1, 3]);
+        assert_eq!(settle(&graph, [0], Some(0)).len(), 1);
+    }
+
+    #[test]
+    fn multi_source() {
+        let mut graph = BTreeMap::new();
+        for i in 0..10 {
+            add_edge(&mut graph, i, i + 1, 1);
+            add_edge(&mut graph, i + 1, i, 1);
+        }
+        let dists = dijkstra_multi_source(&graph, [2, 8]);
+        assert_eq!(dists[&2], None);
+        assert_eq!(dists[&8], None);
+        assert_eq!(dists[&0], Some((1, 2)));
+        assert_eq!(dists[&5], Some((4, 3)));
+        assert_eq!(dists[&6], Some((7, 2)));
+        assert_eq!(dists[&10], Some((9, 2)));
+        assert_eq!(reconstruct_path(&dists, 10), Some(vec![8, 9, 10]));
+    }
+
+    #[test]
+    fn random_graphs() {
+        let mut rng = StdRng::seed_from_u64(11);
+        for _ in 0..100 {
+            let n = rng.gen_range(1..=20);
+            let mut graph: Graph<usize, u32> = (0..n).map(|v| (v, BTreeMap::new())).collect();
+            for _ in 0..rng.gen_range(0..=n * 3) {
+                add_edge(
+                    &mut graph,
+                    rng.gen_range(0..n),
+                    rng.gen_range(0..n),
+                    rng.gen_range(1..=20),
+                );
+            }
+            let sources: Vec<usize> = (0..rng.gen_range(1..=3))
+                .map(|_| rng.gen_range(0..n))
+                .collect();
+            let from_each: Vec<_> = sources.iter().map(|&s| dijkstra(&graph, s)).collect();
+            let dists = dijkstra_multi_source(&graph, sources.iter().copied());
+            for v in 0..n {
+                // the distance to the closest source, 0 for the sources themselves
+                let expected = from_each
+                    .iter()
+                    .filter_map(|d| d.get(&v).map(|e| e.map_or(0, |(_, w)| w)))
+                    .min();
+                assert_eq!(dists.get(&v).map(|e| e.map_or(0, |(_, w)| w)), expected);
+
+                let target = dijkstra_path(&graph, sources[0], v);
+                assert_eq!(
+                    target.as_ref().map(|(w, _)| *w),
+                    from_each[0].get(&v).map(|e| e.map_or(0, |(_, w)| w))
+                );
+                if let Some((weight, path)) = target {
+                    // the path is made of edges of the graph and has the right weight
+                    assert_eq!((path[0], *path.last().unwrap()), (sources[0], v));
+                    let total: u32 = path.windows(2).map(|e| graph[&e[0]][&e[1]]).sum();
+                    assert_eq!(total, weight);
+                }
+            }
+        }
+    }
 }
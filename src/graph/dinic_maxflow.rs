@@ -1,6 +1,8 @@
 use std::collections::VecDeque;
 use std::ops::{Add, AddAssign, Neg, Sub, SubAssign};
 
+use super::WeightedGraph;
+
 // We assume that graph vertices are numbered from 1 to n.
 
 /// Adjacency matrix
@@ -19,6 +21,16 @@ pub struct FlowResultEdge<T> {
     pub flow: T,
 }
 
+/// A minimum cut between the source and the sink of a flow network
+pub struct MinCut<T> {
+    /// The total capacity of the cut, which equals the maximum flow
+    pub capacity: T,
+    /// The vertices that remain reachable from the source in the residual network
+    pub source_side: Vec<usize>,
+    /// The saturated edges going from the source side to the sink side
+    pub cut_edges: Vec<FlowResultEdge<T>>,
+}
+
 impl<T: Clone + Copy + Add + AddAssign + Sub<Output = T> + SubAssign + Ord + Neg + Default>
     FlowEdge<T>
 {
@@ -70,6 +82,24 @@ impl<T: Clone + Copy + Add + AddAssign + Sub<Output = T> + SubAssign + Neg + Ord
             edges: vec![],
         }
     }
+    /// Builds the flow network of `graph`, using the edge weights as capacities.
+    /// Like the network itself, `graph` should number its vertices from 1 to n.
+    pub fn from_graph(
+        graph: &impl WeightedGraph<Vertex = usize, Weight = T>,
+        source: usize,
+        sink: usize,
+    ) -> Self {
+        let num_vertices = graph
+            .vertices()
+            .chain([source, sink])
+            .max()
+            .unwrap_or_default();
+        let mut flow = DinicMaxFlow::new(source, sink, num_vertices);
+        for (u, v, capacity) in graph.edges() {
+            flow.add_edge(u, v, capacity);
+        }
+        flow
+    }
     #[inline]
     pub fn add_edge(&mut self, source: usize, sink: usize, capacity: T) {
         self.edges.push(FlowEdge::new(sink, capacity));
@@ -152,6 +182,31 @@ impl<T: Clone + Copy + Add + AddAssign + Sub<Output = T> + SubAssign + Neg + Ord
         total_flow
     }
 
+    pub fn min_cut(&mut self, infinite_flow: T) -> MinCut<T> {
+        if !self.network_solved {
+            self.find_maxflow(infinite_flow);
+        }
+        let reachable = self.reachable_in_residual();
+        let mut cut = MinCut {
+            capacity: T::default(),
+            source_side: (1..=self.num_vertices).filter(|&v| reachable[v]).collect(),
+            cut_edges: Vec::new(),
+        };
+        // Even edges are the ones that were added, odd edges are their reverse
+        for e in (0..self.num_edges).step_by(2) {
+            let (source, edge) = (self.edges[e + 1].sink, &self.edges[e]);
+            if reachable[source] && !reachable[edge.sink] && edge.capacity > T::default() {
+                cut.capacity += edge.capacity;
+                cut.cut_edges.push(FlowResultEdge {
+                    source,
+                    sink: edge.sink,
+                    flow: edge.flow,
+                });
+            }
+        }
+        cut
+    }
+
     pub fn get_flow_edges(&mut self, infinite_flow: T) -> Vec<FlowResultEdge<T>> {
         if !self.network_solved {
             self.find_maxflow(infinite_flow);
@@ -175,9 +230,32 @@ impl<T: Clone + Copy + Add + AddAssign + Sub<Output = T> + SubAssign + Neg + Ord
     }
 }
 
+impl<T: PartialOrd> DinicMaxFlow<T> {
+    /// Marks the vertices that can be reached from the source using edges that
+    /// are not saturated. Once the maximum flow is found, they form the
+    /// source side of a minimum cut.
+    pub(crate) fn reachable_in_residual(&self) -> Vec<bool> {
+        let mut reachable = vec![false; self.num_vertices + 1];
+        let mut q = VecDeque::from([self.source]);
+        reachable[self.source] = true;
+        while let Some(v) = q.pop_front() {
+            for &e in self.adj[v].iter() {
+                let u = self.edges[e].sink;
+                if self.edges[e].capacity > self.edges[e].flow && !reachable[u] {
+                    reachable[u] = true;
+                    q.push_back(u);
+                }
+            }
+        }
+        reachable
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::collections::BTreeMap;
+
     #[test]
     fn small_graph() {
         let mut flow: DinicMaxFlow<i32> = DinicMaxFlow::new(1, 6, 6);
@@ -210,4 +288,58 @@ mod tests {
         assert_eq!(sm_in[6], max_flow);
         assert_eq!(sm_out[6], 0);
     }
+
+    #[test]
+    fn from_weighted_graph() {
+        let mut graph: BTreeMap<usize, BTreeMap<usize, i32>> = BTreeMap::new();
+        for (u, v, c) in [
+            (1, 2, 16),
+            (1, 4, 13),
+            (2, 3, 12),
+            (3, 4, 9),
+            (3, 6, 20),
+            (4, 2, 4),
+            (4, 5, 14),
+            (5, 3, 7),
+            (5, 6, 4),
+        ] {
+            graph.entry(u).or_default().insert(v, c);
+            graph.entry(v).or_default();
+        }
+        let mut flow = DinicMaxFlow::from_graph(&graph, 1, 6);
+        assert_eq!(flow.num_vertices, 6);
+        assert_eq!(flow.find_maxflow(i32::MAX), 23);
+    }
+
+    #[test]
+    fn min_cut() {
+        let mut flow: DinicMaxFlow<i32> = DinicMaxFlow::new(1, 6, 6);
+        flow.add_edge(1, 2, 16);
+        flow.add_edge(1, 4, 13);
+        flow.add_edge(2, 3, 12);
+        flow.add_edge(3, 4, 9);
+        flow.add_edge(3, 6, 20);
+        flow.add_edge(4, 2, 4);
+        flow.add_edge(4, 5, 14);
+        flow.add_edge(5, 3, 7);
+        flow.add_edge(5, 6, 4);
+
+        let cut = flow.min_cut(i32::MAX);
+        assert_eq!(cut.capacity, 23);
+        assert_eq!(cut.source_side, vec![1, 2, 4, 5]);
+        let cut_edges: Vec<_> = cut
+            .cut_edges
+            .iter()
+            .map(|e| (e.source, e.sink, e.flow))
+            .collect();
+        assert_eq!(cut_edges, vec![(2, 3, 12), (5, 3, 7), (5, 6, 4)]);
+
+        // The sink cannot be reached at all
+        let mut flow: DinicMaxFlow<i32> = DinicMaxFlow::new(1, 3, 3);
+        flow.add_edge(1, 2, 5);
+        let cut = flow.min_cut(i32::MAX);
+        assert_eq!(cut.capacity, 0);
+        assert_eq!(cut.source_side, vec![1, 2]);
+        assert!(cut.cut_edges.is_empty());
+    }
 }
@@ -0,0 +1,210 @@
+/*
+Offline dynamic connectivity:
+Edges of an undirected graph are added and removed over time, and we want to
+know, at given moments, whether two vertices are connected.
+
+All the operations are known in advance. Every edge is then alive during an
+interval of time, which is split over the O(log q) nodes of a segment tree
+over time that exactly cover it. A DFS over the segment tree adds the edges of
+a node when entering it and removes them when leaving it, which is what
+`RollbackDisjointSetUnion` allows; at a leaf, exactly the edges alive at that
+moment are merged.
+
+Complexity: O(q log q log n) for q operations.
+
+Vertices are numbered from 0 to n.
+*/
+
+use std::collections::HashMap;
+
+use super::RollbackDisjointSetUnion;
+
+enum Event {
+    Add(usize, usize),
+    Remove(usize, usize),
+    Query(usize, usize),
+}
+
+pub struct DynamicConnectivity {
+    num_vertices: usize,
+    events: Vec<Event>,
+}
+
+impl DynamicConnectivity {
+    pub fn new(num_vertices: usize) -> Self {
+        DynamicConnectivity {
+            num_vertices,
+            events: Vec::new(),
+        }
+    }
+    pub fn add_edge(&mut self, u: usize, v: usize) {
+        self.events.push(Event::Add(u.min(v), u.max(v)));
+    }
+    // Removes an edge added before. With parallel edges, one copy is removed
+    pub fn remove_edge(&mut self, u: usize, v: usize) {
+        self.events.push(Event::Remove(u.min(v), u.max(v)));
+    }
+    // Asks whether u and v are connected at this point; answered by `solve`
+    pub fn query(&mut self, u: usize, v: usize) {
+        self.events.push(Event::Query(u, v));
+    }
+
+    // Returns the answers to the queries, in the order they were asked.
+    // Panics if an edge is removed while it is not in the graph.
+    pub fn solve(&self) -> Vec<bool> {
+        let num_events = self.events.len();
+        // The events at which the copies of each edge that are alive were added
+        let mut alive_since: HashMap<(usize, usize), Vec<usize>> = HashMap::new();
+        let mut tree = vec![vec![]; 4 * num_events.max(1)];
+        for (time, event) in self.events.iter().enumerate() {
+            match *event {
+                Event::Add(u, v) => alive_since.entry((u, v)).or_default().push(time),
+                Event::Remove(u, v) => {
+                    let start = alive_since
+                        .get_mut(&(u, v))
+                        .and_then(Vec::pop)
+                        .unwrap_or_else(|| panic!("the edge ({u}, {v}) is not in the graph"));
+                    add_interval(&mut tree, 1, 0, num_events, start, time, (u, v));
+                }
+                Event::Query(_, _) => (),
+            }
+        }
+        for (&edge, starts) in &alive_since {
+            for &start in starts {
+                add_interval(&mut tree, 1, 0, num_events, start, num_events, edge);
+            }
+        }
+
+        let mut dsu = RollbackDisjointSetUnion::new(self.num_vertices);
+        let mut answers = Vec::new();
+        if num_events > 0 {
+            self.dfs(&tree, 1, 0, num_events, &mut dsu, &mut answers);
+        }
+        answers
+    }
+
+    fn dfs(
+        &self,
+        tree: &[Vec<(usize, usize)>],
+        node: usize,
+        left: usize,
+        right: usize,
+        dsu: &mut RollbackDisjointSetUnion<()>,
+        answers: &mut Vec<bool>,
+    ) {
+        let snapshot = dsu.snapshot();
+        for &(u, v) in &tree[node] {
+            dsu.merge(u, v);
+        }
+        if right - left == 1 {
+            if let Event::Query(u, v) = self.events[left] {
+                answers.push(dsu.same_set(u, v));
+            }
+        } else {
+            let mid = (left + right) / 2;
+            self.dfs(tree, 2 * node, left, mid, dsu, answers);
+            self.dfs(tree, 2 * node + 1, mid, right, dsu, answers);
+        }
+        dsu.rollback(snapshot);
+    }
+}
+
+// Stores the edge in the nodes of the segment tree that cover [start, end)
+fn add_interval(
+    tree: &mut [Vec<(usize, usize)>],
+    node: usize,
+    left: usize,
+    right: usize,
+    start: usize,
+    end: usize,
+    edge: (usize, usize),
+) {
+    if end <= left || right <= start {
+        return;
+    }
+    if start <= left && right <= end {
+        tree[node].push(edge);
+        return;
+    }
+    let mid = (left + right) / 2;
+    add_interval(tree, 2 * node, left, mid, start, end, edge);
+    add_interval(tree, 2 * node + 1, mid, right, start, end, edge);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::DisjointSetUnion;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn small_example() {
+        let mut dc = DynamicConnectivity::new(4);
+        dc.query(1, 2);
+        dc.add_edge(1, 2);
+        dc.add_edge(2, 3);
+        dc.query(1, 3);
+        dc.add_edge(3, 1);
+        dc.remove_edge(2, 1);
+        dc.query(1, 2);
+        dc.remove_edge(1, 3);
+        dc.query(1, 2);
+        dc.query(4, 4);
+        assert_eq!(dc.solve(), vec![false, true, true, false, true]);
+        assert!(DynamicConnectivity::new(3).solve().is_empty());
+    }
+
+    #[test]
+    fn parallel_edges() {
+        let mut dc = DynamicConnectivity::new(2);
+        dc.add_edge(1, 2);
+        dc.add_edge(2, 1);
+        dc.remove_edge(1, 2);
+        dc.query(1, 2);
+        dc.remove_edge(1, 2);
+        dc.query(1, 2);
+        assert_eq!(dc.solve(), vec![true, false]);
+    }
+
+    #[test]
+    #[should_panic(expected = "not in the graph")]
+    fn removing_a_missing_edge() {
+        let mut dc = DynamicConnectivity::new(2);
+        dc.add_edge(0, 1);
+        dc.remove_edge(1, 2);
+        dc.solve();
+    }
+
+    #[test]
+    fn random_operations_match_rebuilt_dsu() {
+        let mut rng = StdRng::seed_from_u64(2);
+        let n = 12;
+        let mut dc = DynamicConnectivity::new(n);
+        let mut edges: Vec<(usize, usize)> = Vec::new();
+        let mut expected = Vec::new();
+        for _ in 0..1000 {
+            match rng.gen_range(0..3) {
+                0 => {
+                    let edge = (rng.gen_range(0..=n), rng.gen_range(0..=n));
+                    dc.add_edge(edge.0, edge.1);
+                    edges.push(edge);
+                }
+                1 if !edges.is_empty() => {
+                    let (u, v) = edges.swap_remove(rng.gen_range(0..edges.len()));
+                    dc.remove_edge(v, u);
+                }
+                _ => {
+                    let (u, v) = (rng.gen_range(0..=n), rng.gen_range(0..=n));
+                    dc.query(u, v);
+                    let mut dsu = DisjointSetUnion::new(n);
+                    for &(a, b) in &edges {
+                        dsu.merge(a, b);
+                    }
+                    expected.push(dsu.find_set(u) == dsu.find_set(v));
+                }
+            }
+        }
+        assert_eq!(dc.solve(), expected);
+    }
+}
@@ -0,0 +1,370 @@
+/*
+Flows with lower bounds:
+Every edge u -> v carries a flow between a lower and an upper bound.
+
+Sending the lower bound of every edge first leaves an excess of flow at some
+vertices and a deficit at others, and the remaining flow of each edge is
+between 0 and upper - lower. A new source S gives every vertex its excess, and
+a new sink T takes every deficit: a feasible circulation exists if and only if
+the maximum flow from S to T saturates all of these edges.
+
+A flow from s to t is a circulation once edges t -> s and s -> t of unbounded
+capacity are added (the second one for flows of negative value, where the
+bounds force more flow from t to s than the other way). From a feasible flow,
+removing these edges and augmenting from s to t (or from t to s) in the
+residual network gives the maximum (or minimum) flow; the edges of S and T are
+saturated, so they can't be used to break the bounds.
+
+The maximum flows are computed with `DinicMaxFlow`, whose vertices are
+numbered from 1 to n, and so are the ones here.
+*/
+
+use std::fmt::{self, Debug, Display};
+use std::ops::{Add, AddAssign, Neg, Sub, SubAssign};
+
+use super::DinicMaxFlow;
+
+/// The error returned when no flow satisfies all the bounds. `shortfall` is
+/// how much of the lower bounds could not be met.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InfeasibleFlow<T> {
+    pub shortfall: T,
+}
+
+impl<T: Debug> Display for InfeasibleFlow<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "no flow satisfies the bounds, {:?} of the lower bounds can't be met",
+            self.shortfall
+        )
+    }
+}
+
+impl<T: Debug> std::error::Error for InfeasibleFlow<T> {}
+
+/// A flow satisfying the bounds: its value (zero for a circulation) and the
+/// flow on each edge, in the order they were added
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BoundedFlow<T> {
+    pub value: T,
+    pub flows: Vec<T>,
+}
+
+struct BoundedEdge<T> {
+    source: usize,
+    sink: usize,
+    lower: T,
+    upper: T,
+}
+
+pub struct LowerBoundedFlow<T> {
+    pub num_vertices: usize,
+    edges: Vec<BoundedEdge<T>>,
+}
+
+impl<T: Clone + Copy + Add + AddAssign + Sub<Output = T> + SubAssign + Neg + Ord + Default>
+    LowerBoundedFlow<T>
+{
+    pub fn new(num_vertices: usize) -> Self {
+        LowerBoundedFlow {
+            num_vertices,
+            edges: vec![],
+        }
+    }
+
+    /// Adds an edge whose flow must be between `lower` and `upper`, and
+    /// returns its index in the results
+    pub fn add_edge(&mut self, source: usize, sink: usize, lower: T, upper: T) -> usize {
+        self.edges.push(BoundedEdge {
+            source,
+            sink,
+            lower,
+            upper,
+        });
+        self.edges.len() - 1
+    }
+
+    /// Finds a circulation, where every vertex has as much flow coming in as
+    /// going out, that satisfies the bounds
+    pub fn feasible_circulation(
+        &self,
+        infinite_flow: T,
+    ) -> Result<BoundedFlow<T>, InfeasibleFlow<T>> {
+        let network = self.feasible_network(None, infinite_flow)?;
+        Ok(BoundedFlow {
+            value: T::default(),
+            flows: self.edge_flows(&network),
+        })
+    }
+
+    /// Finds any flow from `source` to `sink` that satisfies the bounds. Its
+    /// value is negative if more flow goes from `sink` to `source`.
+    pub fn feasible_flow(
+        &self,
+        source: usize,
+        sink: usize,
+        infinite_flow: T,
+    ) -> Result<BoundedFlow<T>, InfeasibleFlow<T>> {
+        let mut network = self.feasible_network(Some((source, sink)), infinite_flow)?;
+        let value = self.remove_terminal_edges(&mut network);
+        Ok(BoundedFlow {
+            value,
+            flows: self.edge_flows(&network),
+        })
+    }
+
+    /// Finds the maximum flow from `source` to `sink` that satisfies the bounds
+    pub fn max_flow(
+        &self,
+        source: usize,
+        sink: usize,
+        infinite_flow: T,
+    ) -> Result<BoundedFlow<T>, InfeasibleFlow<T>> {
+        let mut network = self.feasible_network(Some((source, sink)), infinite_flow)?;
+        let mut value = self.remove_terminal_edges(&mut network);
+        network.source = source;
+        network.sink = sink;
+        value += network.find_maxflow(infinite_flow);
+        Ok(BoundedFlow {
+            value,
+            flows: self.edge_flows(&network),
+        })
+    }
+
+    /// Finds the minimum flow from `source` to `sink` that satisfies the
+    /// bounds. It is negative if the flow must go from `sink` to `source`.
+    pub fn min_flow(
+        &self,
+        source: usize,
+        sink: usize,
+        infinite_flow: T,
+    ) -> Result<BoundedFlow<T>, InfeasibleFlow<T>> {
+        let mut network = self.feasible_network(Some((source, sink)), infinite_flow)?;
+        let mut value = self.remove_terminal_edges(&mut network);
+        // sending flow back from the sink to the source reduces the flow
+        network.source = sink;
+        network.sink = source;
+        value -= network.find_maxflow(infinite_flow);
+        Ok(BoundedFlow {
+            value,
+            flows: self.edge_flows(&network),
+        })
+    }
+
+    // Builds the network with a new source and sink for the excesses and
+    // deficits, and edges between the terminals if there is a flow to find,
+    // and finds a flow satisfying the lower bounds. The edges are added in the
+    // same order as `self.edges`, then come the terminal edges.
+    fn feasible_network(
+        &self,
+        terminals: Option<(usize, usize)>,
+        infinite_flow: T,
+    ) -> Result<DinicMaxFlow<T>, InfeasibleFlow<T>> {
+        let n = self.num_vertices;
+        let (new_source, new_sink) = (n + 1, n + 2);
+        let mut network = DinicMaxFlow::new(new_source, new_sink, n + 2);
+        let mut excess = vec![T::default(); n + 1];
+        let mut shortfall = T::default();
+        for edge in &self.edges {
+            if edge.lower > edge.upper {
+                shortfall += edge.lower - edge.upper;
+            }
+            network.add_edge(edge.source, edge.sink, edge.upper - edge.lower);
+            excess[edge.sink] += edge.lower;
+            excess[edge.source] -= edge.lower;
+        }
+        if shortfall > T::default() {
+            return Err(InfeasibleFlow { shortfall });
+        }
+        if let Some((source, sink)) = terminals {
+            network.add_edge(sink, source, infinite_flow);
+            network.add_edge(source, sink, infinite_flow);
+        }
+        let mut required = T::default();
+        for (v, &e) in excess.iter().enumerate().skip(1) {
+            if e > T::default() {
+                network.add_edge(new_source, v, e);
+                required += e;
+            } else if e < T::default() {
+                network.add_edge(v, new_sink, T::default() - e);
+            }
+        }
+        let flow = network.find_maxflow(infinite_flow);
+        if flow < required {
+            Err(InfeasibleFlow {
+                shortfall: required - flow,
+            })
+        } else {
+            Ok(network)
+        }
+    }
+
+    // Removes the edges between the terminals, and returns the value of the
+    // flow from the source to the sink: what goes back through sink -> source
+    fn remove_terminal_edges(&self, network: &mut DinicMaxFlow<T>) -> T {
+        let (back, forward) = (2 * self.edges.len(), 2 * self.edges.len() + 2);
+        let value = network.edges[back].flow - network.edges[forward].flow;
+        for e in [back, back + 1, forward, forward + 1] {
+            network.edges[e].capacity = T::default();
+            network.edges[e].flow = T::default();
+        }
+        value
+    }
+
+    fn edge_flows(&self, network: &DinicMaxFlow<T>) -> Vec<T> {
+        self.edges
+            .iter()
+            .enumerate()
+            .map(|(i, edge)| {
+                let mut flow = edge.lower;
+                flow += network.edges[2 * i].flow;
+                flow
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    type Edge = (usize, usize, i32, i32);
+
+    // Whether the flows satisfy the bounds, and the net flow out of each vertex
+    fn net_outflow(n: usize, edges: &[Edge], flows: &[i32]) -> Option<Vec<i32>> {
+        let mut out = vec![0; n + 1];
+        for (&(u, v, lower, upper), &flow) in edges.iter().zip(flows) {
+            if flow < lower || flow > upper {
+                return None;
+            }
+            out[u] += flow;
+            out[v] -= flow;
+        }
+        Some(out)
+    }
+
+    fn network(n: usize, edges: &[Edge]) -> LowerBoundedFlow<i32> {
+        let mut flow = LowerBoundedFlow::new(n);
+        for &(u, v, lower, upper) in edges {
+            flow.add_edge(u, v, lower, upper);
+        }
+        flow
+    }
+
+    #[test]
+    fn circulation() {
+        // a cycle 1 -> 2 -> 3 -> 1 with a shortcut 1 -> 3
+        let edges = [(1, 2, 2, 5), (2, 3, 3, 4), (3, 1, 0, 10), (1, 3, 1, 2)];
+        let result = network(3, &edges).feasible_circulation(i32::MAX).unwrap();
+        let out = net_outflow(3, &edges, &result.flows).unwrap();
+        assert!(out.iter().all(|&f| f == 0));
+        assert_eq!(result.value, 0);
+
+        // 2 -> 3 needs at least 5 but 1 -> 2 gives at most 4
+        let edges = [(1, 2, 0, 4), (2, 3, 5, 6), (3, 1, 0, 10)];
+        let err = network(3, &edges)
+            .feasible_circulation(i32::MAX)
+            .unwrap_err();
+        assert_eq!(err, InfeasibleFlow { shortfall: 1 });
+        assert_eq!(
+            err.to_string(),
+            "no flow satisfies the bounds, 1 of the lower bounds can't be met"
+        );
+        let bad_edge = [(1, 2, 3, 2)];
+        assert!(network(2, &bad_edge)
+            .feasible_circulation(i32::MAX)
+            .is_err());
+    }
+
+    #[test]
+    fn min_and_max_flow() {
+        // 1 -> 2 -> 4 and 1 -> 3 -> 4, with 3 -> 4 needing at least 3
+        let edges = [(1, 2, 0, 4), (2, 4, 1, 3), (1, 3, 0, 5), (3, 4, 3, 6)];
+        let flow = network(4, &edges);
+        assert_eq!(flow.max_flow(1, 4, i32::MAX).unwrap().value, 8);
+        assert_eq!(flow.min_flow(1, 4, i32::MAX).unwrap().value, 4);
+        let feasible = flow.feasible_flow(1, 4, i32::MAX).unwrap();
+        assert!((4..=8).contains(&feasible.value));
+        let out = net_outflow(4, &edges, &feasible.flows).unwrap();
+        assert_eq!(out, vec![0, feasible.value, 0, 0, -feasible.value]);
+
+        // nothing reaches 3 -> 4
+        let edges = [(1, 2, 0, 4), (3, 4, 1, 1)];
+        assert_eq!(
+            network(4, &edges).max_flow(1, 4, i32::MAX),
+            Err(InfeasibleFlow { shortfall: 1 })
+        );
+    }
+
+    #[test]
+    fn random_networks_match_brute_force() {
+        let mut rng = StdRng::seed_from_u64(25);
+        for _ in 0..200 {
+            let n = rng.gen_range(2..=4);
+            let edges: Vec<Edge> = (0..rng.gen_range(1..=5))
+                .map(|_| {
+                    let lower = rng.gen_range(0..=2);
+                    (
+                        rng.gen_range(1..=n),
+                        rng.gen_range(1..=n),
+                        lower,
+                        lower + rng.gen_range(0..=2),
+                    )
+                })
+                .collect();
+            let (source, sink) = (1, n);
+
+            // every assignment of flows within the bounds
+            let mut values = vec![];
+            let mut circulation = false;
+            let mut flows: Vec<i32> = edges.iter().map(|e| e.2).collect();
+            loop {
+                let out = net_outflow(n, &edges, &flows).unwrap();
+                if out[2..n].iter().all(|&f| f == 0) && out[source] == -out[sink] {
+                    values.push(out[source]);
+                }
+                circulation |= out.iter().all(|&f| f == 0);
+                // next assignment
+                let mut i = 0;
+                while i < edges.len() && flows[i] == edges[i].3 {
+                    flows[i] = edges[i].2;
+                    i += 1;
+                }
+                if i == edges.len() {
+                    break;
+                }
+                flows[i] += 1;
+            }
+
+            let flow = network(n, &edges);
+            match flow.feasible_circulation(i32::MAX) {
+                Ok(result) => {
+                    let out = net_outflow(n, &edges, &result.flows).unwrap();
+                    assert!(out.iter().all(|&f| f == 0));
+                }
+                Err(_) => assert!(!circulation),
+            }
+            let max = flow.max_flow(source, sink, i32::MAX);
+            let min = flow.min_flow(source, sink, i32::MAX);
+            match (values.iter().max(), values.iter().min()) {
+                (Some(&best), Some(&least)) => {
+                    let (max, min) = (max.unwrap(), min.unwrap());
+                    assert_eq!(max.value, best);
+                    assert_eq!(min.value, least);
+                    for result in [max, min] {
+                        let out = net_outflow(n, &edges, &result.flows).unwrap();
+                        assert_eq!(out[source], result.value);
+                    }
+                    assert!(flow.feasible_flow(source, sink, i32::MAX).is_ok());
+                }
+                _ => {
+                    assert!(max.is_err() && min.is_err());
+                    assert!(flow.feasible_flow(source, sink, i32::MAX).is_err());
+                }
+            }
+        }
+    }
+}
@@ -2,7 +2,7 @@ use num_traits::Zero;
 use std::collections::BTreeMap;
 use std::ops::Add;
 
-type Graph<V, E> = BTreeMap<V, BTreeMap<V, E>>;
+use super::WeightedGraph;
 
 /// Performs the Floyd-Warshall algorithm on the input graph.\
 /// The graph is a weighted, directed graph with no negative cycles.
@@ -15,21 +15,15 @@ type Graph<V, E> = BTreeMap<V, BTreeMap<V, E>>;
 /// For a key `v`, if `map[v].len() == 0`, then `v` cannot reach any other vertex, but is in the graph
 /// (island node, or sink in the case of a directed graph)
 pub fn floyd_warshall<V: Ord + Copy, E: Ord + Copy + Add<Output = E> + num_traits::Zero>(
-    graph: &Graph<V, E>,
+    graph: &impl WeightedGraph<Vertex = V, Weight = E>,
 ) -> BTreeMap<V, BTreeMap<V, E>> {
     let mut map: BTreeMap<V, BTreeMap<V, E>> = BTreeMap::new();
-    for (u, edges) in graph.iter() {
-        if !map.contains_key(u) {
-            map.insert(*u, BTreeMap::new());
-        }
-        map.entry(*u).or_default().insert(*u, Zero::zero());
-        for (v, weight) in edges.iter() {
-            if !map.contains_key(v) {
-                map.insert(*v, BTreeMap::new());
-            }
-            map.entry(*v).or_default().insert(*v, Zero::zero());
-            map.entry(*u).and_modify(|mp| {
-                mp.insert(*v, *weight);
+    for u in graph.vertices() {
+        map.entry(u).or_default().insert(u, Zero::zero());
+        for (v, weight) in graph.neighbors(u) {
+            map.entry(v).or_default().insert(v, Zero::zero());
+            map.entry(u).and_modify(|mp| {
+                mp.insert(v, weight);
             });
         }
     }
@@ -67,9 +61,11 @@ pub fn floyd_warshall<V: Ord + Copy, E: Ord + Copy + Add<Output = E> + num_trait
 
 #[cfg(test)]
 mod tests {
-    use super::{floyd_warshall, Graph};
+    use super::floyd_warshall;
     use std::collections::BTreeMap;
 
+    type Graph<V, E> = BTreeMap<V, BTreeMap<V, E>>;
+
     fn add_edge<V: Ord + Copy, E: Ord + Copy>(graph: &mut Graph<V, E>, v1: V, v2: V, c: E) {
         graph.entry(v1).or_default().insert(v2, c);
     }
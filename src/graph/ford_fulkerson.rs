@@ -15,10 +15,10 @@ The following is simple idea of Ford-Fulkerson algorithm:
 */
 use std::collections::VecDeque;
 
-const V: usize = 6; // Number of vertices in graph
+use super::{FlowResultEdge, MinCut};
 
 pub fn bfs(r_graph: &mut [Vec<i32>], s: usize, t: usize, parent: &mut [i32]) -> bool {
-    let mut visited = [false; V];
+    let mut visited = vec![false; r_graph.len()];
     visited[s] = true;
     parent[s] = -1;
 
@@ -26,7 +26,7 @@ pub fn bfs(r_graph: &mut [Vec<i32>], s: usize, t: usize, parent: &mut [i32]) ->
     queue.push_back(s);
 
     while let Some(u) = queue.pop_front() {
-        for v in 0..V {
+        for v in 0..r_graph.len() {
             if !visited[v] && r_graph[u][v] > 0 {
                 visited[v] = true;
                 parent[v] = u as i32; // Convert u to i32
@@ -41,9 +41,10 @@ pub fn bfs(r_graph: &mut [Vec<i32>], s: usize, t: usize, parent: &mut [i32]) ->
     false
 }
 
-pub fn ford_fulkerson(graph: &mut [Vec<i32>], s: usize, t: usize) -> i32 {
+// Returns the maximum flow and the residual graph it leaves
+fn max_flow_residual(graph: &[Vec<i32>], s: usize, t: usize) -> (i32, Vec<Vec<i32>>) {
     let mut r_graph = graph.to_owned();
-    let mut parent = vec![-1; V];
+    let mut parent = vec![-1; graph.len()];
     let mut max_flow = 0;
 
     while bfs(&mut r_graph, s, t, &mut parent) {
@@ -67,7 +68,43 @@ pub fn ford_fulkerson(graph: &mut [Vec<i32>], s: usize, t: usize) -> i32 {
         max_flow += path_flow;
     }
 
-    max_flow
+    (max_flow, r_graph)
+}
+
+pub fn ford_fulkerson(graph: &mut [Vec<i32>], s: usize, t: usize) -> i32 {
+    max_flow_residual(graph, s, t).0
+}
+
+/// Finds a minimum s-t cut of the network given by its capacity matrix.
+/// The source side holds the vertices that the source can still reach in the
+/// residual graph once the maximum flow is found, and every edge from the source
+/// side to the other side is saturated.
+pub fn ford_fulkerson_min_cut(graph: &[Vec<i32>], s: usize, t: usize) -> MinCut<i32> {
+    let (max_flow, mut r_graph) = max_flow_residual(graph, s, t);
+    // A last search fails to reach the sink, but marks the source side
+    let mut parent = vec![-1; graph.len()];
+    bfs(&mut r_graph, s, t, &mut parent);
+    let source_side: Vec<usize> = (0..graph.len())
+        .filter(|&v| v == s || parent[v] != -1)
+        .collect();
+
+    let mut cut_edges = Vec::new();
+    for &u in &source_side {
+        for (v, &capacity) in graph[u].iter().enumerate() {
+            if capacity > 0 && !source_side.contains(&v) {
+                cut_edges.push(FlowResultEdge {
+                    source: u,
+                    sink: v,
+                    flow: capacity,
+                });
+            }
+        }
+    }
+    MinCut {
+        capacity: max_flow,
+        source_side,
+        cut_edges,
+    }
 }
 
 #[cfg(test)]
@@ -138,4 +175,47 @@ mod tests {
         ];
         assert_eq!(ford_fulkerson(&mut graph, 0, 5), 23);
     }
+
+    #[test]
+    fn test_min_cut() {
+        let graph = vec![
+            vec![0, 16, 13, 0, 0, 0],
+            vec![0, 0, 10, 12, 0, 0],
+            vec![0, 4, 0, 0, 14, 0],
+            vec![0, 0, 9, 0, 0, 20],
+            vec![0, 0, 0, 7, 0, 4],
+            vec![0, 0, 0, 0, 0, 0],
+        ];
+        let cut = ford_fulkerson_min_cut(&graph, 0, 5);
+        assert_eq!(cut.capacity, 23);
+        assert_eq!(cut.source_side, vec![0, 1, 2, 4]);
+        let cut_edges: Vec<_> = cut
+            .cut_edges
+            .iter()
+            .map(|e| (e.source, e.sink, e.flow))
+            .collect();
+        assert_eq!(cut_edges, vec![(1, 3, 12), (4, 3, 7), (4, 5, 4)]);
+    }
+
+    #[test]
+    fn test_min_cut_larger_graph() {
+        // Two groups of four vertices, joined by two edges of capacity 1
+        let mut graph = vec![vec![0; 8]; 8];
+        for group in [0..4, 4..8] {
+            for u in group.clone() {
+                for v in group.clone() {
+                    if u != v {
+                        graph[u][v] = 5;
+                    }
+                }
+            }
+        }
+        graph[2][5] = 1;
+        graph[3][4] = 1;
+        let cut = ford_fulkerson_min_cut(&graph, 0, 7);
+        assert_eq!(cut.capacity, 2);
+        assert_eq!(ford_fulkerson(&mut graph, 0, 7), 2);
+        assert_eq!(cut.source_side, vec![0, 1, 2, 3]);
+        assert_eq!(cut.cut_edges.len(), 2);
+    }
 }
@@ -1,31 +1,33 @@
-use std::collections::BTreeMap;
-
-type Graph<Vertex> = BTreeMap<Vertex, Vec<Vertex>>;
+use super::weighted_graph::index_graph;
+use super::WeightedGraph;
 
 /*
 This function creates a graph with vertices numbered from 1 to n for any input
-`Graph<V>`. The result is in the form of Vec<Vec<usize> to make implementing
-other algorithms on the graph easier and help with performance.
+`WeightedGraph`, such as `BTreeMap<V, Vec<V>>`. The result is in the form of
+Vec<Vec<usize> to make implementing other algorithms on the graph easier and
+help with performance. Vertices are numbered in the order the graph yields them.
 
 We expect that all vertices, even the isolated ones, to have an entry in `adj`
 (possibly an empty vector)
 */
-pub fn enumerate_graph<V: Ord + Clone>(adj: &Graph<V>) -> Vec<Vec<usize>> {
-    let mut result = vec![vec![]; adj.len() + 1];
-    let ordering: Vec<V> = adj.keys().cloned().collect();
-    for (zero_idx, edges) in adj.values().enumerate() {
-        let idx = zero_idx + 1;
-        result[idx] = edges
-            .iter()
-            .map(|x| ordering.binary_search(x).unwrap() + 1)
-            .collect();
-    }
+pub fn enumerate_graph<V: Ord + Clone>(adj: &impl WeightedGraph<Vertex = V>) -> Vec<Vec<usize>> {
+    let (_, edges) = index_graph(adj);
+    let mut result = vec![vec![]];
+    result.extend(
+        edges
+            .into_iter()
+            .map(|edges| edges.into_iter().map(|x| x + 1).collect()),
+    );
     result
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::collections::BTreeMap;
+
+    type Graph<Vertex> = BTreeMap<Vertex, Vec<Vertex>>;
+
     fn add_edge<V: Ord + Clone>(graph: &mut Graph<V>, a: V, b: V) {
         graph.entry(a.clone()).or_default().push(b.clone());
         graph.entry(b).or_default().push(a);
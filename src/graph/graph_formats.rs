@@ -0,0 +1,1195 @@
+/*
+Reading and writing graphs as text, so that test fixtures can be loaded and
+results (a shortest path tree, an MST, a maximum flow and its minimum cut) can
+be dumped for visualization.
+
+Supported formats:
+- Graphviz DOT: `graph`/`digraph` with node and edge statements. Edge chains
+  (`a -> b -> c`), attribute lists, quoted identifiers and comments are
+  understood; subgraphs are not. The weight of an edge is read from its
+  `weight` attribute, or else from its `label`.
+- Weighted edge lists: one `source destination weight` edge per line, a line
+  with a single vertex declares an isolated vertex and `#` starts a comment line.
+- Adjacency lists: one `vertex: neighbour[:weight] ...` line per vertex, a
+  missing weight being the default value of the weight type. `#` starts a
+  comment line.
+- DIMACS shortest path (`p sp n m` followed by `a u v w` arcs) and DIMACS
+  maximum flow (`p max n m`, `n s s`, `n t t` and `a u v capacity` arcs).
+  Vertices are numbered from 1 to n and `c` starts a comment line.
+
+Every parser reports the line and column (both starting from 1) of the first
+error it finds.
+*/
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{self, Display, Write};
+use std::ops::{Add, AddAssign, Neg, Sub, SubAssign};
+use std::str::FromStr;
+
+use super::{DinicMaxFlow, WeightedGraph};
+use crate::data_structures::Graph;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl ParseError {
+    fn new(line: usize, column: usize, message: impl Into<String>) -> Self {
+        ParseError {
+            line,
+            column,
+            message: message.into(),
+        }
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.message
+        )
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// A parsed graph, before it is turned into one of the graph types.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EdgeList<V, E> {
+    pub directed: bool,
+    /// The vertices that were declared on their own, in order of appearance.
+    /// The endpoints of the edges are not repeated here.
+    pub vertices: Vec<V>,
+    /// The `(source, destination, weight)` edges, in order of appearance
+    pub edges: Vec<(V, V, E)>,
+}
+
+impl<V: Ord + Clone, E: Clone> EdgeList<V, E> {
+    /// The map-of-maps graph used by `dijkstra`, `prim`, `bellman_ford`...
+    /// Undirected edges are stored in both directions.
+    pub fn into_map_graph(self) -> BTreeMap<V, BTreeMap<V, E>> {
+        let mut graph: BTreeMap<V, BTreeMap<V, E>> = BTreeMap::new();
+        for v in self.vertices {
+            graph.entry(v).or_default();
+        }
+        for (u, v, weight) in self.edges {
+            graph.entry(v.clone()).or_default();
+            if !self.directed {
+                graph.get_mut(&v).unwrap().insert(u.clone(), weight.clone());
+            }
+            graph.entry(u).or_default().insert(v, weight);
+        }
+        graph
+    }
+}
+
+impl<V: std::hash::Hash + Eq + Clone, E: Clone> EdgeList<V, E> {
+    /// A `DirectedGraph` or an `UndirectedGraph`. The edges are added as they
+    /// were written, so an undirected file should be loaded in an `UndirectedGraph`.
+    pub fn into_graph<G: Graph<V, E>>(self) -> G {
+        let mut graph = G::new();
+        for v in self.vertices {
+            graph.add_node(v);
+        }
+        for edge in self.edges {
+            graph.add_edge(edge);
+        }
+        graph
+    }
+}
+
+// The whitespace separated words of a line, with their column
+fn words(line: &str) -> Vec<(usize, &str)> {
+    let mut words = Vec::new();
+    let mut start = None;
+    let mut column = 0;
+    for (i, c) in line.char_indices() {
+        column += 1;
+        match (c.is_whitespace(), start) {
+            (false, None) => start = Some((i, column)),
+            (true, Some((begin, word_column))) => {
+                words.push((word_column, &line[begin..i]));
+                start = None;
+            }
+            _ => (),
+        }
+    }
+    if let Some((begin, word_column)) = start {
+        words.push((word_column, &line[begin..]));
+    }
+    words
+}
+
+fn parse_word<T: FromStr>(
+    line: usize,
+    (column, word): (usize, &str),
+    what: &str,
+) -> Result<T, ParseError> {
+    word.parse()
+        .map_err(|_| ParseError::new(line, column, format!("invalid {what} `{word}`")))
+}
+
+// The column right after the end of the line, where a missing word is reported
+fn end_column(line: &str) -> usize {
+    line.trim_end().chars().count() + 1
+}
+
+/// Parses a weighted edge list: `source destination weight` on each line.
+pub fn parse_edge_list<V: FromStr, E: FromStr>(
+    input: &str,
+    directed: bool,
+) -> Result<EdgeList<V, E>, ParseError> {
+    let mut result = EdgeList {
+        directed,
+        vertices: Vec::new(),
+        edges: Vec::new(),
+    };
+    for (i, text) in input.lines().enumerate() {
+        let line = i + 1;
+        let words = words(text);
+        match words.as_slice() {
+            [] => (),
+            [(_, first), ..] if first.starts_with('#') => (),
+            [vertex] => result.vertices.push(parse_word(line, *vertex, "vertex")?),
+            [_, _] => return Err(ParseError::new(line, end_column(text), "expected a weight")),
+            [source, destination, weight] => result.edges.push((
+                parse_word(line, *source, "vertex")?,
+                parse_word(line, *destination, "vertex")?,
+                parse_word(line, *weight, "weight")?,
+            )),
+            [_, _, _, (column, _), ..] => {
+                return Err(ParseError::new(
+                    line,
+                    *column,
+                    "expected the end of the line",
+                ))
+            }
+        }
+    }
+    Ok(result)
+}
+
+// Sorts the vertices so that the output does not depend on the order in which
+// hash based graphs iterate over them
+fn sorted_vertices<G: WeightedGraph>(graph: &G) -> Vec<G::Vertex>
+where
+    G::Vertex: Ord,
+{
+    let mut vertices: Vec<G::Vertex> = graph.vertices().collect();
+    vertices.sort();
+    vertices
+}
+
+// The edges to write: an undirected graph stores its edges in both
+// directions, but they are only written once
+fn edges_to_write<G: WeightedGraph>(
+    graph: &G,
+    directed: bool,
+) -> Vec<(G::Vertex, G::Vertex, G::Weight)>
+where
+    G::Vertex: Ord,
+{
+    let mut edges = Vec::new();
+    for u in sorted_vertices(graph) {
+        for (v, weight) in graph.neighbors(u.clone()) {
+            if directed || u <= v {
+                edges.push((u.clone(), v, weight));
+            }
+        }
+    }
+    edges
+}
+
+/// Writes `graph` as a weighted edge list. Vertices without any edge are
+/// written on their own line. The vertices must not contain whitespace.
+pub fn write_edge_list<G>(graph: &G, directed: bool) -> String
+where
+    G: WeightedGraph,
+    G::Vertex: Ord + Display,
+    G::Weight: Display,
+{
+    let edges = edges_to_write(graph, directed);
+    let endpoints: BTreeSet<&G::Vertex> = edges.iter().flat_map(|(u, v, _)| [u, v]).collect();
+    let mut output = String::new();
+    for v in sorted_vertices(graph) {
+        if !endpoints.contains(&v) {
+            writeln!(output, "{v}").unwrap();
+        }
+    }
+    for (u, v, weight) in edges {
+        writeln!(output, "{u} {v} {weight}").unwrap();
+    }
+    output
+}
+
+// A token or an identifier, with its line and column
+type Located<T> = (T, usize, usize);
+
+#[derive(Debug, PartialEq)]
+enum DotToken {
+    Id { text: String, quoted: bool },
+    Symbol(&'static str),
+}
+
+impl DotToken {
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(self, DotToken::Id { text, quoted: false } if text.eq_ignore_ascii_case(keyword))
+    }
+}
+
+struct DotLexer {
+    chars: Vec<char>,
+    pos: usize,
+    line: usize,
+    column: usize,
+}
+
+impl DotLexer {
+    fn peek(&self, offset: usize) -> Option<char> {
+        self.chars.get(self.pos + offset).copied()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek(0)?;
+        self.pos += 1;
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        Some(c)
+    }
+
+    fn skip_line(&mut self) {
+        while self.peek(0).is_some_and(|c| c != '\n') {
+            self.bump();
+        }
+    }
+
+    // Splits the input into tokens, ready to be parsed
+    fn tokenize(mut self) -> Result<DotParser, ParseError> {
+        let mut tokens = Vec::new();
+        while let Some(c) = self.peek(0) {
+            let (line, column) = (self.line, self.column);
+            let token = match (c, self.peek(1)) {
+                (c, _) if c.is_whitespace() => {
+                    self.bump();
+                    continue;
+                }
+                ('/', Some('/')) | ('#', _) => {
+                    self.skip_line();
+                    continue;
+                }
+                ('/', Some('*')) => {
+                    self.bump();
+                    self.bump();
+                    while !(self.peek(0) == Some('*') && self.peek(1) == Some('/')) {
+                        if self.bump().is_none() {
+                            return Err(ParseError::new(line, column, "unterminated comment"));
+                        }
+                    }
+                    self.bump();
+                    self.bump();
+                    continue;
+                }
+                ('"', _) => {
+                    self.bump();
+                    let mut text = String::new();
+                    loop {
+                        match self.bump() {
+                            None => {
+                                return Err(ParseError::new(line, column, "unterminated string"))
+                            }
+                            Some('"') => break,
+                            Some('\\') if matches!(self.peek(0), Some('"' | '\\')) => {
+                                text.push(self.bump().unwrap())
+                            }
+                            Some(c) => text.push(c),
+                        }
+                    }
+                    DotToken::Id { text, quoted: true }
+                }
+                ('-', Some('>')) | ('-', Some('-')) => {
+                    self.bump();
+                    let arrow = self.bump() == Some('>');
+                    DotToken::Symbol(if arrow { "->" } else { "--" })
+                }
+                (c, next)
+                    if c.is_alphanumeric()
+                        || c == '_'
+                        || c == '.'
+                        || (c == '-' && next.is_some_and(|n| n.is_ascii_digit() || n == '.')) =>
+                {
+                    let mut text = String::new();
+                    text.push(self.bump().unwrap());
+                    while let Some(c) = self.peek(0) {
+                        if !(c.is_alphanumeric() || c == '_' || c == '.') {
+                            break;
+                        }
+                        text.push(c);
+                        self.bump();
+                    }
+                    DotToken::Id {
+                        text,
+                        quoted: false,
+                    }
+                }
+                _ => {
+                    let symbol = ["{", "}", "[", "]", ";", ",", "="]
+                        .into_iter()
+                        .find(|s| s.starts_with(c))
+                        .ok_or_else(|| {
+                            ParseError::new(line, column, format!("unexpected character `{c}`"))
+                        })?;
+                    self.bump();
+                    DotToken::Symbol(symbol)
+                }
+            };
+            tokens.push((token, line, column));
+        }
+        Ok(DotParser {
+            tokens,
+            pos: 0,
+            end: (self.line, self.column),
+        })
+    }
+}
+
+struct DotParser {
+    tokens: Vec<Located<DotToken>>,
+    pos: usize,
+    end: (usize, usize),
+}
+
+impl DotParser {
+    fn peek(&self) -> Option<&DotToken> {
+        self.tokens.get(self.pos).map(|(token, _, _)| token)
+    }
+
+    // The position of the next token, or of the end of the input
+    fn position(&self) -> (usize, usize) {
+        self.tokens
+            .get(self.pos)
+            .map_or(self.end, |&(_, line, column)| (line, column))
+    }
+
+    fn error(&self, message: impl Into<String>) -> ParseError {
+        let (line, column) = self.position();
+        ParseError::new(line, column, message)
+    }
+
+    fn symbol(&mut self, symbol: &str) -> bool {
+        let found = matches!(self.peek(), Some(DotToken::Symbol(s)) if *s == symbol);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn expect_symbol(&mut self, symbol: &str) -> Result<(), ParseError> {
+        if self.symbol(symbol) {
+            Ok(())
+        } else {
+            Err(self.error(format!("expected `{symbol}`")))
+        }
+    }
+
+    // An identifier, with its position
+    fn id(&mut self, what: &str) -> Result<Located<String>, ParseError> {
+        match self.tokens.get(self.pos) {
+            Some((DotToken::Id { text, .. }, line, column)) => {
+                self.pos += 1;
+                Ok((text.clone(), *line, *column))
+            }
+            _ => Err(self.error(format!("expected {what}"))),
+        }
+    }
+
+    fn parse_id<T: FromStr>(&mut self, what: &str) -> Result<T, ParseError> {
+        let (text, line, column) = self.id(what)?;
+        parse_word(line, (column, &text), what)
+    }
+
+    // `[key = value, ...]`, if there is one
+    fn attributes(&mut self) -> Result<Vec<(String, Located<String>)>, ParseError> {
+        let mut attributes = Vec::new();
+        if !self.symbol("[") {
+            return Ok(attributes);
+        }
+        while !self.symbol("]") {
+            if self.symbol(",") || self.symbol(";") {
+                continue;
+            }
+            let (key, _, _) = self.id("an attribute name or `]`")?;
+            self.expect_symbol("=")?;
+            attributes.push((key, self.id("an attribute value")?));
+        }
+        Ok(attributes)
+    }
+}
+
+/// Parses a Graphviz DOT graph. Every edge needs a `weight` or a `label`
+/// attribute holding its weight.
+pub fn parse_dot<V: FromStr + Clone, E: FromStr + Clone>(
+    input: &str,
+) -> Result<EdgeList<V, E>, ParseError> {
+    let lexer = DotLexer {
+        chars: input.chars().collect(),
+        pos: 0,
+        line: 1,
+        column: 1,
+    };
+    let mut parser = lexer.tokenize()?;
+
+    if parser.peek().is_some_and(|t| t.is_keyword("strict")) {
+        parser.pos += 1;
+    }
+    let directed = match parser.peek() {
+        Some(t) if t.is_keyword("digraph") => true,
+        Some(t) if t.is_keyword("graph") => false,
+        _ => return Err(parser.error("expected `graph` or `digraph`")),
+    };
+    parser.pos += 1;
+    if let Some(DotToken::Id { .. }) = parser.peek() {
+        parser.pos += 1;
+    }
+    parser.expect_symbol("{")?;
+
+    let mut result = EdgeList {
+        directed,
+        vertices: Vec::new(),
+        edges: Vec::new(),
+    };
+    loop {
+        if parser.symbol("}") {
+            break;
+        }
+        if parser.symbol(";") {
+            continue;
+        }
+        match parser.peek() {
+            Some(t) if ["graph", "node", "edge"].iter().any(|k| t.is_keyword(k)) => {
+                parser.pos += 1;
+                parser.attributes()?;
+                continue;
+            }
+            Some(DotToken::Id { .. }) => (),
+            Some(t) if t.is_keyword("subgraph") || *t == DotToken::Symbol("{") => {
+                return Err(parser.error("subgraphs are not supported"))
+            }
+            Some(_) => return Err(parser.error("expected a statement or `}`")),
+            None => return Err(parser.error("expected `}`")),
+        }
+        // `name = value` sets an attribute of the graph
+        if matches!(
+            parser.tokens.get(parser.pos + 1),
+            Some((DotToken::Symbol("="), _, _))
+        ) {
+            parser.pos += 2;
+            parser.id("an attribute value")?;
+            continue;
+        }
+
+        let mut chain = vec![parser.parse_id::<V>("vertex")?];
+        let first_edge = parser.position();
+        loop {
+            let (line, column) = parser.position();
+            let edge_op = if directed { "->" } else { "--" };
+            let wrong_op = if directed { "--" } else { "->" };
+            if parser.symbol(wrong_op) {
+                let kind = if directed { "digraph" } else { "graph" };
+                return Err(ParseError::new(
+                    line,
+                    column,
+                    format!("`{wrong_op}` cannot be used in a {kind}"),
+                ));
+            }
+            if !parser.symbol(edge_op) {
+                break;
+            }
+            chain.push(parser.parse_id("vertex")?);
+        }
+        let attributes = parser.attributes()?;
+
+        if chain.len() == 1 {
+            result.vertices.extend(chain);
+            continue;
+        }
+        let weight = ["weight", "label"]
+            .iter()
+            .find_map(|key| attributes.iter().find(|(k, _)| k == key))
+            .ok_or_else(|| {
+                ParseError::new(
+                    first_edge.0,
+                    first_edge.1,
+                    "the edge has no weight or label",
+                )
+            })?;
+        let (text, line, column) = &weight.1;
+        let weight: E = parse_word(*line, (*column, text), "weight")?;
+        for pair in chain.windows(2) {
+            result
+                .edges
+                .push((pair[0].clone(), pair[1].clone(), weight.clone()));
+        }
+    }
+    if parser.peek().is_some() {
+        return Err(parser.error("expected the end of the input"));
+    }
+    Ok(result)
+}
+
+fn quote(text: impl Display) -> String {
+    let text = text.to_string().replace('\\', "\\\\").replace('"', "\\\"");
+    format!("\"{text}\"")
+}
+
+const HIGHLIGHT: &str = ", color=red, penwidth=2";
+
+fn dot<G>(graph: &G, directed: bool, highlighted: impl Fn(&G::Vertex, &G::Vertex) -> bool) -> String
+where
+    G: WeightedGraph,
+    G::Vertex: Ord + Display,
+    G::Weight: Display,
+{
+    let (kind, edge_op) = if directed {
+        ("digraph", "->")
+    } else {
+        ("graph", "--")
+    };
+    let mut output = format!("{kind} {{\n");
+    for v in sorted_vertices(graph) {
+        writeln!(output, "    {};", quote(v)).unwrap();
+    }
+    for (u, v, weight) in edges_to_write(graph, directed) {
+        let style = if highlighted(&u, &v) { HIGHLIGHT } else { "" };
+        writeln!(
+            output,
+            "    {} {edge_op} {} [label={}{style}];",
+            quote(&u),
+            quote(&v),
+            quote(weight)
+        )
+        .unwrap();
+    }
+    output.push_str("}\n");
+    output
+}
+
+/// Writes `graph` in the DOT format, with the weights as edge labels.
+/// Each edge of an undirected graph is written once.
+pub fn write_dot<G>(graph: &G, directed: bool) -> String
+where
+    G: WeightedGraph,
+    G::Vertex: Ord + Display,
+    G::Weight: Display,
+{
+    dot(graph, directed, |_, _| false)
+}
+
+/// Like `write_dot`, drawing in red the edges that are also in `highlighted`,
+/// for example the MST returned by `prim` or the path found by `dijkstra`.
+pub fn write_dot_highlighted<G, H>(graph: &G, directed: bool, highlighted: &H) -> String
+where
+    G: WeightedGraph,
+    G::Vertex: Ord + Display,
+    G::Weight: Display,
+    H: WeightedGraph<Vertex = G::Vertex>,
+{
+    dot(graph, directed, |u, v| {
+        highlighted.neighbors(u.clone()).any(|(w, _)| w == *v)
+            || (!directed && highlighted.neighbors(v.clone()).any(|(w, _)| w == *u))
+    })
+}
+
+/// Writes a flow network in the DOT format, labelling every edge with
+/// `flow/capacity`. Once `find_maxflow` has run, the vertices on the source
+/// side of the minimum cut are filled and the edges of the cut are drawn in red.
+pub fn write_flow_dot<T: Copy + Display + PartialOrd>(flow: &DinicMaxFlow<T>) -> String {
+    let source_side = flow.reachable_in_residual();
+    let mut output = String::from("digraph {\n");
+    for (v, &filled) in source_side.iter().enumerate().skip(1) {
+        let style = if filled {
+            " [style=filled, fillcolor=lightblue]"
+        } else {
+            ""
+        };
+        writeln!(output, "    {}{style};", quote(v)).unwrap();
+    }
+    // Even edges are the ones that were added, odd edges are their reverse
+    for e in (0..flow.num_edges).step_by(2) {
+        let (u, edge) = (flow.edges[e + 1].sink, &flow.edges[e]);
+        let in_cut = source_side[u] && !source_side[edge.sink];
+        writeln!(
+            output,
+            "    {} -> {} [label=\"{}/{}\"{}];",
+            quote(u),
+            quote(edge.sink),
+            edge.flow,
+            edge.capacity,
+            if in_cut { HIGHLIGHT } else { "" }
+        )
+        .unwrap();
+    }
+    output.push_str("}\n");
+    output
+}
+
+// The non empty lines of a DIMACS file that are not comments, with their number
+fn dimacs_lines(input: &str) -> impl Iterator<Item = (usize, Vec<(usize, &str)>)> {
+    input
+        .lines()
+        .enumerate()
+        .map(|(i, text)| (i + 1, words(text)))
+        .filter(|(_, words)| !matches!(words.first(), None | Some((_, "c"))))
+}
+
+// Checks that a line has exactly `count` words
+fn expect_words(line: usize, words: &[(usize, &str)], count: usize) -> Result<(), ParseError> {
+    match words.get(count) {
+        Some(&(column, _)) => Err(ParseError::new(
+            line,
+            column,
+            "expected the end of the line",
+        )),
+        None if words.len() < count => {
+            let (column, last) = words[words.len() - 1];
+            Err(ParseError::new(
+                line,
+                column + last.chars().count(),
+                format!("expected {count} fields"),
+            ))
+        }
+        None => Ok(()),
+    }
+}
+
+// A vertex of a DIMACS file, which must be between 1 and n
+fn dimacs_vertex(line: usize, word: (usize, &str), n: usize) -> Result<usize, ParseError> {
+    let v = parse_word(line, word, "vertex")?;
+    if v == 0 || v > n {
+        return Err(ParseError::new(
+            line,
+            word.0,
+            format!("vertex {v} is not between 1 and {n}"),
+        ));
+    }
+    Ok(v)
+}
+
+// Reads a DIMACS file of the given problem, calling `line_fn` on every line
+// that follows the problem line with `n`. Returns the problem line, `n` and
+// the number of arcs that was announced
+fn parse_dimacs(
+    input: &str,
+    problem: &str,
+    mut line_fn: impl FnMut(usize, &[(usize, &str)], usize) -> Result<(), ParseError>,
+) -> Result<(usize, usize, (usize, usize)), ParseError> {
+    let mut header = None;
+    for (line, words) in dimacs_lines(input) {
+        match (words[0].1, header) {
+            ("p", None) => {
+                expect_words(line, &words, 4)?;
+                if words[1].1 != problem {
+                    return Err(ParseError::new(
+                        line,
+                        words[1].0,
+                        format!("expected a `{problem}` problem"),
+                    ));
+                }
+                let n = parse_word(line, words[2], "number of vertices")?;
+                let m = parse_word(line, words[3], "number of arcs")?;
+                header = Some((line, n, (words[3].0, m)));
+            }
+            ("p", Some(_)) => {
+                return Err(ParseError::new(line, words[0].0, "duplicate problem line"))
+            }
+            (_, Some((_, n, _))) => line_fn(line, &words, n)?,
+            (_, None) => {
+                return Err(ParseError::new(
+                    line,
+                    words[0].0,
+                    "expected the problem line first",
+                ))
+            }
+        }
+    }
+    let (line, n, m) = header.ok_or_else(|| ParseError::new(1, 1, "missing problem line"))?;
+    Ok((line, n, m))
+}
+
+fn check_arc_count(
+    line: usize,
+    (column, m): (usize, usize),
+    arcs: usize,
+) -> Result<(), ParseError> {
+    if m == arcs {
+        Ok(())
+    } else {
+        Err(ParseError::new(
+            line,
+            column,
+            format!("{m} arcs were announced but {arcs} were given"),
+        ))
+    }
+}
+
+fn unknown_line(line: usize, (column, word): (usize, &str)) -> ParseError {
+    ParseError::new(line, column, format!("unknown line type `{word}`"))
+}
+
+/// Parses a DIMACS shortest path problem. The vertices are `1..=n`.
+pub fn parse_dimacs_shortest_path<E: FromStr>(
+    input: &str,
+) -> Result<EdgeList<usize, E>, ParseError> {
+    let mut edges = Vec::new();
+    let (line, n, m) = parse_dimacs(input, "sp", |line, words, n| {
+        if words[0].1 != "a" {
+            return Err(unknown_line(line, words[0]));
+        }
+        expect_words(line, words, 4)?;
+        edges.push((
+            dimacs_vertex(line, words[1], n)?,
+            dimacs_vertex(line, words[2], n)?,
+            parse_word(line, words[3], "weight")?,
+        ));
+        Ok(())
+    })?;
+    check_arc_count(line, m, edges.len())?;
+    Ok(EdgeList {
+        directed: true,
+        vertices: (1..=n).collect(),
+        edges,
+    })
+}
+
+/// Writes `graph` as a DIMACS shortest path problem, whose vertices are
+/// numbered from 1. A graph numbered from 1 that leaves vertex 0 isolated is
+/// written as it is; if vertex 0 has an edge (e.g. in a `CsrGraph`), every
+/// vertex v is written as v + 1.
+pub fn write_dimacs_shortest_path<G>(graph: &G) -> String
+where
+    G: WeightedGraph<Vertex = usize>,
+    G::Weight: Display,
+{
+    let edges = edges_to_write(graph, true);
+    let shift = usize::from(edges.iter().any(|&(u, v, _)| u == 0 || v == 0));
+    let n = graph.vertices().max().map_or(0, |n| n + shift);
+    let mut output = format!("p sp {n} {}\n", edges.len());
+    for (u, v, weight) in edges {
+        writeln!(output, "a {} {} {weight}", u + shift, v + shift).unwrap();
+    }
+    output
+}
+
+/// Parses a DIMACS maximum flow problem into a flow network.
+pub fn parse_dimacs_max_flow<T>(input: &str) -> Result<DinicMaxFlow<T>, ParseError>
+where
+    T: FromStr + Clone + Copy + Add + AddAssign + Sub<Output = T> + SubAssign + Neg + Ord + Default,
+{
+    let (mut source, mut sink) = (None, None);
+    let mut arcs = Vec::new();
+    let (line, n, m) = parse_dimacs(input, "max", |line, words, n| match words[0].1 {
+        "n" => {
+            expect_words(line, words, 3)?;
+            let v = dimacs_vertex(line, words[1], n)?;
+            let terminal = match words[2].1 {
+                "s" => &mut source,
+                "t" => &mut sink,
+                _ => return Err(ParseError::new(line, words[2].0, "expected `s` or `t`")),
+            };
+            if terminal.replace(v).is_some() {
+                return Err(ParseError::new(line, words[2].0, "duplicate terminal"));
+            }
+            Ok(())
+        }
+        "a" => {
+            expect_words(line, words, 4)?;
+            arcs.push((
+                dimacs_vertex(line, words[1], n)?,
+                dimacs_vertex(line, words[2], n)?,
+                parse_word(line, words[3], "capacity")?,
+            ));
+            Ok(())
+        }
+        _ => Err(unknown_line(line, words[0])),
+    })?;
+    check_arc_count(line, m, arcs.len())?;
+    let (Some(source), Some(sink)) = (source, sink) else {
+        return Err(ParseError::new(
+            line,
+            1,
+            "the source or the sink is missing",
+        ));
+    };
+    let mut flow = DinicMaxFlow::new(source, sink, n);
+    for (u, v, capacity) in arcs {
+        flow.add_edge(u, v, capacity);
+    }
+    Ok(flow)
+}
+
+/// Writes a flow network as a DIMACS maximum flow problem.
+pub fn write_dimacs_max_flow<T: Display>(flow: &DinicMaxFlow<T>) -> String {
+    let mut output = format!(
+        "p max {} {}\nn {} s\nn {} t\n",
+        flow.num_vertices,
+        flow.num_edges / 2,
+        flow.source,
+        flow.sink
+    );
+    for e in (0..flow.num_edges).step_by(2) {
+        let (u, edge) = (flow.edges[e + 1].sink, &flow.edges[e]);
+        writeln!(output, "a {u} {} {}", edge.sink, edge.capacity).unwrap();
+    }
+    output
+}
+
+/// Parses an adjacency list: `vertex: neighbour[:weight] ...` on each line.
+/// A neighbour without a weight gets `E::default()`, and a vertex without any
+/// neighbour is declared on its own.
+pub fn parse_adjacency_list<V: FromStr + Clone, E: FromStr + Default>(
+    input: &str,
+    directed: bool,
+) -> Result<EdgeList<V, E>, ParseError> {
+    let mut result = EdgeList {
+        directed,
+        vertices: Vec::new(),
+        edges: Vec::new(),
+    };
+    for (i, text) in input.lines().enumerate() {
+        let line = i + 1;
+        let head = words(text);
+        match head.first() {
+            None => continue,
+            Some((_, first)) if first.starts_with('#') => continue,
+            Some(_) => (),
+        }
+        let Some(colon) = text.find(':') else {
+            return Err(ParseError::new(line, end_column(text), "expected `:`"));
+        };
+        // the columns of the neighbours start after the colon
+        let offset = text[..=colon].chars().count();
+        let vertex: V = match words(&text[..colon]).as_slice() {
+            [vertex] => parse_word(line, *vertex, "vertex")?,
+            [_, (column, _), ..] => return Err(ParseError::new(line, *column, "expected `:`")),
+            [] => return Err(ParseError::new(line, offset, "expected a vertex")),
+        };
+        let neighbours = words(&text[colon + 1..]);
+        if neighbours.is_empty() {
+            result.vertices.push(vertex.clone());
+        }
+        for (column, word) in neighbours {
+            let column = column + offset;
+            let (neighbour, weight) = match word.split_once(':') {
+                Some((neighbour, weight)) => {
+                    let weight_column = column + neighbour.chars().count() + 1;
+                    (
+                        neighbour,
+                        parse_word(line, (weight_column, weight), "weight")?,
+                    )
+                }
+                None => (word, E::default()),
+            };
+            let neighbour = parse_word(line, (column, neighbour), "vertex")?;
+            result.edges.push((vertex.clone(), neighbour, weight));
+        }
+    }
+    Ok(result)
+}
+
+/// Writes `graph` as an adjacency list, with the weight of every edge. Like
+/// `write_edge_list`, each edge of an undirected graph is written once, on the
+/// line of its smallest endpoint, and a vertex only gets a line if it has
+/// something to write or no edge at all. The vertices must not contain
+/// whitespace or `:`.
+pub fn write_adjacency_list<G>(graph: &G, directed: bool) -> String
+where
+    G: WeightedGraph,
+    G::Vertex: Ord + Display,
+    G::Weight: Display,
+{
+    let edges = edges_to_write(graph, directed);
+    let endpoints: BTreeSet<&G::Vertex> = edges.iter().flat_map(|(u, v, _)| [u, v]).collect();
+    let mut output = String::new();
+    for v in sorted_vertices(graph) {
+        let mut neighbours = edges.iter().filter(|(u, _, _)| *u == v).peekable();
+        if neighbours.peek().is_none() && endpoints.contains(&v) {
+            continue;
+        }
+        write!(output, "{v}:").unwrap();
+        for (_, u, weight) in neighbours {
+            write!(output, " {u}:{weight}").unwrap();
+        }
+        output.push('\n');
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_structures::{DirectedGraph, UndirectedGraph};
+    use crate::graph::{dijkstra, prim, CsrGraph};
+
+    #[test]
+    fn edge_list() {
+        let input = "# a small graph\n1 2 4\n1 3 1\n\n3 2 2\n2 4 5\n5\n";
+        let parsed: EdgeList<u32, u64> = parse_edge_list(input, true).unwrap();
+        assert_eq!(parsed.vertices, vec![5]);
+        assert_eq!(parsed.edges.len(), 4);
+
+        let graph = parsed.into_map_graph();
+        assert_eq!(graph.len(), 5);
+        assert_eq!(dijkstra(&graph, 1)[&2], Some((3, 3)));
+        assert_eq!(
+            write_edge_list(&graph, true),
+            "5\n1 2 4\n1 3 1\n2 4 5\n3 2 2\n"
+        );
+
+        let undirected: UndirectedGraph<String, i32> = parse_edge_list("a b 1\nb c -2\n", false)
+            .unwrap()
+            .into_graph();
+        assert_eq!(write_edge_list(&undirected, false), "a b 1\nb c -2\n");
+    }
+
+    #[test]
+    fn edge_list_errors() {
+        let error = parse_edge_list::<u32, u32>("1 2 3\n1 2\n", true).unwrap_err();
+        assert_eq!((error.line, error.column), (2, 4));
+        let error = parse_edge_list::<u32, u32>("1 2 3\n  1  2 x\n", true).unwrap_err();
+        assert_eq!(error, ParseError::new(2, 8, "invalid weight `x`"));
+        assert_eq!(error.to_string(), "line 2, column 8: invalid weight `x`");
+        let error = parse_edge_list::<u32, u32>("1 2 3 4", true).unwrap_err();
+        assert_eq!((error.line, error.column), (1, 7));
+    }
+
+    #[test]
+    fn adjacency_list() {
+        let input = "# a small graph\n1: 2:4 3:1\n\n3: 2:2\n2:4:5\n4:\n5:\n";
+        let parsed: EdgeList<u32, u64> = parse_adjacency_list(input, true).unwrap();
+        assert_eq!(parsed.vertices, vec![4, 5]);
+        assert_eq!(
+            parsed.edges,
+            vec![(1, 2, 4), (1, 3, 1), (3, 2, 2), (2, 4, 5)]
+        );
+        let graph = parsed.into_map_graph();
+        assert_eq!(dijkstra(&graph, 1)[&2], Some((3, 3)));
+        let written = write_adjacency_list(&graph, true);
+        assert_eq!(written, "1: 2:4 3:1\n2: 4:5\n3: 2:2\n5:\n");
+        let reparsed = parse_adjacency_list::<u32, u64>(&written, true).unwrap();
+        assert_eq!(reparsed.into_map_graph(), graph);
+
+        // without weights, and undirected
+        let unweighted: EdgeList<char, u32> =
+            parse_adjacency_list("a: b c\nc: d\n", false).unwrap();
+        assert_eq!(
+            unweighted.edges,
+            vec![('a', 'b', 0), ('a', 'c', 0), ('c', 'd', 0)]
+        );
+        let undirected: UndirectedGraph<char, u32> = unweighted.clone().into_graph();
+        let written = write_adjacency_list(&undirected, false);
+        assert_eq!(written, "a: b:0 c:0\nc: d:0\n");
+        let reparsed = parse_adjacency_list::<char, u32>(&written, false).unwrap();
+        assert_eq!(reparsed.into_map_graph(), unweighted.into_map_graph());
+    }
+
+    #[test]
+    fn adjacency_list_errors() {
+        let cases = [
+            ("1: 2\n3 4\n", (2, 4), "expected `:`"),
+            ("1 2: 3\n", (1, 3), "expected `:`"),
+            (": 3\n", (1, 1), "expected a vertex"),
+            ("1: 2:3\n  4:  5:x\n", (2, 9), "invalid weight `x`"),
+            ("1: 2 y:3\n", (1, 6), "invalid vertex `y`"),
+            ("z: 2\n", (1, 1), "invalid vertex `z`"),
+        ];
+        for (input, (line, column), message) in cases {
+            assert_eq!(
+                parse_adjacency_list::<u32, u32>(input, true),
+                Err(ParseError::new(line, column, message)),
+                "{input}"
+            );
+        }
+    }
+
+    #[test]
+    fn dot_round_trip() {
+        let mut graph: DirectedGraph<String, i32> = DirectedGraph::new();
+        graph.add_edge(("a".to_string(), "b".to_string(), 5));
+        graph.add_edge(("b".to_string(), "say \"c\"".to_string(), -7));
+        graph.add_node("d".to_string());
+
+        let dot = write_dot(&graph, true);
+        assert_eq!(
+            dot,
+            "digraph {\n    \"a\";\n    \"b\";\n    \"d\";\n    \"say \\\"c\\\"\";\n    \
+             \"a\" -> \"b\" [label=\"5\"];\n    \"b\" -> \"say \\\"c\\\"\" [label=\"-7\"];\n}\n"
+        );
+        let parsed: DirectedGraph<String, i32> = parse_dot(&dot).unwrap().into_graph();
+        assert_eq!(write_dot(&parsed, true), dot);
+    }
+
+    #[test]
+    fn dot_syntax() {
+        let input = r#"
+            strict graph G {
+                // settings
+                graph [rankdir=LR]; node [shape=circle]
+                rankdir = LR
+                /* a chain
+                   of edges */
+                1 -- 2 -- 3 [label=4, weight=2];
+                3 -- "4" [label="-1"] # done
+                5 [shape=box]
+            }
+        "#;
+        let parsed: EdgeList<u8, i8> = parse_dot(input).unwrap();
+        assert!(!parsed.directed);
+        assert_eq!(parsed.vertices, vec![5]);
+        assert_eq!(parsed.edges, vec![(1, 2, 2), (2, 3, 2), (3, 4, -1)]);
+
+        let graph = parsed.into_map_graph();
+        assert_eq!(graph[&3][&2], 2);
+        assert_eq!(graph[&4][&3], -1);
+    }
+
+    #[test]
+    fn dot_errors() {
+        let cases = [
+            (
+                "digraph {\n  a -> b;\n}",
+                (2, 5),
+                "the edge has no weight or label",
+            ),
+            (
+                "graph { a -> b [label=1] }",
+                (1, 11),
+                "`->` cannot be used in a graph",
+            ),
+            ("digraph {\n  a -> \"b\n}", (2, 8), "unterminated string"),
+            (
+                "digraph {\n  a -> b [label=x]\n}",
+                (2, 17),
+                "invalid weight `x`",
+            ),
+            ("digraph {\n  a -> b [label=1]\n", (3, 1), "expected `}`"),
+            ("tree { }", (1, 1), "expected `graph` or `digraph`"),
+            ("digraph { a ! b }", (1, 13), "unexpected character `!`"),
+            ("digraph { } }", (1, 13), "expected the end of the input"),
+        ];
+        for (input, (line, column), message) in cases {
+            assert_eq!(
+                parse_dot::<String, u32>(input),
+                Err(ParseError::new(line, column, message)),
+                "{input}"
+            );
+        }
+    }
+
+    #[test]
+    fn dot_highlights_mst() {
+        let input = "1 2 4\n1 3 1\n3 2 2\n2 4 5\n3 4 8\n";
+        let graph = parse_edge_list::<usize, u32>(input, false)
+            .unwrap()
+            .into_map_graph();
+        let mst = prim(&graph);
+        let dot = write_dot_highlighted(&graph, false, &mst);
+        assert_eq!(dot.lines().filter(|l| l.contains("--")).count(), 5);
+        assert_eq!(dot.matches("color=red").count(), 3);
+        assert!(dot.contains("\"1\" -- \"3\" [label=\"1\", color=red, penwidth=2];"));
+        assert!(dot.contains("\"3\" -- \"4\" [label=\"8\"];"));
+    }
+
+    const MAX_FLOW: &str = "c the network of DinicMaxFlow's tests\n\
+                            p max 6 9\n\
+                            n 1 s\n\
+                            n 6 t\n\
+                            a 1 2 16\n\
+                            a 1 4 13\n\
+                            a 2 3 12\n\
+                            a 3 4 9\n\
+                            a 3 6 20\n\
+                            a 4 2 4\n\
+                            a 4 5 14\n\
+                            a 5 3 7\n\
+                            a 5 6 4\n";
+
+    #[test]
+    fn dimacs_max_flow() {
+        let mut flow: DinicMaxFlow<i32> = parse_dimacs_max_flow(MAX_FLOW).unwrap();
+        assert_eq!((flow.source, flow.sink, flow.num_vertices), (1, 6, 6));
+        let without_comment = MAX_FLOW.split_once('\n').unwrap().1;
+        assert_eq!(write_dimacs_max_flow(&flow), without_comment);
+
+        assert_eq!(flow.find_maxflow(i32::MAX), 23);
+        let dot = write_flow_dot(&flow);
+        // The minimum cut is {1, 2, 4, 5} | {3, 6}
+        assert_eq!(dot.matches("fillcolor").count(), 4);
+        assert!(dot.contains("\"5\" [style=filled, fillcolor=lightblue];"));
+        assert!(dot.contains("\"2\" -> \"3\" [label=\"12/12\", color=red, penwidth=2];"));
+        assert!(dot.contains("\"5\" -> \"6\" [label=\"4/4\", color=red, penwidth=2];"));
+        assert_eq!(dot.matches("color=red").count(), 3);
+    }
+
+    #[test]
+    fn dimacs_shortest_path() {
+        let input = "c a comment\np sp 4 4\na 1 2 4\na 1 3 1\na 3 2 2\na 2 4 -5\n";
+        let graph = parse_dimacs_shortest_path::<i64>(input)
+            .unwrap()
+            .into_map_graph();
+        assert_eq!(graph.len(), 4);
+        assert_eq!(
+            write_dimacs_shortest_path(&graph),
+            "p sp 4 4\na 1 2 4\na 1 3 1\na 2 4 -5\na 3 2 2\n"
+        );
+
+        // numbered from 0
+        let csr = CsrGraph::from_edges(3, &[(0, 1, 7), (2, 0, 3)]);
+        let written = write_dimacs_shortest_path(&csr);
+        assert_eq!(written, "p sp 3 2\na 1 2 7\na 3 1 3\n");
+        let parsed = parse_dimacs_shortest_path::<u32>(&written).unwrap();
+        assert_eq!(parsed.edges, vec![(1, 2, 7), (3, 1, 3)]);
+    }
+
+    #[test]
+    fn dimacs_errors() {
+        let cases = [
+            ("a 1 2 3\n", (1, 1), "expected the problem line first"),
+            ("c only\n", (1, 1), "missing problem line"),
+            (
+                "p sp 3 1\na 1 4 2\n",
+                (2, 5),
+                "vertex 4 is not between 1 and 3",
+            ),
+            (
+                "p sp 3 2\na 1 2 2\n",
+                (1, 8),
+                "2 arcs were announced but 1 were given",
+            ),
+            ("p sp 3 1\na 1 2\n", (2, 6), "expected 4 fields"),
+            ("p sp 3 1\nx 1 2\n", (2, 1), "unknown line type `x`"),
+            ("p max 3 1\na 1 2 2\n", (1, 3), "expected a `sp` problem"),
+        ];
+        for (input, (line, column), message) in cases {
+            assert_eq!(
+                parse_dimacs_shortest_path::<i32>(input),
+                Err(ParseError::new(line, column, message)),
+                "{input}"
+            );
+        }
+        let error = parse_dimacs_max_flow::<i32>("p max 2 1\nn 1 s\na 1 2 3\n").err();
+        assert_eq!(
+            error,
+            Some(ParseError::new(1, 1, "the source or the sink is missing"))
+        );
+        let error = parse_dimacs_max_flow::<i32>("p max 2 0\nn 1 s\nn 2 s\n").err();
+        assert_eq!(error, Some(ParseError::new(3, 5, "duplicate terminal")));
+    }
+}
@@ -0,0 +1,371 @@
+/*
+Path and subtree queries with Heavy Light Decomposition:
+The decomposition numbers the vertices so that every heavy path, and every
+subtree, is a contiguous range of positions. A segment tree over the positions
+then answers queries about any path, by splitting it into O(lg(n)) ranges, and
+about any subtree, which is a single range.
+
+Values can be stored on the vertices, or on the edges: the value of the edge
+between v and its parent is stored on v, and the lowest common ancestor of a
+path is then left out of it.
+
+The values are combined with any associative function, which doesn't have to
+be commutative: a path is combined in order, from its first vertex to its
+last. To do so, two `LazySegmentTree`s hold the values, one by increasing
+positions and one by decreasing positions, for the parts of a path that go up
+the tree. Updates assign a value to a whole path or subtree, in both trees.
+
+The tree vertices are numbered from 1 to n, as in `HeavyLightDecomposition`.
+
+Complexity: O(n) to build, O(lg(n)^2) for a path query, O(lg(n)^3) for a path
+update, O(lg(n)) for a subtree query and O(lg(n)^2) for a subtree update.
+*/
+
+use std::ops::Range;
+
+use super::HeavyLightDecomposition;
+use crate::data_structures::LazySegmentTree;
+
+/// Where the values of the tree are stored
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueLocation {
+    Vertices,
+    // The value of v is the value of the edge between v and its parent
+    Edges,
+}
+
+pub struct HeavyLightQueries<T> {
+    pub decomposition: HeavyLightDecomposition,
+    pub parent: Vec<usize>,
+    pub depth: Vec<usize>,
+    pub subtree_size: Vec<usize>,
+    location: ValueLocation,
+    merge: fn(T, T) -> T,
+    // The values by position, and by position from the end, over the `len`
+    // positions of the tree
+    len: usize,
+    forward: LazySegmentTree<T>,
+    backward: LazySegmentTree<T>,
+}
+
+impl<T: Copy> HeavyLightQueries<T> {
+    /// Builds the queries over the tree `adj` rooted at `root`, where
+    /// `values[v]` is the value of vertex v (`values[0]` is ignored), or of
+    /// the edge from v to its parent (then `values[root]` is ignored too).
+    pub fn new(
+        adj: &[Vec<usize>],
+        root: usize,
+        values: &[T],
+        location: ValueLocation,
+        merge: fn(T, T) -> T,
+    ) -> Self {
+        let n = adj.len() - 1;
+        let mut decomposition = HeavyLightDecomposition::new(n);
+        decomposition.decompose(root, adj);
+
+        let mut parent = vec![0; n + 1];
+        let mut depth = vec![0; n + 1];
+        let mut subtree_size = vec![1; n + 1];
+        let mut order = vec![root];
+        let mut i = 0;
+        while i < order.len() {
+            let v = order[i];
+            for &u in &adj[v] {
+                if u != parent[v] {
+                    parent[u] = v;
+                    depth[u] = depth[v] + 1;
+                    order.push(u);
+                }
+            }
+            i += 1;
+        }
+        for &v in order.iter().skip(1).rev() {
+            subtree_size[parent[v]] += subtree_size[v];
+        }
+
+        // Only the vertices of the tree have a position, from 1 to the size of the tree
+        let mut by_position = vec![values[root]; order.len()];
+        for &v in &order {
+            by_position[decomposition.position[v] - 1] = values[v];
+        }
+        let forward = LazySegmentTree::from_vec(&by_position, merge);
+        by_position.reverse();
+        HeavyLightQueries {
+            decomposition,
+            parent,
+            depth,
+            subtree_size,
+            location,
+            merge,
+            len: order.len(),
+            forward,
+            backward: LazySegmentTree::from_vec(&by_position, merge),
+        }
+    }
+
+    pub fn lowest_common_ancestor(&self, mut u: usize, mut v: usize) -> usize {
+        let head = &self.decomposition.head;
+        while head[u] != head[v] {
+            if self.depth[head[u]] >= self.depth[head[v]] {
+                u = self.parent[head[u]];
+            } else {
+                v = self.parent[head[v]];
+            }
+        }
+        if self.depth[u] <= self.depth[v] {
+            u
+        } else {
+            v
+        }
+    }
+
+    /// Combines the values on the path from u to v, in this order. Returns
+    /// `None` if the path has no value (an edge path from a vertex to itself).
+    pub fn query_path(&mut self, u: usize, v: usize) -> Option<T> {
+        let mut result = None;
+        for (range, upwards) in self.path_ranges(u, v) {
+            let aggregate = if upwards {
+                let range = self.reversed(range);
+                self.backward.query(range)?
+            } else {
+                self.forward.query(range)?
+            };
+            result = Some(match result {
+                Some(result) => (self.merge)(result, aggregate),
+                None => aggregate,
+            });
+        }
+        result
+    }
+
+    /// Assigns `value` to every vertex (or edge) of the path from u to v
+    pub fn assign_path(&mut self, u: usize, v: usize, value: T) {
+        for (range, _) in self.path_ranges(u, v) {
+            self.assign(range, value);
+        }
+    }
+
+    /// Combines the values of the subtree of v, in the order of their
+    /// positions. With values on the edges, the edge above v is left out.
+    pub fn query_subtree(&mut self, v: usize) -> Option<T> {
+        let range = self.subtree_range(v);
+        self.forward.query(range)
+    }
+
+    /// Assigns `value` to every vertex (or edge) of the subtree of v
+    pub fn assign_subtree(&mut self, v: usize, value: T) {
+        let range = self.subtree_range(v);
+        self.assign(range, value);
+    }
+
+    /// Changes the value of vertex v, or of the edge between v and its parent
+    pub fn set(&mut self, v: usize, value: T) {
+        let position = self.decomposition.position[v] - 1;
+        self.assign(position..position + 1, value);
+    }
+
+    fn assign(&mut self, range: Range<usize>, value: T) {
+        let reversed = self.reversed(range.clone());
+        self.forward.assign(range, value);
+        self.backward.assign(reversed, value);
+    }
+
+    // The same positions in `backward`
+    fn reversed(&self, range: Range<usize>) -> Range<usize> {
+        self.len - range.end..self.len - range.start
+    }
+
+    fn subtree_range(&self, v: usize) -> Range<usize> {
+        let start = self.decomposition.position[v] - 1;
+        let end = start + self.subtree_size[v];
+        match self.location {
+            ValueLocation::Vertices => start..end,
+            ValueLocation::Edges => start + 1..end,
+        }
+    }
+
+    // The ranges of positions covering the path from u to v, in the order of
+    // the path. `upwards` ranges are walked by decreasing positions.
+    fn path_ranges(&self, mut u: usize, mut v: usize) -> Vec<(Range<usize>, bool)> {
+        let head = &self.decomposition.head;
+        let position = &self.decomposition.position;
+        let mut from_u = vec![];
+        let mut to_v = vec![];
+        while head[u] != head[v] {
+            if self.depth[head[u]] >= self.depth[head[v]] {
+                from_u.push((position[head[u]] - 1..position[u], true));
+                u = self.parent[head[u]];
+            } else {
+                to_v.push((position[head[v]] - 1..position[v], false));
+                v = self.parent[head[v]];
+            }
+        }
+        // u and v are now on the same heavy path, the upper one is the LCA
+        let skip = usize::from(self.location == ValueLocation::Edges);
+        if self.depth[u] >= self.depth[v] {
+            from_u.push((position[v] - 1 + skip..position[u], true));
+        } else {
+            from_u.push((position[u] - 1 + skip..position[v], false));
+        }
+        from_u.extend(to_v.into_iter().rev());
+        from_u.retain(|(range, _)| !range.is_empty());
+        from_u
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    const MOD: u64 = 1_000_000_007;
+
+    // Composition of affine maps x -> a * x + b, applying the left one first.
+    // It is associative but not commutative, so the order of a path matters.
+    fn compose(f: (u64, u64), g: (u64, u64)) -> (u64, u64) {
+        (f.0 * g.0 % MOD, (f.1 * g.0 + g.1) % MOD)
+    }
+
+    // The vertices of the path from u to v, by walking up to the LCA
+    fn brute_path(parent: &[usize], depth: &[usize], mut u: usize, mut v: usize) -> Vec<usize> {
+        let mut from_u = vec![];
+        let mut to_v = vec![];
+        while u != v {
+            if depth[u] >= depth[v] {
+                from_u.push(u);
+                u = parent[u];
+            } else {
+                to_v.push(v);
+                v = parent[v];
+            }
+        }
+        from_u.push(u);
+        from_u.extend(to_v.into_iter().rev());
+        from_u
+    }
+
+    fn is_ancestor(parent: &[usize], u: usize, mut w: usize) -> bool {
+        while w != 0 && w != u {
+            w = parent[w];
+        }
+        w == u
+    }
+
+    #[test]
+    fn vertex_sums() {
+        //        1
+        //      / | \
+        //     2  3  4
+        //    / \     \
+        //   5   6     7
+        let adj = vec![
+            vec![],
+            vec![2, 3, 4],
+            vec![5, 6],
+            vec![],
+            vec![7],
+            vec![],
+            vec![],
+            vec![],
+        ];
+        let values = vec![0, 1, 2, 3, 4, 5, 6, 7];
+        let mut hld =
+            HeavyLightQueries::new(&adj, 1, &values, ValueLocation::Vertices, |a, b| a + b);
+        assert_eq!(hld.lowest_common_ancestor(5, 7), 1);
+        assert_eq!(hld.lowest_common_ancestor(5, 6), 2);
+        assert_eq!(hld.query_path(5, 7), Some(5 + 2 + 1 + 4 + 7));
+        assert_eq!(hld.query_path(3, 3), Some(3));
+        assert_eq!(hld.query_subtree(2), Some(2 + 5 + 6));
+        assert_eq!(hld.query_subtree(1), Some(28));
+
+        hld.assign_path(6, 4, 10);
+        assert_eq!(hld.query_subtree(1), Some(10 * 4 + 3 + 5 + 7));
+        hld.assign_subtree(2, 0);
+        assert_eq!(hld.query_path(5, 7), Some(10 + 10 + 7));
+        hld.set(7, 1);
+        assert_eq!(hld.query_subtree(4), Some(11));
+    }
+
+    #[test]
+    fn edge_maximums() {
+        // 1 -(4)- 2 -(9)- 3 -(2)- 4, and 2 -(7)- 5
+        let adj = vec![vec![], vec![2], vec![1, 3, 5], vec![2, 4], vec![3], vec![2]];
+        let values = vec![0, 0, 4, 9, 2, 7];
+        let mut hld = HeavyLightQueries::new(&adj, 1, &values, ValueLocation::Edges, std::cmp::max);
+        assert_eq!(hld.query_path(4, 5), Some(9));
+        assert_eq!(hld.query_path(1, 5), Some(7));
+        assert_eq!(hld.query_path(3, 4), Some(2));
+        assert_eq!(hld.query_path(3, 3), None);
+        assert_eq!(hld.query_subtree(3), Some(2));
+        assert_eq!(hld.query_subtree(4), None);
+        hld.assign_path(1, 3, 1);
+        assert_eq!(hld.query_path(4, 5), Some(7));
+        assert_eq!(hld.query_subtree(1), Some(7));
+    }
+
+    #[test]
+    fn random_trees_with_non_commutative_merge() {
+        let mut rng = StdRng::seed_from_u64(21);
+        for location in [ValueLocation::Vertices, ValueLocation::Edges] {
+            for _ in 0..30 {
+                let n = rng.gen_range(1..=40);
+                let mut adj = vec![vec![]; n + 1];
+                let mut parent = vec![0; n + 1];
+                let mut depth = vec![0; n + 1];
+                for v in 2..=n {
+                    let p = rng.gen_range(1..v);
+                    adj[p].push(v);
+                    adj[v].push(p);
+                    parent[v] = p;
+                    depth[v] = depth[p] + 1;
+                }
+                let mut values: Vec<(u64, u64)> = (0..=n)
+                    .map(|_| (rng.gen_range(1..10), rng.gen_range(0..10)))
+                    .collect();
+                let mut hld = HeavyLightQueries::new(&adj, 1, &values, location, compose);
+                for _ in 0..50 {
+                    let (u, v) = (rng.gen_range(1..=n), rng.gen_range(1..=n));
+                    let mut path = brute_path(&parent, &depth, u, v);
+                    let mut subtree: Vec<usize> =
+                        (1..=n).filter(|&w| is_ancestor(&parent, u, w)).collect();
+                    if location == ValueLocation::Edges {
+                        // the LCA is the vertex of the path closest to the root
+                        let lca = *path.iter().min_by_key(|&&w| depth[w]).unwrap();
+                        path.retain(|&w| w != lca);
+                        subtree.retain(|&w| w != u);
+                    }
+                    // a subtree is combined in the order of the positions
+                    subtree.sort_by_key(|&w| hld.decomposition.position[w]);
+                    let value = (rng.gen_range(1..10), rng.gen_range(0..10));
+                    match rng.gen_range(0..5) {
+                        0 => {
+                            let expected = path.iter().map(|&w| values[w]).reduce(compose);
+                            assert_eq!(hld.query_path(u, v), expected);
+                        }
+                        1 => {
+                            hld.assign_path(u, v, value);
+                            for &w in &path {
+                                values[w] = value;
+                            }
+                        }
+                        2 => {
+                            let expected = subtree.iter().map(|&w| values[w]).reduce(compose);
+                            assert_eq!(hld.query_subtree(u), expected);
+                        }
+                        3 => {
+                            hld.assign_subtree(u, value);
+                            for &w in &subtree {
+                                values[w] = value;
+                            }
+                        }
+                        _ => {
+                            hld.set(u, value);
+                            values[u] = value;
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,262 @@
+/*
+Hungarian algorithm (Kuhn-Munkres) for the assignment problem:
+Given an n x m matrix where cost[i][j] is the cost of assigning row i (a
+worker) to column j (a job), assign every row to a distinct column so that the
+total cost is minimal (or maximal). When there are more rows than columns, only
+m rows are assigned and the others are left out.
+
+The rows are added one by one. Every row keeps a potential u[i] and every
+column a potential v[j], with u[i] + v[j] <= cost[i][j] for all i, j. Adding a
+row is a search for a shortest augmenting path (with respect to the reduced
+costs cost[i][j] - u[i] - v[j]), during which the potentials are adjusted so
+that the edges of the path become tight.
+
+Complexity: O(n^2 * m) for n <= m, so O(n^3) for a square matrix.
+*/
+
+use std::ops::{Add, AddAssign, Neg, Sub, SubAssign};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Objective {
+    Minimize,
+    Maximize,
+}
+
+/// Solves the assignment problem for a rectangular `cost` matrix. Returns the
+/// total cost of the assignment, and for each row the column it is assigned
+/// to, or `None` if the row is left out because there are fewer columns than rows.
+pub fn hungarian<T>(cost: &[Vec<T>], objective: Objective) -> (T, Vec<Option<usize>>)
+where
+    T: Copy
+        + Ord
+        + Default
+        + Add<Output = T>
+        + AddAssign
+        + Sub<Output = T>
+        + SubAssign
+        + Neg<Output = T>,
+{
+    let n = cost.len();
+    let m = cost.first().map_or(0, Vec::len);
+    assert!(
+        cost.iter().all(|row| row.len() == m),
+        "all the rows must have the same length"
+    );
+    // Maximizing a cost is minimizing its opposite, and the algorithm needs at
+    // least as many columns as rows
+    let sign = |c: T| match objective {
+        Objective::Minimize => c,
+        Objective::Maximize => -c,
+    };
+    let transposed = n > m;
+    let matrix: Vec<Vec<T>> = if transposed {
+        (0..m)
+            .map(|j| (0..n).map(|i| sign(cost[i][j])).collect())
+            .collect()
+    } else {
+        cost.iter()
+            .map(|row| row.iter().map(|&c| sign(c)).collect())
+            .collect()
+    };
+
+    let row_of_column = min_cost_assignment(&matrix);
+    let mut assignment = vec![None; n];
+    for (j, row) in row_of_column.into_iter().enumerate() {
+        if let Some(i) = row {
+            if transposed {
+                assignment[j] = Some(i);
+            } else {
+                assignment[i] = Some(j);
+            }
+        }
+    }
+    let total = assignment
+        .iter()
+        .enumerate()
+        .filter_map(|(i, j)| j.map(|j| cost[i][j]))
+        .fold(T::default(), |total, c| total + c);
+    (total, assignment)
+}
+
+// The minimum cost assignment of an n x m matrix with n <= m. Returns for
+// each column the row assigned to it, if any.
+fn min_cost_assignment<T>(a: &[Vec<T>]) -> Vec<Option<usize>>
+where
+    T: Copy + Ord + Default + Add<Output = T> + AddAssign + Sub<Output = T> + SubAssign,
+{
+    let n = a.len();
+    let m = a.first().map_or(0, Vec::len);
+    // Rows and columns are numbered from 1, column 0 is a dummy column
+    // that holds the row being added
+    let mut u = vec![T::default(); n + 1];
+    let mut v = vec![T::default(); m + 1];
+    // row[j] is the row assigned to column j, 0 if there is none
+    let mut row = vec![0; m + 1];
+    // way[j] is the previous column on the augmenting path to column j
+    let mut way = vec![0; m + 1];
+
+    for i in 1..=n {
+        row[0] = i;
+        let mut j0 = 0;
+        // The smallest reduced cost from a row of the path to each column,
+        // `None` standing for infinity
+        let mut min_reduced: Vec<Option<T>> = vec![None; m + 1];
+        let mut used = vec![false; m + 1];
+        loop {
+            used[j0] = true;
+            let i0 = row[j0];
+            let mut delta = None;
+            let mut j1 = 0;
+            for j in 1..=m {
+                if used[j] {
+                    continue;
+                }
+                let reduced = a[i0 - 1][j - 1] - u[i0] - v[j];
+                if min_reduced[j].is_none_or(|min| reduced < min) {
+                    min_reduced[j] = Some(reduced);
+                    way[j] = j0;
+                }
+                if delta.is_none_or(|delta| min_reduced[j] < Some(delta)) {
+                    delta = min_reduced[j];
+                    j1 = j;
+                }
+            }
+            // There is always a free column left since n <= m
+            let delta = delta.unwrap();
+            for j in 0..=m {
+                if used[j] {
+                    u[row[j]] += delta;
+                    v[j] -= delta;
+                } else if let Some(min) = min_reduced[j].as_mut() {
+                    *min -= delta;
+                }
+            }
+            j0 = j1;
+            if row[j0] == 0 {
+                break;
+            }
+        }
+        // Flip the augmenting path
+        while j0 != 0 {
+            let j1 = way[j0];
+            row[j0] = row[j1];
+            j0 = j1;
+        }
+    }
+    row.into_iter()
+        .skip(1)
+        .map(|i| if i == 0 { None } else { Some(i - 1) })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    // The best total over every way to assign min(n, m) rows to distinct columns
+    fn brute_force(cost: &[Vec<i64>], objective: Objective) -> i64 {
+        fn go(
+            cost: &[Vec<i64>],
+            i: usize,
+            left: usize,
+            used: &mut [bool],
+            maximize: bool,
+        ) -> Option<i64> {
+            if left == 0 {
+                return Some(0);
+            }
+            if i == cost.len() {
+                return None;
+            }
+            let better = |a: Option<i64>, b: Option<i64>| match (a, b) {
+                (Some(a), Some(b)) => Some(if maximize { a.max(b) } else { a.min(b) }),
+                (a, b) => a.or(b),
+            };
+            // Row i is left out, or assigned to a free column
+            let mut best = go(cost, i + 1, left, used, maximize);
+            for j in 0..used.len() {
+                if !used[j] {
+                    used[j] = true;
+                    let total = go(cost, i + 1, left - 1, used, maximize).map(|t| t + cost[i][j]);
+                    used[j] = false;
+                    best = better(best, total);
+                }
+            }
+            best
+        }
+        let m = cost.first().map_or(0, Vec::len);
+        let maximize = objective == Objective::Maximize;
+        go(cost, 0, cost.len().min(m), &mut vec![false; m], maximize).unwrap()
+    }
+
+    fn check(cost: &[Vec<i64>], objective: Objective) {
+        let (total, assignment) = hungarian(cost, objective);
+        let m = cost.first().map_or(0, Vec::len);
+        assert_eq!(total, brute_force(cost, objective));
+        assert_eq!(assignment.len(), cost.len());
+        let mut columns: Vec<usize> = assignment.iter().flatten().copied().collect();
+        assert_eq!(columns.len(), cost.len().min(m));
+        columns.sort();
+        columns.dedup();
+        assert_eq!(columns.len(), cost.len().min(m));
+        let sum: i64 = assignment
+            .iter()
+            .enumerate()
+            .filter_map(|(i, j)| j.map(|j| cost[i][j]))
+            .sum();
+        assert_eq!(sum, total);
+    }
+
+    #[test]
+    fn square_matrix() {
+        let cost = vec![vec![4, 1, 3], vec![2, 0, 5], vec![3, 2, 2]];
+        assert_eq!(
+            hungarian(&cost, Objective::Minimize),
+            (5, vec![Some(1), Some(0), Some(2)])
+        );
+        assert_eq!(
+            hungarian(&cost, Objective::Maximize),
+            (11, vec![Some(0), Some(2), Some(1)])
+        );
+    }
+
+    #[test]
+    fn rectangular_matrices() {
+        let wide = vec![vec![10, 2, 8, 7], vec![3, 9, 1, 6]];
+        assert_eq!(
+            hungarian(&wide, Objective::Minimize),
+            (3, vec![Some(1), Some(2)])
+        );
+        let tall = vec![vec![10, 3], vec![2, 9], vec![8, 1], vec![7, 6]];
+        assert_eq!(
+            hungarian(&tall, Objective::Minimize),
+            (3, vec![None, Some(0), Some(1), None])
+        );
+        assert_eq!(
+            hungarian(&tall, Objective::Maximize),
+            (19, vec![Some(0), Some(1), None, None])
+        );
+        let empty: Vec<Vec<i32>> = vec![];
+        assert_eq!(hungarian(&empty, Objective::Minimize), (0, vec![]));
+        assert_eq!(
+            hungarian::<i32>(&[vec![], vec![]], Objective::Maximize),
+            (0, vec![None, None])
+        );
+    }
+
+    #[test]
+    fn random_matrices_match_brute_force() {
+        let mut rng = StdRng::seed_from_u64(42);
+        for _ in 0..200 {
+            let n = rng.gen_range(1..=6);
+            let m = rng.gen_range(1..=6);
+            let cost: Vec<Vec<i64>> = (0..n)
+                .map(|_| (0..m).map(|_| rng.gen_range(-50..=50)).collect())
+                .collect();
+            check(&cost, Objective::Minimize);
+            check(&cost, Objective::Maximize);
+        }
+    }
+}
@@ -0,0 +1,273 @@
+/*
+Johnson's algorithm:
+All-pairs shortest paths in a sparse directed graph whose edges may have
+negative weights.
+
+A virtual vertex with an edge of weight 0 to every vertex is added, and
+Bellman-Ford computes from it a potential h(v) (the shortest distance to v, at
+most 0). Reweighting every edge u -> v to w + h(u) - h(v) makes all the
+weights non-negative without changing which paths are the shortest, so
+Dijkstra can then be run from every vertex.
+
+Complexity: O(V * E * log V), better than the O(V^3) of Floyd-Warshall when the
+graph is sparse.
+*/
+
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BTreeMap, BinaryHeap};
+use std::ops::Add;
+
+use num_traits::Zero;
+
+use super::bellman_ford::{negative_cycle, NegativeCycle};
+use super::WeightedGraph;
+
+/// Performs Johnson's algorithm on the input graph, which may have negative
+/// edges.
+///
+/// Returns the same map as `floyd_warshall`: `map[u][v]` is the distance from
+/// `u` to `v` if `v` can be reached from `u`, and `map[u][u]` is zero. If the
+/// graph has a negative cycle, returns the vertices of one of them instead.
+pub fn johnson<V: Ord + Copy, E: Ord + Copy + Add<Output = E> + Zero>(
+    graph: &impl WeightedGraph<Vertex = V, Weight = E>,
+) -> Result<BTreeMap<V, BTreeMap<V, E>>, NegativeCycle<V>> {
+    let vertices: Vec<V> = graph.vertices().collect();
+    let index: BTreeMap<V, usize> = vertices.iter().enumerate().map(|(i, &v)| (v, i)).collect();
+    let adj: Vec<Vec<(usize, E)>> = vertices
+        .iter()
+        .map(|&u| graph.neighbors(u).map(|(v, w)| (index[&v], w)).collect())
+        .collect();
+
+    let potential = match potentials(&adj) {
+        Ok(potential) => potential,
+        Err(cycle) => {
+            return Err(NegativeCycle {
+                cycle: cycle.into_iter().map(|v| vertices[v]).collect(),
+                unbounded: vec![],
+            })
+        }
+    };
+
+    Ok(vertices
+        .iter()
+        .enumerate()
+        .map(|(source, &u)| {
+            let distances = reweighted_dijkstra(&adj, &potential, source)
+                .into_iter()
+                .enumerate()
+                .filter_map(|(v, dist)| dist.map(|dist| (vertices[v], dist)))
+                .collect();
+            (u, distances)
+        })
+        .collect())
+}
+
+// Bellman-Ford from a virtual vertex linked to every vertex with weight 0.
+// Returns the distances from it, or the vertices of a negative cycle.
+fn potentials<E: Ord + Copy + Add<Output = E> + Zero>(
+    adj: &[Vec<(usize, E)>],
+) -> Result<Vec<E>, Vec<usize>> {
+    let n = adj.len();
+    let mut dist = vec![E::zero(); n];
+    let mut parent = vec![None; n];
+    // The edges from the virtual vertex are the first round of relaxations;
+    // with n + 1 vertices, a change in the n-th round means a negative cycle
+    for round in 1..=n {
+        let mut last_changed = None;
+        for u in 0..n {
+            for &(v, w) in &adj[u] {
+                if dist[u] + w < dist[v] {
+                    dist[v] = dist[u] + w;
+                    parent[v] = Some(u);
+                    last_changed = Some(v);
+                }
+            }
+        }
+        match last_changed {
+            None => break,
+            Some(v) if round == n => return Err(negative_cycle(&parent, v)),
+            Some(_) => {}
+        }
+    }
+    Ok(dist)
+}
+
+// A vertex in the queue of Dijkstra, whose key is its distance for the reweighted
+// edges, up to a constant: dist - potential. It is compared as
+// a.dist + b.potential against b.dist + a.potential, which only needs `Add`.
+struct Candidate<E> {
+    dist: E,
+    potential: E,
+    vertex: usize,
+}
+
+impl<E: Ord + Copy + Add<Output = E>> Ord for Candidate<E> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.dist + other.potential)
+            .cmp(&(other.dist + self.potential))
+            .then(self.vertex.cmp(&other.vertex))
+    }
+}
+
+impl<E: Ord + Copy + Add<Output = E>> PartialOrd for Candidate<E> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<E: Ord + Copy + Add<Output = E>> PartialEq for Candidate<E> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<E: Ord + Copy + Add<Output = E>> Eq for Candidate<E> {}
+
+// Dijkstra from source ordered by the reweighted distances, returning the
+// original distances
+fn reweighted_dijkstra<E: Ord + Copy + Add<Output = E> + Zero>(
+    adj: &[Vec<(usize, E)>],
+    potential: &[E],
+    source: usize,
+) -> Vec<Option<E>> {
+    let mut dist = vec![None; adj.len()];
+    let mut done = vec![false; adj.len()];
+    dist[source] = Some(E::zero());
+    let mut queue = BinaryHeap::from([Reverse(Candidate {
+        dist: E::zero(),
+        potential: potential[source],
+        vertex: source,
+    })]);
+    while let Some(Reverse(Candidate {
+        dist: d, vertex: u, ..
+    })) = queue.pop()
+    {
+        if done[u] {
+            continue;
+        }
+        done[u] = true;
+        for &(v, w) in &adj[u] {
+            if dist[v].is_none_or(|old| d + w < old) {
+                dist[v] = Some(d + w);
+                queue.push(Reverse(Candidate {
+                    dist: d + w,
+                    potential: potential[v],
+                    vertex: v,
+                }));
+            }
+        }
+    }
+    dist
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::floyd_warshall;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    type Graph<V, E> = BTreeMap<V, BTreeMap<V, E>>;
+
+    fn add_edge<V: Ord + Copy, E: Ord>(graph: &mut Graph<V, E>, v1: V, v2: V, c: E) {
+        graph.entry(v1).or_default().insert(v2, c);
+        graph.entry(v2).or_default();
+    }
+
+    #[test]
+    fn negative_edges() {
+        let mut graph = BTreeMap::new();
+        add_edge(&mut graph, 0, 1, 6);
+        add_edge(&mut graph, 0, 3, 7);
+        add_edge(&mut graph, 1, 2, 5);
+        add_edge(&mut graph, 1, 3, 8);
+        add_edge(&mut graph, 1, 4, -4);
+        add_edge(&mut graph, 2, 1, -2);
+        add_edge(&mut graph, 3, 2, -3);
+        add_edge(&mut graph, 3, 4, 9);
+        add_edge(&mut graph, 4, 0, 3);
+        add_edge(&mut graph, 4, 2, 7);
+
+        let dists = johnson(&graph).unwrap();
+        assert_eq!(dists, floyd_warshall(&graph));
+        assert_eq!(dists[&3][&4], -9);
+        assert_eq!(dists[&0][&1], 2);
+    }
+
+    #[test]
+    fn unreachable_vertices() {
+        let mut graph: Graph<char, i32> = BTreeMap::new();
+        add_edge(&mut graph, 'a', 'b', -1);
+        graph.insert('c', BTreeMap::new());
+        let dists = johnson(&graph).unwrap();
+        assert_eq!(dists[&'a'], BTreeMap::from([('a', 0), ('b', -1)]));
+        assert_eq!(dists[&'b'], BTreeMap::from([('b', 0)]));
+        assert_eq!(dists[&'c'], BTreeMap::from([('c', 0)]));
+        assert_eq!(johnson(&Graph::<char, i32>::new()), Ok(BTreeMap::new()));
+    }
+
+    #[test]
+    fn negative_cycle() {
+        let mut graph = BTreeMap::new();
+        add_edge(&mut graph, 0, 1, 6);
+        add_edge(&mut graph, 1, 2, 5);
+        add_edge(&mut graph, 1, 4, -4);
+        add_edge(&mut graph, 2, 1, -6);
+        add_edge(&mut graph, 4, 0, 3);
+        let err = johnson(&graph).unwrap_err();
+        let mut cycle = err.cycle.clone();
+        let first = cycle.iter().position(|&v| v == 1).unwrap();
+        cycle.rotate_left(first);
+        assert_eq!(cycle, vec![1, 2]);
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "the graph has a cycle of negative weight through {:?}",
+                err.cycle
+            )
+        );
+
+        let mut self_loop = BTreeMap::new();
+        add_edge(&mut self_loop, 'a', 'a', -1);
+        assert_eq!(
+            johnson(&self_loop),
+            Err(NegativeCycle {
+                cycle: vec!['a'],
+                unbounded: vec![]
+            })
+        );
+    }
+
+    #[test]
+    fn random_graphs_match_floyd_warshall() {
+        let mut rng = StdRng::seed_from_u64(13);
+        for _ in 0..100 {
+            let n = rng.gen_range(1..=15);
+            // weights w + p(u) - p(v) with w >= 0 have no negative cycle
+            let p: Vec<i64> = (0..n).map(|_| rng.gen_range(-20..=20)).collect();
+            let mut graph: Graph<usize, i64> = (0..n).map(|v| (v, BTreeMap::new())).collect();
+            for _ in 0..rng.gen_range(0..=n * 3) {
+                let (u, v) = (rng.gen_range(0..n), rng.gen_range(0..n));
+                if u != v {
+                    add_edge(&mut graph, u, v, rng.gen_range(0..=10) + p[u] - p[v]);
+                }
+            }
+            assert_eq!(johnson(&graph).unwrap(), floyd_warshall(&graph));
+
+            // a negative cycle, if there is one, is made of edges of the graph
+            let u = rng.gen_range(0..n);
+            let v = rng.gen_range(0..n);
+            add_edge(&mut graph, u, v, -100);
+            match johnson(&graph) {
+                Ok(dists) => assert_eq!(dists, floyd_warshall(&graph)),
+                Err(NegativeCycle { cycle, unbounded }) => {
+                    assert!(unbounded.is_empty());
+                    let weight: i64 = (0..cycle.len())
+                        .map(|i| graph[&cycle[i]][&cycle[(i + 1) % cycle.len()]])
+                        .sum();
+                    assert!(weight < 0);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,276 @@
+/*
+Yen's algorithm for the k shortest loopless paths:
+Finds the shortest paths from a start to a target that don't go through the
+same vertex twice, in non-decreasing order of weight.
+
+The first path is the shortest one. Every following path leaves the previous
+path at some "spur" vertex: for each vertex of the last path found, the part
+before it is kept as the root, the edges used from the same root by the paths
+already found and the vertices of the root are removed, and Dijkstra finds the
+best way from the spur vertex to the target. These paths are candidates, and
+the best candidate is the next path.
+
+Complexity: O(k * V * E * log V) for k paths. Weights must not be negative.
+*/
+
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet, BinaryHeap};
+use std::ops::Add;
+
+use num_traits::Zero;
+
+use super::WeightedGraph;
+
+/// Iterates over the loopless paths from `start` to `target`, shortest first,
+/// as `(weight, vertices)` pairs. Paths are only computed as they are asked for.
+pub struct KShortestPaths<'a, G: WeightedGraph> {
+    graph: &'a G,
+    start: G::Vertex,
+    target: G::Vertex,
+    found: Vec<Vec<G::Vertex>>,
+    candidates: BTreeSet<(G::Weight, Vec<G::Vertex>)>,
+}
+
+/// Returns an iterator over the loopless paths from `start` to `target`, in
+/// non-decreasing order of weight.
+pub fn k_shortest_paths<G, V, E>(graph: &G, start: V, target: V) -> KShortestPaths<'_, G>
+where
+    G: WeightedGraph<Vertex = V, Weight = E>,
+    V: Ord + Copy,
+    E: Ord + Copy + Add<Output = E> + Zero,
+{
+    KShortestPaths {
+        graph,
+        start,
+        target,
+        found: vec![],
+        candidates: BTreeSet::new(),
+    }
+}
+
+/// Returns the (at most) `k` shortest loopless paths from `start` to `target`
+/// with their weights, in non-decreasing order of weight.
+pub fn yen_k_shortest_paths<V, E>(
+    graph: &impl WeightedGraph<Vertex = V, Weight = E>,
+    start: V,
+    target: V,
+    k: usize,
+) -> Vec<(E, Vec<V>)>
+where
+    V: Ord + Copy,
+    E: Ord + Copy + Add<Output = E> + Zero,
+{
+    k_shortest_paths(graph, start, target).take(k).collect()
+}
+
+impl<G, V, E> Iterator for KShortestPaths<'_, G>
+where
+    G: WeightedGraph<Vertex = V, Weight = E>,
+    V: Ord + Copy,
+    E: Ord + Copy + Add<Output = E> + Zero,
+{
+    type Item = (E, Vec<V>);
+
+    fn next(&mut self) -> Option<(E, Vec<V>)> {
+        match self.found.last() {
+            None => {
+                let first = restricted_dijkstra(
+                    self.graph,
+                    self.start,
+                    self.target,
+                    &BTreeSet::new(),
+                    &BTreeSet::new(),
+                );
+                self.candidates.extend(first);
+            }
+            Some(last) => {
+                let mut root_weight = E::zero();
+                for i in 0..last.len() - 1 {
+                    let root = &last[..=i];
+                    let spur = last[i];
+                    // the next edge of every path found with the same root
+                    let removed_edges: BTreeSet<(V, V)> = self
+                        .found
+                        .iter()
+                        .filter(|path| path.len() > i + 1 && path[..=i] == *root)
+                        .map(|path| (path[i], path[i + 1]))
+                        .collect();
+                    let removed_vertices: BTreeSet<V> = root[..i].iter().copied().collect();
+                    if let Some((weight, spur_path)) = restricted_dijkstra(
+                        self.graph,
+                        spur,
+                        self.target,
+                        &removed_vertices,
+                        &removed_edges,
+                    ) {
+                        let mut path = root[..i].to_vec();
+                        path.extend(spur_path);
+                        self.candidates.insert((root_weight + weight, path));
+                    }
+                    root_weight = root_weight + edge_weight(self.graph, last[i], last[i + 1]);
+                }
+            }
+        }
+        let (weight, path) = self.candidates.pop_first()?;
+        self.found.push(path.clone());
+        Some((weight, path))
+    }
+}
+
+// the lowest weight of an edge u -> v
+fn edge_weight<V: Ord + Copy, E: Ord + Copy>(
+    graph: &impl WeightedGraph<Vertex = V, Weight = E>,
+    u: V,
+    v: V,
+) -> E {
+    graph
+        .neighbors(u)
+        .filter(|&(next, _)| next == v)
+        .map(|(_, weight)| weight)
+        .min()
+        .unwrap()
+}
+
+// Dijkstra from start to target that doesn't use the removed vertices and edges
+fn restricted_dijkstra<V, E>(
+    graph: &impl WeightedGraph<Vertex = V, Weight = E>,
+    start: V,
+    target: V,
+    removed_vertices: &BTreeSet<V>,
+    removed_edges: &BTreeSet<(V, V)>,
+) -> Option<(E, Vec<V>)>
+where
+    V: Ord + Copy,
+    E: Ord + Copy + Add<Output = E> + Zero,
+{
+    let mut dist: BTreeMap<V, (E, Option<V>)> = BTreeMap::from([(start, (E::zero(), None))]);
+    let mut settled = BTreeSet::new();
+    let mut queue = BinaryHeap::from([Reverse((E::zero(), start))]);
+    while let Some(Reverse((d, u))) = queue.pop() {
+        if !settled.insert(u) {
+            continue;
+        }
+        if u == target {
+            let mut path = vec![target];
+            let mut current = target;
+            while let Some(prev) = dist[&current].1 {
+                path.push(prev);
+                current = prev;
+            }
+            path.reverse();
+            return Some((d, path));
+        }
+        for (v, weight) in graph.neighbors(u) {
+            if removed_vertices.contains(&v) || removed_edges.contains(&(u, v)) {
+                continue;
+            }
+            if dist.get(&v).is_none_or(|&(old, _)| d + weight < old) {
+                dist.insert(v, (d + weight, Some(u)));
+                queue.push(Reverse((d + weight, v)));
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    type Graph<V, E> = BTreeMap<V, BTreeMap<V, E>>;
+
+    fn add_edge<V: Ord + Copy, E: Ord>(graph: &mut Graph<V, E>, v1: V, v2: V, c: E) {
+        graph.entry(v1).or_default().insert(v2, c);
+        graph.entry(v2).or_default();
+    }
+
+    #[test]
+    fn classic_example() {
+        let mut graph = BTreeMap::new();
+        for (u, v, w) in [
+            ('C', 'D', 3),
+            ('C', 'E', 2),
+            ('D', 'F', 4),
+            ('E', 'D', 1),
+            ('E', 'F', 2),
+            ('E', 'G', 3),
+            ('F', 'G', 2),
+            ('F', 'H', 1),
+            ('G', 'H', 2),
+        ] {
+            add_edge(&mut graph, u, v, w);
+        }
+        assert_eq!(
+            yen_k_shortest_paths(&graph, 'C', 'H', 3),
+            vec![
+                (5, vec!['C', 'E', 'F', 'H']),
+                (7, vec!['C', 'E', 'G', 'H']),
+                (8, vec!['C', 'D', 'F', 'H']),
+            ]
+        );
+        // there are 7 loopless paths in total
+        let all: Vec<_> = k_shortest_paths(&graph, 'C', 'H').collect();
+        assert_eq!(all.len(), 7);
+        assert_eq!(all.last(), Some(&(11, vec!['C', 'E', 'D', 'F', 'G', 'H'])));
+    }
+
+    #[test]
+    fn no_path() {
+        let mut graph = BTreeMap::new();
+        add_edge(&mut graph, 1, 2, 1);
+        assert!(yen_k_shortest_paths(&graph, 2, 1, 5).is_empty());
+        assert_eq!(yen_k_shortest_paths(&graph, 1, 1, 5), vec![(0, vec![1])]);
+        assert_eq!(yen_k_shortest_paths(&graph, 1, 2, 0), vec![]);
+    }
+
+    // every loopless path from u to target, with its weight
+    fn all_paths(
+        graph: &Graph<usize, u32>,
+        path: &mut Vec<usize>,
+        weight: u32,
+        target: usize,
+        res: &mut Vec<(u32, Vec<usize>)>,
+    ) {
+        let u = *path.last().unwrap();
+        if u == target {
+            res.push((weight, path.clone()));
+            return;
+        }
+        for (&v, &w) in &graph[&u] {
+            if !path.contains(&v) {
+                path.push(v);
+                all_paths(graph, path, weight + w, target, res);
+                path.pop();
+            }
+        }
+    }
+
+    #[test]
+    fn random_graphs_match_brute_force() {
+        let mut rng = StdRng::seed_from_u64(15);
+        for _ in 0..100 {
+            let n = rng.gen_range(1..=7);
+            let mut graph: Graph<usize, u32> = (0..n).map(|v| (v, BTreeMap::new())).collect();
+            for _ in 0..rng.gen_range(0..=n * 3) {
+                let (u, v) = (rng.gen_range(0..n), rng.gen_range(0..n));
+                if u != v {
+                    add_edge(&mut graph, u, v, rng.gen_range(0..=10));
+                }
+            }
+            let (start, target) = (rng.gen_range(0..n), rng.gen_range(0..n));
+            let mut expected = vec![];
+            all_paths(&graph, &mut vec![start], 0, target, &mut expected);
+            expected.sort();
+
+            let paths: Vec<_> = k_shortest_paths(&graph, start, target).collect();
+            let weights: Vec<u32> = paths.iter().map(|(w, _)| *w).collect();
+            let expected_weights: Vec<u32> = expected.iter().map(|(w, _)| *w).collect();
+            assert_eq!(weights, expected_weights);
+            let mut sorted = paths.clone();
+            sorted.sort();
+            assert_eq!(sorted, expected);
+        }
+    }
+}
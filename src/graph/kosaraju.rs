@@ -1,3 +1,6 @@
+use super::weighted_graph::index_graph;
+use super::WeightedGraph;
+
 // Kosaraju algorithm, a linear-time algorithm to find the strongly connected components (SCCs) of a directed graph, in Rust.
 pub struct Graph {
     vertices: usize,
@@ -40,7 +43,39 @@ impl Graph {
     }
 }
 
-pub fn kosaraju(graph: &Graph) -> Vec<Vec<usize>> {
+impl WeightedGraph for Graph {
+    type Vertex = usize;
+    type Weight = ();
+
+    fn vertices(&self) -> impl Iterator<Item = usize> + '_ {
+        0..self.vertices
+    }
+
+    fn neighbors(&self, vertex: usize) -> impl Iterator<Item = (usize, ())> + '_ {
+        self.adj_list[vertex].iter().map(|&v| (v, ()))
+    }
+
+    fn num_vertices(&self) -> usize {
+        self.vertices
+    }
+}
+
+// Accepts any directed graph; each component lists its vertices in discovery order
+pub fn kosaraju<V: Ord + Copy>(graph: &impl WeightedGraph<Vertex = V>) -> Vec<Vec<V>> {
+    let (vertices, adj) = index_graph(graph);
+    let mut indexed = Graph::new(vertices.len());
+    for (u, edges) in adj.into_iter().enumerate() {
+        for v in edges {
+            indexed.add_edge(u, v);
+        }
+    }
+    kosaraju_indexed(&indexed)
+        .into_iter()
+        .map(|scc| scc.into_iter().map(|v| vertices[v]).collect())
+        .collect()
+}
+
+fn kosaraju_indexed(graph: &Graph) -> Vec<Vec<usize>> {
     let mut visited = vec![false; graph.vertices];
     let mut stack = Vec::new();
 
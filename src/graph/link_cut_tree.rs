@@ -0,0 +1,479 @@
+/*
+Link-cut tree:
+A forest of rooted trees where edges can be added and removed, and paths can be
+queried and updated, all in O(lg(n)) amortized time per operation (Sleator and
+Tarjan).
+
+Every tree is split into "preferred paths", each one stored in a splay tree
+ordered by depth; the root of a splay tree keeps a pointer to the parent of the
+top of its path. `access(v)` rebuilds the preferred paths so that the one of v
+goes from the root of the tree to v, and splays v to the root of its splay
+tree: the whole path from the root to v is then the splay tree of v.
+
+To work on a path between any two vertices, one of them is made the root of its
+tree by reversing the path from the root to it (a lazy flag in the splay tree).
+The operations below restore the previous root afterwards, so the forest stays
+rooted as the user built it.
+
+Values are on the vertices and are combined with any associative function, in
+the order of the path, so every splay node keeps the aggregate of its subtree
+in both directions. Updates assign a value to a whole path.
+
+Vertices are numbered from 0 to n - 1.
+*/
+
+use crate::math::associative_power;
+
+struct Node<T> {
+    children: [Option<usize>; 2],
+    // The parent in the splay tree, or the path-parent for the root of a splay tree
+    parent: Option<usize>,
+    reversed: bool,
+    assigned: Option<T>,
+    size: usize,
+    value: T,
+    forward: T,
+    backward: T,
+}
+
+pub struct LinkCutTree<T> {
+    nodes: Vec<Node<T>>,
+    merge: fn(T, T) -> T,
+}
+
+impl<T: Copy> LinkCutTree<T> {
+    /// Creates a forest of isolated vertices, `values[v]` being the value of v
+    pub fn new(values: &[T], merge: fn(T, T) -> T) -> Self {
+        LinkCutTree {
+            nodes: values
+                .iter()
+                .map(|&value| Node {
+                    children: [None, None],
+                    parent: None,
+                    reversed: false,
+                    assigned: None,
+                    size: 1,
+                    value,
+                    forward: value,
+                    backward: value,
+                })
+                .collect(),
+            merge,
+        }
+    }
+
+    /// Makes `child`, which must be the root of its tree, a child of `parent`.
+    /// Returns false, without changing anything, if `child` is not a root or is
+    /// already connected to `parent`.
+    pub fn link(&mut self, child: usize, parent: usize) -> bool {
+        if self.find_root(child) != child || self.connected(child, parent) {
+            return false;
+        }
+        // child is the root: after access, it is alone on its path
+        self.access(child);
+        self.nodes[child].parent = Some(parent);
+        true
+    }
+
+    /// Removes the edge between u and v. Returns false if there is no such edge.
+    /// The vertex farther from the root becomes the root of its new tree.
+    pub fn cut(&mut self, u: usize, v: usize) -> bool {
+        if self.parent(u) == Some(v) {
+            self.cut_from_parent(u);
+            true
+        } else if self.parent(v) == Some(u) {
+            self.cut_from_parent(v);
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn find_root(&mut self, v: usize) -> usize {
+        self.access(v);
+        let mut root = v;
+        loop {
+            self.push(root);
+            match self.nodes[root].children[0] {
+                Some(left) => root = left,
+                None => break,
+            }
+        }
+        self.splay(root);
+        root
+    }
+
+    pub fn connected(&mut self, u: usize, v: usize) -> bool {
+        self.find_root(u) == self.find_root(v)
+    }
+
+    /// The parent of v in its rooted tree
+    pub fn parent(&mut self, v: usize) -> Option<usize> {
+        self.access(v);
+        // the vertex just before v on the path from the root
+        let mut x = self.nodes[v].children[0]?;
+        loop {
+            self.push(x);
+            match self.nodes[x].children[1] {
+                Some(right) => x = right,
+                None => break,
+            }
+        }
+        self.splay(x);
+        Some(x)
+    }
+
+    /// Makes v the root of its tree
+    pub fn make_root(&mut self, v: usize) {
+        self.access(v);
+        self.reverse(v);
+    }
+
+    /// The lowest common ancestor of u and v, if they are in the same tree
+    pub fn lowest_common_ancestor(&mut self, u: usize, v: usize) -> Option<usize> {
+        if !self.connected(u, v) {
+            return None;
+        }
+        self.access(u);
+        Some(self.access(v))
+    }
+
+    /// Combines the values on the path from u to v, in this order, if they
+    /// are in the same tree
+    pub fn query_path(&mut self, u: usize, v: usize) -> Option<T> {
+        let root = self.expose_path(u, v)?;
+        let result = self.nodes[v].forward;
+        self.make_root(root);
+        Some(result)
+    }
+
+    /// Assigns `value` to every vertex of the path from u to v. Returns false
+    /// if they are not in the same tree.
+    pub fn assign_path(&mut self, u: usize, v: usize, value: T) -> bool {
+        let Some(root) = self.expose_path(u, v) else {
+            return false;
+        };
+        self.assign(v, value);
+        self.make_root(root);
+        true
+    }
+
+    /// Changes the value of v
+    pub fn set(&mut self, v: usize, value: T) {
+        self.access(v);
+        self.nodes[v].value = value;
+        self.pull(v);
+    }
+
+    // Makes the splay tree of v hold exactly the path from u to v, u being the
+    // root of the tree. Returns the previous root, to restore it afterwards.
+    fn expose_path(&mut self, u: usize, v: usize) -> Option<usize> {
+        let root = self.find_root(u);
+        if root != self.find_root(v) {
+            return None;
+        }
+        self.make_root(u);
+        self.access(v);
+        Some(root)
+    }
+
+    fn cut_from_parent(&mut self, v: usize) {
+        self.access(v);
+        if let Some(left) = self.nodes[v].children[0].take() {
+            self.nodes[left].parent = None;
+            self.pull(v);
+        }
+    }
+
+    // Makes the path from the root to v preferred, with v at the root of its
+    // splay tree. Returns the last vertex where the path joined the previous
+    // preferred paths, which is the LCA of v and the previously accessed vertex.
+    fn access(&mut self, v: usize) -> usize {
+        let mut last = None;
+        let mut joined = v;
+        let mut x = Some(v);
+        while let Some(y) = x {
+            self.splay(y);
+            self.nodes[y].children[1] = last;
+            self.pull(y);
+            joined = y;
+            last = Some(y);
+            x = self.nodes[y].parent;
+        }
+        self.splay(v);
+        joined
+    }
+
+    fn is_splay_root(&self, x: usize) -> bool {
+        match self.nodes[x].parent {
+            None => true,
+            Some(p) => !self.nodes[p].children.contains(&Some(x)),
+        }
+    }
+
+    fn side(&self, x: usize) -> usize {
+        let p = self.nodes[x].parent.unwrap();
+        usize::from(self.nodes[p].children[1] == Some(x))
+    }
+
+    fn rotate(&mut self, x: usize) {
+        let p = self.nodes[x].parent.unwrap();
+        let grandparent = self.nodes[p].parent;
+        let side = self.side(x);
+        let p_is_root = self.is_splay_root(p);
+        // x's inner child moves under p
+        let inner = self.nodes[x].children[1 - side];
+        self.nodes[p].children[side] = inner;
+        if let Some(c) = inner {
+            self.nodes[c].parent = Some(p);
+        }
+        self.nodes[x].children[1 - side] = Some(p);
+        self.nodes[p].parent = Some(x);
+        self.nodes[x].parent = grandparent;
+        if let (Some(g), false) = (grandparent, p_is_root) {
+            let p_side = usize::from(self.nodes[g].children[1] == Some(p));
+            self.nodes[g].children[p_side] = Some(x);
+        }
+        self.pull(p);
+        self.pull(x);
+    }
+
+    fn splay(&mut self, x: usize) {
+        // push the lazy tags down from the root of the splay tree first
+        let mut path = vec![x];
+        while !self.is_splay_root(*path.last().unwrap()) {
+            path.push(self.nodes[*path.last().unwrap()].parent.unwrap());
+        }
+        for &y in path.iter().rev() {
+            self.push(y);
+        }
+        while !self.is_splay_root(x) {
+            let p = self.nodes[x].parent.unwrap();
+            if !self.is_splay_root(p) {
+                if self.side(x) == self.side(p) {
+                    self.rotate(p);
+                } else {
+                    self.rotate(x);
+                }
+            }
+            self.rotate(x);
+        }
+    }
+
+    fn pull(&mut self, x: usize) {
+        let [left, right] = self.nodes[x].children;
+        let node = &self.nodes[x];
+        let mut size = 1;
+        let mut forward = node.value;
+        let mut backward = node.value;
+        if let Some(l) = left {
+            size += self.nodes[l].size;
+            forward = (self.merge)(self.nodes[l].forward, forward);
+            backward = (self.merge)(backward, self.nodes[l].backward);
+        }
+        if let Some(r) = right {
+            size += self.nodes[r].size;
+            forward = (self.merge)(forward, self.nodes[r].forward);
+            backward = (self.merge)(self.nodes[r].backward, backward);
+        }
+        let node = &mut self.nodes[x];
+        node.size = size;
+        node.forward = forward;
+        node.backward = backward;
+    }
+
+    fn reverse(&mut self, x: usize) {
+        let node = &mut self.nodes[x];
+        node.children.swap(0, 1);
+        std::mem::swap(&mut node.forward, &mut node.backward);
+        node.reversed = !node.reversed;
+    }
+
+    fn assign(&mut self, x: usize, value: T) {
+        let aggregate = associative_power(self.merge, value, self.nodes[x].size);
+        let node = &mut self.nodes[x];
+        node.value = value;
+        node.forward = aggregate;
+        node.backward = aggregate;
+        node.assigned = Some(value);
+    }
+
+    fn push(&mut self, x: usize) {
+        let children = self.nodes[x].children;
+        if std::mem::take(&mut self.nodes[x].reversed) {
+            for c in children.into_iter().flatten() {
+                self.reverse(c);
+            }
+        }
+        if let Some(value) = self.nodes[x].assigned.take() {
+            for c in children.into_iter().flatten() {
+                self.assign(c, value);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::LowestCommonAncestorOnline;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    const MOD: u64 = 1_000_000_007;
+
+    // Composition of affine maps x -> a * x + b, applying the left one first
+    fn compose(f: (u64, u64), g: (u64, u64)) -> (u64, u64) {
+        (f.0 * g.0 % MOD, (f.1 * g.0 + g.1) % MOD)
+    }
+
+    #[test]
+    fn small_forest() {
+        let mut lct = LinkCutTree::new(&[1, 2, 3, 4, 5, 6], |a, b| a + b);
+        assert!(lct.link(1, 0));
+        assert!(lct.link(2, 1));
+        assert!(lct.link(3, 1));
+        assert!(lct.link(4, 3));
+        assert!(!lct.link(0, 4));
+        assert!(!lct.link(4, 5));
+        assert_eq!(lct.find_root(4), 0);
+        assert_eq!(lct.parent(4), Some(3));
+        assert_eq!(lct.parent(0), None);
+        assert_eq!(lct.lowest_common_ancestor(2, 4), Some(1));
+        assert_eq!(lct.lowest_common_ancestor(2, 5), None);
+        assert_eq!(lct.query_path(2, 4), Some(3 + 2 + 4 + 5));
+        assert_eq!(lct.query_path(5, 5), Some(6));
+        assert_eq!(lct.query_path(0, 5), None);
+
+        assert!(lct.assign_path(0, 4, 10));
+        assert_eq!(lct.query_path(2, 4), Some(3 + 30));
+        // the root hasn't changed
+        assert_eq!(lct.find_root(2), 0);
+
+        assert!(lct.cut(3, 1));
+        assert!(!lct.cut(3, 1));
+        assert_eq!(lct.find_root(4), 3);
+        assert!(!lct.connected(4, 2));
+        assert!(lct.link(5, 4));
+        lct.make_root(5);
+        assert_eq!(lct.lowest_common_ancestor(3, 4), Some(4));
+        lct.set(4, 0);
+        assert_eq!(lct.query_path(3, 5), Some(16));
+    }
+
+    #[test]
+    fn random_forests_match_static_lca() {
+        let mut rng = StdRng::seed_from_u64(24);
+        for _ in 0..20 {
+            let n = rng.gen_range(1..=30);
+            let mut values: Vec<(u64, u64)> = (0..n)
+                .map(|_| (rng.gen_range(1..10), rng.gen_range(0..10)))
+                .collect();
+            let mut lct = LinkCutTree::new(&values, compose);
+            // the forest, as the parent of every vertex
+            let mut parent: Vec<Option<usize>> = vec![None; n];
+            let root_of = |parent: &[Option<usize>], mut v: usize| {
+                while let Some(p) = parent[v] {
+                    v = p;
+                }
+                v
+            };
+            for _ in 0..200 {
+                let (u, v) = (rng.gen_range(0..n), rng.gen_range(0..n));
+                match rng.gen_range(0..5) {
+                    0 => {
+                        let ok = parent[u].is_none() && root_of(&parent, v) != u;
+                        assert_eq!(lct.link(u, v), ok);
+                        if ok {
+                            parent[u] = Some(v);
+                        }
+                    }
+                    1 => {
+                        let ok = parent[u] == Some(v) || parent[v] == Some(u);
+                        assert_eq!(lct.cut(u, v), ok);
+                        if parent[u] == Some(v) {
+                            parent[u] = None;
+                        } else if parent[v] == Some(u) {
+                            parent[v] = None;
+                        }
+                    }
+                    2 => {
+                        // rerooting reverses the parents on the path to the old root
+                        lct.make_root(u);
+                        let mut previous = None;
+                        let mut x = Some(u);
+                        while let Some(y) = x {
+                            x = std::mem::replace(&mut parent[y], previous);
+                            previous = Some(y);
+                        }
+                    }
+                    3 => {
+                        let value = (rng.gen_range(1..10), rng.gen_range(0..10));
+                        lct.assign_path(u, v, value);
+                        if root_of(&parent, u) == root_of(&parent, v) {
+                            let root = root_of(&parent, u);
+                            for w in path(&parent, root, u, v) {
+                                values[w] = value;
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+                assert_eq!(lct.find_root(u), root_of(&parent, u));
+                assert_eq!(lct.parent(v), parent[v]);
+
+                // recompute the LCA structure of the tree of u from scratch,
+                // numbering the vertices from 1
+                let root = root_of(&parent, u);
+                let mut adj = vec![vec![]; n + 1];
+                for (w, p) in parent.iter().enumerate() {
+                    if let Some(p) = p {
+                        adj[p + 1].push(w + 1);
+                    }
+                }
+                let mut lca = LowestCommonAncestorOnline::new(n);
+                lca.fill_sparse_table(root + 1, 0, 0, &adj);
+                if root_of(&parent, v) == root {
+                    assert_eq!(
+                        lct.lowest_common_ancestor(u, v),
+                        Some(lca.get_ancestor(u + 1, v + 1) - 1)
+                    );
+                    let expected = (0..=lca.distance(u + 1, v + 1))
+                        .map(|k| values[lca.kth_on_path(u + 1, v + 1, k).unwrap() - 1])
+                        .reduce(compose);
+                    assert_eq!(lct.query_path(u, v), expected);
+                } else {
+                    assert_eq!(lct.lowest_common_ancestor(u, v), None);
+                    assert_eq!(lct.query_path(u, v), None);
+                }
+            }
+        }
+    }
+
+    // The vertices of the path from u to v in the tree rooted at root
+    fn path(parent: &[Option<usize>], root: usize, u: usize, v: usize) -> Vec<usize> {
+        let to_root = |mut x: usize| {
+            let mut vertices = vec![x];
+            while x != root {
+                x = parent[x].unwrap();
+                vertices.push(x);
+            }
+            vertices
+        };
+        let (from_u, from_v) = (to_root(u), to_root(v));
+        let mut result: Vec<usize> = from_u
+            .iter()
+            .copied()
+            .take_while(|x| !from_v.contains(x))
+            .collect();
+        let top = from_u[result.len()];
+        result.push(top);
+        let down = from_v
+            .iter()
+            .copied()
+            .take_while(|&x| x != top)
+            .collect::<Vec<_>>();
+        result.extend(down.into_iter().rev());
+        result
+    }
+}
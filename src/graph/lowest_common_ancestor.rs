@@ -4,9 +4,14 @@ If a tree is not enumerated that way or its vertices are not represented
 using numbers, it can trivially be converted using Depth First Search
 manually or by using `src/graph/graph_enumeration.rs`
 
- Here we implement two different algorithms:
+ Here we implement three different algorithms:
 - The online one is implemented using Sparse Table and has O(n.lg(n))
 time complexity and memory usage. It answers each query in O(lg(n)).
+The same table gives the k-th ancestor of a vertex, hence the k-th vertex of
+a path, and distances follow from the heights (or weighted depths) of the
+vertices and their LCA.
+- The Euler tour one reduces LCA to a range minimum query over the heights
+of the vertices in DFS order, answered in O(1) after O(n.lg(n)) preprocessing.
 - The offline algorithm was discovered by Robert Tarjan. At first each
 query should be determined and saved. Then, vertices are visited in
 Depth First Search order and queries are answered using Disjoint
@@ -15,41 +20,126 @@ memory usage is O(n + q), but time complexity can be considered to be O(n + q),
 because alpha(n) < 5 for n < 10 ^ 600
  */
 
+use std::ops::{Add, Sub};
+
+use num_traits::Zero;
+
 use super::DisjointSetUnion;
-pub struct LowestCommonAncestorOnline {
+use crate::data_structures::RangeMinimumQuery;
+
+// `E` is the type of the edge weights. Without weights, the distance from the
+// root is the height of a vertex.
+pub struct LowestCommonAncestorOnline<E = usize> {
     // Make members public to allow the user to fill them themself.
+    // `parents_sparse_table` and `height` are needed by every query,
+    // `tin` and `tout` by `is_ancestor`, and `root_distance` by `weighted_distance`.
     pub parents_sparse_table: Vec<Vec<usize>>,
     pub height: Vec<usize>,
+    // The weighted distance from the root to each vertex
+    pub root_distance: Vec<E>,
+    // Entry and exit times of each vertex in the Euler tour of the tree: u is an
+    // ancestor of v if and only if tin[u] <= tin[v] and tout[v] <= tout[u]
+    pub tin: Vec<usize>,
+    pub tout: Vec<usize>,
 }
 
 impl LowestCommonAncestorOnline {
+    pub fn new(num_vertices: usize) -> Self {
+        Self::weighted(num_vertices)
+    }
     // Should be called once as:
     // fill_sparse_table(tree_root, 0, 0, adjacency_list)
-    #[inline]
-    fn get_parent(&self, v: usize, i: usize) -> usize {
-        self.parents_sparse_table[v][i]
+    pub fn fill_sparse_table(
+        &mut self,
+        vertex: usize,
+        parent: usize,
+        height: usize,
+        adj: &[Vec<usize>],
+    ) {
+        self.fill(vertex, parent, height, adj, &mut 0);
     }
-    #[inline]
-    fn num_parents(&self, v: usize) -> usize {
-        self.parents_sparse_table[v].len()
+    // `timer` counts the entries and exits of the Euler tour so far
+    fn fill(
+        &mut self,
+        vertex: usize,
+        parent: usize,
+        height: usize,
+        adj: &[Vec<usize>],
+        timer: &mut usize,
+    ) {
+        self.enter(vertex, parent, height, timer);
+        self.root_distance[vertex] = height;
+        for &child in adj[vertex].iter() {
+            if child == parent {
+                // It isn't a child!
+                continue;
+            }
+            self.fill(child, vertex, height + 1, adj, timer);
+        }
+        self.exit(vertex, timer);
     }
-    pub fn new(num_vertices: usize) -> Self {
+}
+
+impl<E: Copy + Zero> LowestCommonAncestorOnline<E> {
+    pub fn weighted(num_vertices: usize) -> Self {
         let mut pars = vec![vec![0]; num_vertices + 1];
         pars[0].clear();
         LowestCommonAncestorOnline {
             parents_sparse_table: pars,
             height: vec![0; num_vertices + 1],
+            root_distance: vec![E::zero(); num_vertices + 1],
+            tin: vec![0; num_vertices + 1],
+            tout: vec![0; num_vertices + 1],
         }
     }
-    pub fn fill_sparse_table(
+    // Same as `fill_sparse_table`, with `adj[v]` listing (neighbour, weight)
+    // pairs. Should be called once as:
+    // fill_weighted_sparse_table(tree_root, 0, 0, E::zero(), adjacency_list)
+    pub fn fill_weighted_sparse_table(
         &mut self,
         vertex: usize,
         parent: usize,
         height: usize,
-        adj: &[Vec<usize>],
+        distance: E,
+        adj: &[Vec<(usize, E)>],
+    ) {
+        self.fill_weighted(vertex, parent, height, distance, adj, &mut 0);
+    }
+    fn fill_weighted(
+        &mut self,
+        vertex: usize,
+        parent: usize,
+        height: usize,
+        distance: E,
+        adj: &[Vec<(usize, E)>],
+        timer: &mut usize,
     ) {
+        self.enter(vertex, parent, height, timer);
+        self.root_distance[vertex] = distance;
+        for &(child, weight) in adj[vertex].iter() {
+            if child == parent {
+                continue;
+            }
+            self.fill_weighted(child, vertex, height + 1, distance + weight, adj, timer);
+        }
+        self.exit(vertex, timer);
+    }
+}
+
+impl<E> LowestCommonAncestorOnline<E> {
+    #[inline]
+    fn get_parent(&self, v: usize, i: usize) -> usize {
+        self.parents_sparse_table[v][i]
+    }
+    #[inline]
+    fn num_parents(&self, v: usize) -> usize {
+        self.parents_sparse_table[v].len()
+    }
+    fn enter(&mut self, vertex: usize, parent: usize, height: usize, timer: &mut usize) {
         self.parents_sparse_table[vertex][0] = parent;
         self.height[vertex] = height;
+        self.tin[vertex] = *timer;
+        *timer += 1;
         let mut level = 1;
         let mut current_parent = parent;
         while self.num_parents(current_parent) >= level {
@@ -57,13 +147,10 @@ impl LowestCommonAncestorOnline {
             level += 1;
             self.parents_sparse_table[vertex].push(current_parent);
         }
-        for &child in adj[vertex].iter() {
-            if child == parent {
-                // It isn't a child!
-                continue;
-            }
-            self.fill_sparse_table(child, vertex, height + 1, adj);
-        }
+    }
+    fn exit(&mut self, vertex: usize, timer: &mut usize) {
+        self.tout[vertex] = *timer;
+        *timer += 1;
     }
 
     pub fn get_ancestor(&self, mut v: usize, mut u: usize) -> usize {
@@ -72,15 +159,7 @@ impl LowestCommonAncestorOnline {
         }
         // Bring v up to so that it has the same height as u
         let height_diff = self.height[v] - self.height[u];
-        for i in 0..63 {
-            let bit = 1 << i;
-            if bit > height_diff {
-                break;
-            }
-            if height_diff & bit != 0 {
-                v = self.get_parent(v, i);
-            }
-        }
+        v = self.kth_ancestor(v, height_diff).unwrap();
         if u == v {
             return u;
         }
@@ -95,6 +174,131 @@ impl LowestCommonAncestorOnline {
         }
         self.get_parent(v, 0)
     }
+
+    // Whether u is an ancestor of v. Every vertex is an ancestor of itself.
+    pub fn is_ancestor(&self, u: usize, v: usize) -> bool {
+        self.tin[u] <= self.tin[v] && self.tout[v] <= self.tout[u]
+    }
+
+    // The ancestor of v that is k levels higher, if v is deep enough
+    pub fn kth_ancestor(&self, mut v: usize, k: usize) -> Option<usize> {
+        if k > self.height[v] {
+            return None;
+        }
+        for i in 0..usize::BITS as usize {
+            let bit = 1 << i;
+            if bit > k {
+                break;
+            }
+            if k & bit != 0 {
+                v = self.get_parent(v, i);
+            }
+        }
+        Some(v)
+    }
+
+    // The number of edges on the path between u and v
+    pub fn distance(&self, u: usize, v: usize) -> usize {
+        let ancestor = self.get_ancestor(u, v);
+        self.height[u] + self.height[v] - 2 * self.height[ancestor]
+    }
+
+    // The k-th vertex on the path from u to v, u being the 0-th one
+    pub fn kth_on_path(&self, u: usize, v: usize, k: usize) -> Option<usize> {
+        let ancestor = self.get_ancestor(u, v);
+        let up = self.height[u] - self.height[ancestor];
+        let down = self.height[v] - self.height[ancestor];
+        if k <= up {
+            self.kth_ancestor(u, k)
+        } else if k <= up + down {
+            self.kth_ancestor(v, up + down - k)
+        } else {
+            None
+        }
+    }
+}
+
+impl<E: Copy + Add<Output = E> + Sub<Output = E>> LowestCommonAncestorOnline<E> {
+    // The sum of the weights of the edges on the path between u and v
+    pub fn weighted_distance(&self, u: usize, v: usize) -> E {
+        let ancestor = self.root_distance[self.get_ancestor(u, v)];
+        (self.root_distance[u] - ancestor) + (self.root_distance[v] - ancestor)
+    }
+}
+
+/*
+ The Euler tour lists the vertices each time the DFS goes through them, so it
+has 2n - 1 entries. The LCA of u and v is the highest vertex of the tour
+between their first occurrences, given by a `RangeMinimumQuery` over the
+(height, vertex) pairs. O(n.lg(n)) preprocessing, O(1) per query.
+ */
+pub struct EulerTourLowestCommonAncestor {
+    first_occurrence: Vec<usize>,
+    height: Vec<usize>,
+    tour: RangeMinimumQuery<(usize, usize)>,
+}
+
+impl EulerTourLowestCommonAncestor {
+    pub fn new(root: usize, adj: &[Vec<usize>]) -> Self {
+        let mut first_occurrence = vec![0; adj.len()];
+        let mut height = vec![0; adj.len()];
+        let mut tour = Vec::with_capacity(2 * adj.len());
+        Self::euler_tour(
+            root,
+            0,
+            0,
+            adj,
+            &mut first_occurrence,
+            &mut height,
+            &mut tour,
+        );
+        EulerTourLowestCommonAncestor {
+            first_occurrence,
+            height,
+            tour: RangeMinimumQuery::new(&tour),
+        }
+    }
+
+    fn euler_tour(
+        vertex: usize,
+        parent: usize,
+        depth: usize,
+        adj: &[Vec<usize>],
+        first_occurrence: &mut [usize],
+        height: &mut [usize],
+        tour: &mut Vec<(usize, usize)>,
+    ) {
+        first_occurrence[vertex] = tour.len();
+        height[vertex] = depth;
+        tour.push((depth, vertex));
+        for &child in adj[vertex].iter() {
+            if child == parent {
+                continue;
+            }
+            Self::euler_tour(
+                child,
+                vertex,
+                depth + 1,
+                adj,
+                first_occurrence,
+                height,
+                tour,
+            );
+            tour.push((depth, vertex));
+        }
+    }
+
+    pub fn get_ancestor(&self, u: usize, v: usize) -> usize {
+        let (a, b) = (self.first_occurrence[u], self.first_occurrence[v]);
+        let (_, ancestor) = self.tour.get_range_min(a.min(b), a.max(b) + 1).unwrap();
+        ancestor
+    }
+
+    // The number of edges on the path between u and v
+    pub fn distance(&self, u: usize, v: usize) -> usize {
+        let ancestor = self.get_ancestor(u, v);
+        self.height[u] + self.height[v] - 2 * self.height[ancestor]
+    }
 }
 
 #[derive(Clone, Copy)]
@@ -176,6 +380,8 @@ impl LowestCommonAncestorOffline {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
     #[test]
     fn small_binary_tree() {
         let num_verts = 127;
@@ -208,4 +414,132 @@ mod tests {
         offline_answers.sort_unstable_by(|a1, a2| a1.query_id.cmp(&a2.query_id));
         assert_eq!(offline_answers, online_answers);
     }
+
+    // The vertices of the path from u to v, by walking up to the LCA
+    fn brute_path(parent: &[usize], height: &[usize], mut u: usize, mut v: usize) -> Vec<usize> {
+        let mut from_u = vec![];
+        let mut to_v = vec![];
+        while u != v {
+            if height[u] >= height[v] {
+                from_u.push(u);
+                u = parent[u];
+            } else {
+                to_v.push(v);
+                v = parent[v];
+            }
+        }
+        from_u.push(u);
+        from_u.extend(to_v.into_iter().rev());
+        from_u
+    }
+
+    #[test]
+    fn path_queries() {
+        // 1 -(3)- 2 -(5)- 4, 1 -(2)- 3 -(7)- 5 -(1)- 6
+        let edges = [(1, 2, 3), (2, 4, 5), (1, 3, 2), (3, 5, 7), (5, 6, 1)];
+        let mut tree = vec![vec![]; 7];
+        let mut weighted = vec![vec![]; 7];
+        for (u, v, w) in edges {
+            tree[u].push(v);
+            tree[v].push(u);
+            weighted[u].push((v, w));
+            weighted[v].push((u, w));
+        }
+        let mut lca = LowestCommonAncestorOnline::weighted(6);
+        lca.fill_weighted_sparse_table(1, 0, 0, 0u64, &weighted);
+        assert_eq!(lca.get_ancestor(4, 6), 1);
+        assert_eq!(lca.weighted_distance(4, 6), 18);
+        assert_eq!(lca.weighted_distance(3, 6), 8);
+        assert_eq!(lca.distance(4, 6), 5);
+        assert_eq!(lca.kth_ancestor(6, 2), Some(3));
+        assert_eq!(lca.kth_ancestor(6, 4), None);
+        assert_eq!(lca.kth_on_path(4, 6, 1), Some(2));
+        assert_eq!(lca.kth_on_path(4, 6, 3), Some(3));
+        assert_eq!(lca.kth_on_path(4, 6, 5), Some(6));
+        assert_eq!(lca.kth_on_path(4, 6, 6), None);
+        assert!(lca.is_ancestor(3, 6));
+        assert!(lca.is_ancestor(6, 6));
+        assert!(!lca.is_ancestor(2, 6));
+
+        let mut unweighted = LowestCommonAncestorOnline::new(6);
+        unweighted.fill_sparse_table(1, 0, 0, &tree);
+        assert_eq!(unweighted.weighted_distance(4, 6), 5);
+        let euler = EulerTourLowestCommonAncestor::new(1, &tree);
+        assert_eq!(euler.get_ancestor(4, 6), 1);
+        assert_eq!(euler.get_ancestor(5, 6), 5);
+        assert_eq!(euler.distance(2, 6), 4);
+
+        // filled by hand: 1 is the root, 2 and 3 its children, 4 a child of 2
+        let by_hand = LowestCommonAncestorOnline {
+            parents_sparse_table: vec![vec![], vec![0], vec![1, 0], vec![1, 0], vec![2, 1, 0]],
+            height: vec![0, 0, 1, 1, 2],
+            root_distance: vec![0, 0, 1, 1, 2],
+            tin: vec![0, 0, 1, 5, 2],
+            tout: vec![0, 7, 4, 6, 3],
+        };
+        assert_eq!(by_hand.get_ancestor(4, 3), 1);
+        assert!(by_hand.is_ancestor(2, 4));
+        assert!(!by_hand.is_ancestor(3, 4));
+        assert!(!by_hand.is_ancestor(4, 2));
+        assert_eq!(by_hand.weighted_distance(4, 3), 3);
+    }
+
+    #[test]
+    fn random_trees() {
+        let mut rng = StdRng::seed_from_u64(23);
+        for _ in 0..30 {
+            let n = rng.gen_range(1..=60);
+            let root = rng.gen_range(1..=n);
+            // a random tree with labels shuffled around the root
+            let mut tree = vec![vec![]; n + 1];
+            let mut weighted = vec![vec![]; n + 1];
+            let mut labels: Vec<usize> = (1..=n).filter(|&v| v != root).collect();
+            labels.insert(0, root);
+            for i in 1..n {
+                let j = rng.gen_range(0..i);
+                let (u, v, w) = (labels[j], labels[i], rng.gen_range(0..100i64));
+                tree[u].push(v);
+                tree[v].push(u);
+                weighted[u].push((v, w));
+                weighted[v].push((u, w));
+            }
+            let mut parent = vec![0; n + 1];
+            let mut height = vec![0; n + 1];
+            let mut weight_to_parent = vec![0; n + 1];
+            let mut stack = vec![root];
+            while let Some(u) = stack.pop() {
+                for &(v, w) in &weighted[u] {
+                    if v != parent[u] {
+                        parent[v] = u;
+                        height[v] = height[u] + 1;
+                        weight_to_parent[v] = w;
+                        stack.push(v);
+                    }
+                }
+            }
+
+            let mut lca = LowestCommonAncestorOnline::weighted(n);
+            lca.fill_weighted_sparse_table(root, 0, 0, 0, &weighted);
+            let euler = EulerTourLowestCommonAncestor::new(root, &tree);
+            for _ in 0..100 {
+                let (u, v) = (rng.gen_range(1..=n), rng.gen_range(1..=n));
+                let path = brute_path(&parent, &height, u, v);
+                let top = *path.iter().min_by_key(|&&w| height[w]).unwrap();
+                assert_eq!(lca.get_ancestor(u, v), top);
+                assert_eq!(euler.get_ancestor(u, v), top);
+                assert_eq!(lca.distance(u, v), path.len() - 1);
+                assert_eq!(euler.distance(u, v), path.len() - 1);
+                let weight: i64 = path
+                    .iter()
+                    .filter(|&&w| w != top)
+                    .map(|&w| weight_to_parent[w])
+                    .sum();
+                assert_eq!(lca.weighted_distance(u, v), weight);
+                for k in 0..=path.len() {
+                    assert_eq!(lca.kth_on_path(u, v, k), path.get(k).copied());
+                }
+                assert_eq!(lca.is_ancestor(u, v), top == u);
+            }
+        }
+    }
 }
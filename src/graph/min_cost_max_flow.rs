@@ -0,0 +1,374 @@
+/*
+Min-cost max-flow with successive shortest paths.
+
+Every edge has a capacity and a cost per unit of flow. Among all the maximum
+flows from the source to the sink, we look for one of minimum total cost.
+
+The algorithm repeatedly sends flow along a cheapest path of the residual
+network. Costs may be negative, as long as the network has no cycle of negative
+cost: a first Bellman-Ford pass computes the distances from the source, and
+they are used as vertex potentials p so that the reduced costs
+cost(u, v) + p(u) - p(v) of the residual edges are never negative. Every next
+cheapest path can then be found with Dijkstra's algorithm, after which the
+potentials are updated with the new distances.
+
+Complexity: O(V * E) for Bellman-Ford, then O(E log V) per augmenting path.
+The number of augmenting paths is bounded by the value of the flow when the
+capacities are integers.
+
+Like `DinicMaxFlow`, we assume that the vertices are numbered from 1 to n.
+*/
+
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, VecDeque};
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+
+use super::dinic_maxflow::{FlowEdge, FlowResultEdge};
+
+pub struct MinCostMaxFlow<T> {
+    pub source: usize,
+    pub sink: usize,
+
+    /// Number of edges added to the residual network
+    pub num_edges: usize,
+    pub num_vertices: usize,
+
+    /// The indices of the edges leaving each vertex
+    pub adj: Vec<Vec<usize>>,
+
+    /// The list of flow edges. Edge `e ^ 1` is the reverse of edge `e`
+    pub edges: Vec<FlowEdge<T>>,
+    /// The cost of one unit of flow on each edge, the opposite for reverse edges
+    pub costs: Vec<T>,
+
+    /// The potential of each vertex, `None` for vertices the source cannot reach
+    potential: Vec<Option<T>>,
+}
+
+impl<T> MinCostMaxFlow<T>
+where
+    T: Copy
+        + Add<Output = T>
+        + AddAssign
+        + Sub<Output = T>
+        + SubAssign
+        + Mul<Output = T>
+        + Neg<Output = T>
+        + Ord
+        + Default,
+{
+    pub fn new(source: usize, sink: usize, num_vertices: usize) -> Self {
+        MinCostMaxFlow {
+            source,
+            sink,
+            num_edges: 0,
+            num_vertices,
+            adj: vec![vec![]; num_vertices + 1],
+            edges: vec![],
+            costs: vec![],
+            potential: vec![],
+        }
+    }
+
+    pub fn add_edge(&mut self, source: usize, sink: usize, capacity: T, cost: T) {
+        self.edges.push(FlowEdge::new(sink, capacity));
+        self.costs.push(cost);
+        // Add the reverse edge with zero capacity, which refunds the cost
+        self.edges.push(FlowEdge::new(source, T::default()));
+        self.costs.push(-cost);
+        self.adj[source].push(self.num_edges);
+        self.adj[sink].push(self.num_edges + 1);
+        self.num_edges += 2;
+    }
+
+    fn has_capacity(&self, e: usize) -> bool {
+        self.edges[e].capacity > self.edges[e].flow
+    }
+
+    // Bellman-Ford (queue based) from the source, which gives the initial potentials
+    fn init_potential(&mut self) {
+        let mut distance: Vec<Option<T>> = vec![None; self.num_vertices + 1];
+        let mut in_queue = vec![false; self.num_vertices + 1];
+        // A vertex that enters the queue n times lies on a negative cycle
+        let mut relaxations = vec![0; self.num_vertices + 1];
+        let mut queue = VecDeque::from([self.source]);
+        distance[self.source] = Some(T::default());
+        while let Some(u) = queue.pop_front() {
+            in_queue[u] = false;
+            let du = distance[u].unwrap();
+            for &e in &self.adj[u] {
+                let v = self.edges[e].sink;
+                let candidate = du + self.costs[e];
+                if self.has_capacity(e) && distance[v].is_none_or(|dv| candidate < dv) {
+                    distance[v] = Some(candidate);
+                    if !in_queue[v] {
+                        relaxations[v] += 1;
+                        assert!(
+                            relaxations[v] <= self.num_vertices,
+                            "the network has a cycle of negative cost"
+                        );
+                        in_queue[v] = true;
+                        queue.push_back(v);
+                    }
+                }
+            }
+        }
+        self.potential = distance;
+    }
+
+    // Dijkstra on the reduced costs. Returns, for every vertex reached, the
+    // edge used to reach it, and updates the potentials with the distances.
+    fn shortest_paths(&mut self) -> Vec<Option<usize>> {
+        let mut distance: Vec<Option<T>> = vec![None; self.num_vertices + 1];
+        let mut parent_edge = vec![None; self.num_vertices + 1];
+        let mut heap = BinaryHeap::new();
+        distance[self.source] = Some(T::default());
+        heap.push(Reverse((T::default(), self.source)));
+        while let Some(Reverse((du, u))) = heap.pop() {
+            if distance[u] != Some(du) {
+                continue;
+            }
+            let pu = self.potential[u].unwrap();
+            for &e in &self.adj[u] {
+                let v = self.edges[e].sink;
+                if !self.has_capacity(e) {
+                    continue;
+                }
+                // The reduced cost, which is never negative
+                let candidate = du + self.costs[e] + pu - self.potential[v].unwrap();
+                if distance[v].is_none_or(|dv| candidate < dv) {
+                    distance[v] = Some(candidate);
+                    parent_edge[v] = Some(e);
+                    heap.push(Reverse((candidate, v)));
+                }
+            }
+        }
+        for (potential, distance) in self.potential.iter_mut().zip(distance) {
+            if let (Some(p), Some(d)) = (potential.as_mut(), distance) {
+                *p += d;
+            }
+        }
+        parent_edge
+    }
+
+    /// Sends at most `flow_limit` units of flow from the source to the sink,
+    /// as cheaply as possible. Returns the flow that was sent and its total cost.
+    /// The flow already in the network is kept, so calling this again sends
+    /// more flow on top of it.
+    ///
+    /// Panics if the network has a cycle of negative cost.
+    pub fn find_min_cost_flow(&mut self, flow_limit: T) -> (T, T) {
+        self.init_potential();
+        let (mut total_flow, mut total_cost) = (T::default(), T::default());
+        while total_flow < flow_limit {
+            let parent_edge = self.shortest_paths();
+            if parent_edge[self.sink].is_none() {
+                break;
+            }
+            let mut path = Vec::new();
+            let mut v = self.sink;
+            while let Some(e) = parent_edge[v] {
+                path.push(e);
+                v = self.edges[e ^ 1].sink;
+            }
+            let pushed = path.iter().fold(flow_limit - total_flow, |pushed, &e| {
+                std::cmp::min(pushed, self.edges[e].capacity - self.edges[e].flow)
+            });
+            for &e in &path {
+                self.edges[e].flow += pushed;
+                self.edges[e ^ 1].flow -= pushed;
+                total_cost += pushed * self.costs[e];
+            }
+            total_flow += pushed;
+        }
+        (total_flow, total_cost)
+    }
+
+    /// The maximum flow of minimum cost, and that cost. `infinite_flow` must
+    /// be larger than the maximum flow.
+    pub fn find_min_cost_max_flow(&mut self, infinite_flow: T) -> (T, T) {
+        self.find_min_cost_flow(infinite_flow)
+    }
+
+    /// The edges that carry flow after `find_min_cost_flow` or `find_min_cost_max_flow`
+    pub fn get_flow_edges(&self) -> Vec<FlowResultEdge<T>> {
+        let mut result = Vec::new();
+        for v in 1..self.adj.len() {
+            for &e_ind in self.adj[v].iter() {
+                let e = &self.edges[e_ind];
+                // Reverse edges never carry a positive flow
+                if e.flow > T::default() {
+                    result.push(FlowResultEdge {
+                        source: v,
+                        sink: e.sink,
+                        flow: e.flow,
+                    });
+                }
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::DinicMaxFlow;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn small_network() {
+        let mut flow = MinCostMaxFlow::new(1, 4, 4);
+        flow.add_edge(1, 2, 2, 1);
+        flow.add_edge(1, 3, 1, 2);
+        flow.add_edge(2, 3, 1, 1);
+        flow.add_edge(2, 4, 1, 3);
+        flow.add_edge(3, 4, 2, 1);
+        assert_eq!(flow.find_min_cost_max_flow(i32::MAX), (3, 10));
+
+        let mut edges: Vec<_> = flow
+            .get_flow_edges()
+            .into_iter()
+            .map(|e| (e.source, e.sink, e.flow))
+            .collect();
+        edges.sort();
+        assert_eq!(
+            edges,
+            vec![(1, 2, 2), (1, 3, 1), (2, 3, 1), (2, 4, 1), (3, 4, 2)]
+        );
+    }
+
+    #[test]
+    fn flow_limit() {
+        let mut flow = MinCostMaxFlow::new(1, 3, 3);
+        flow.add_edge(1, 3, 5, 10);
+        flow.add_edge(1, 2, 2, 1);
+        flow.add_edge(2, 3, 2, 1);
+        assert_eq!(flow.find_min_cost_flow(3), (3, 14));
+
+        let mut unreachable = MinCostMaxFlow::new(1, 3, 3);
+        unreachable.add_edge(2, 3, 5, 1);
+        assert_eq!(unreachable.find_min_cost_max_flow(i32::MAX), (0, 0));
+        assert!(unreachable.get_flow_edges().is_empty());
+    }
+
+    #[test]
+    fn negative_costs() {
+        // Taking the long way through the negative edge is cheaper
+        let mut flow = MinCostMaxFlow::new(1, 4, 4);
+        flow.add_edge(1, 4, 1, 0);
+        flow.add_edge(1, 2, 1, 1);
+        flow.add_edge(2, 3, 1, -5);
+        flow.add_edge(3, 4, 1, 1);
+        flow.add_edge(2, 4, 1, 0);
+        assert_eq!(flow.find_min_cost_flow(1), (1, -3));
+        // The second call only sends the flow that is still missing
+        assert_eq!(flow.find_min_cost_max_flow(i64::MAX), (1, 0));
+        assert_eq!(flow.get_flow_edges().len(), 4);
+    }
+
+    #[test]
+    #[should_panic(expected = "negative cost")]
+    fn negative_cycle() {
+        let mut flow = MinCostMaxFlow::new(1, 4, 4);
+        flow.add_edge(1, 2, 1, 1);
+        flow.add_edge(2, 3, 1, -2);
+        flow.add_edge(3, 2, 1, 1);
+        flow.add_edge(3, 4, 1, 1);
+        flow.find_min_cost_max_flow(10);
+    }
+
+    // The cheapest assignment of n workers to n jobs, by trying every permutation
+    fn brute_force_assignment(cost: &[Vec<i64>]) -> i64 {
+        fn go(cost: &[Vec<i64>], row: usize, used: &mut Vec<bool>) -> i64 {
+            if row == cost.len() {
+                return 0;
+            }
+            let mut best = i64::MAX;
+            for job in 0..cost.len() {
+                if !used[job] {
+                    used[job] = true;
+                    best = best.min(cost[row][job] + go(cost, row + 1, used));
+                    used[job] = false;
+                }
+            }
+            best
+        }
+        go(cost, 0, &mut vec![false; cost.len()])
+    }
+
+    #[test]
+    fn assignment_problems() {
+        let mut rng = StdRng::seed_from_u64(5);
+        for _ in 0..50 {
+            let n = rng.gen_range(1..=6);
+            let cost: Vec<Vec<i64>> = (0..n)
+                .map(|_| (0..n).map(|_| rng.gen_range(-20..=20)).collect())
+                .collect();
+            // Vertices: source 1, workers 2..=n+1, jobs n+2..=2n+1, sink 2n+2
+            let mut flow = MinCostMaxFlow::new(1, 2 * n + 2, 2 * n + 2);
+            for (i, row) in cost.iter().enumerate() {
+                flow.add_edge(1, i + 2, 1, 0);
+                flow.add_edge(n + i + 2, 2 * n + 2, 1, 0);
+                for (j, &c) in row.iter().enumerate() {
+                    flow.add_edge(i + 2, n + j + 2, 1, c);
+                }
+            }
+            let (max_flow, min_cost) = flow.find_min_cost_max_flow(i64::MAX);
+            assert_eq!(max_flow, n as i64);
+            assert_eq!(min_cost, brute_force_assignment(&cost));
+        }
+    }
+
+    #[test]
+    fn random_networks() {
+        let mut rng = StdRng::seed_from_u64(17);
+        for _ in 0..50 {
+            let n = rng.gen_range(2..=12);
+            let mut flow = MinCostMaxFlow::new(1, n, n);
+            let mut dinic = DinicMaxFlow::new(1, n, n);
+            for _ in 0..rng.gen_range(0..=3 * n) {
+                // Edges only go forward, so there is no cycle of negative cost
+                let u = rng.gen_range(1..n);
+                let v = rng.gen_range(u + 1..=n);
+                let capacity = rng.gen_range(1..=10);
+                flow.add_edge(u, v, capacity, rng.gen_range(-10..=10));
+                dinic.add_edge(u, v, capacity);
+            }
+            let (max_flow, min_cost) = flow.find_min_cost_max_flow(i64::MAX);
+            assert_eq!(max_flow, dinic.find_maxflow(i64::MAX));
+
+            let mut balance = vec![0; n + 1];
+            let mut cost = 0;
+            for e in (0..flow.num_edges).step_by(2) {
+                let (u, edge) = (flow.edges[e + 1].sink, &flow.edges[e]);
+                assert!(edge.flow >= 0 && edge.flow <= edge.capacity);
+                balance[u] -= edge.flow;
+                balance[edge.sink] += edge.flow;
+                cost += edge.flow * flow.costs[e];
+            }
+            assert_eq!(cost, min_cost);
+            assert_eq!(balance[n], max_flow);
+            assert!(balance[2..n].iter().all(|&b| b == 0));
+
+            // The flow is of minimum cost iff the residual network has no
+            // cycle of negative cost, which Bellman-Ford would detect
+            let mut distance = vec![0; n + 1];
+            for _ in 0..n {
+                for e in 0..flow.num_edges {
+                    let (u, v) = (flow.edges[e ^ 1].sink, flow.edges[e].sink);
+                    if flow.edges[e].capacity > flow.edges[e].flow {
+                        distance[v] = distance[v].min(distance[u] + flow.costs[e]);
+                    }
+                }
+            }
+            for e in 0..flow.num_edges {
+                let (u, v) = (flow.edges[e ^ 1].sink, flow.edges[e].sink);
+                if flow.edges[e].capacity > flow.edges[e].flow {
+                    assert!(distance[v] <= distance[u] + flow.costs[e]);
+                }
+            }
+        }
+    }
+}
@@ -1,51 +1,107 @@
 mod astar;
 mod bellman_ford;
+mod biconnectivity;
+mod bidirectional_dijkstra;
 mod bipartite_matching;
+mod blossom_matching;
 mod breadth_first_search;
 mod centroid_decomposition;
+mod compressed_sparse_row;
+mod condensation;
+mod contraction_hierarchies;
+mod critical_path;
 mod depth_first_search;
 mod depth_first_search_tic_tac_toe;
 mod dijkstra;
 mod dinic_maxflow;
 mod disjoint_set_union;
+mod dynamic_connectivity;
 mod eulerian_path;
+mod flow_with_lower_bounds;
 mod floyd_warshall;
 mod ford_fulkerson;
 mod graph_enumeration;
+mod graph_formats;
 mod heavy_light_decomposition;
+mod heavy_light_queries;
+mod hungarian_algorithm;
+mod johnson;
+mod k_shortest_paths;
 mod kosaraju;
 mod lee_breadth_first_search;
+mod link_cut_tree;
 mod lowest_common_ancestor;
+mod min_cost_max_flow;
 mod minimum_spanning_tree;
 mod prim;
 mod prufer_code;
+mod rollback_disjoint_set_union;
+mod stoer_wagner;
 mod strongly_connected_components;
 mod tarjans_ssc;
 mod topological_sort;
+mod tree;
 mod two_satisfiability;
+mod weighted_graph;
 
-pub use self::astar::astar;
-pub use self::bellman_ford::bellman_ford;
+pub use self::astar::{astar, astar_with_expanded_count};
+pub use self::bellman_ford::{bellman_ford, NegativeCycle};
+pub use self::biconnectivity::{Biconnectivity, BlockCutNode};
+pub use self::bidirectional_dijkstra::BidirectionalDijkstra;
 pub use self::bipartite_matching::BipartiteMatching;
-pub use self::breadth_first_search::breadth_first_search;
+pub use self::blossom_matching::BlossomMatching;
+pub use self::breadth_first_search::{breadth_first_search, breadth_first_search_csr};
 pub use self::centroid_decomposition::CentroidDecomposition;
+pub use self::compressed_sparse_row::CsrGraph;
+pub use self::condensation::Condensation;
+pub use self::contraction_hierarchies::ContractionHierarchy;
+pub use self::critical_path::{critical_path, longest_path, Schedule};
 pub use self::depth_first_search::depth_first_search;
 pub use self::depth_first_search_tic_tac_toe::minimax;
-pub use self::dijkstra::dijkstra;
-pub use self::dinic_maxflow::DinicMaxFlow;
+pub use self::dijkstra::{
+    dijkstra, dijkstra_csr, dijkstra_multi_source, dijkstra_path, reconstruct_path,
+};
+pub use self::dinic_maxflow::{DinicMaxFlow, FlowEdge, FlowResultEdge, MinCut};
 pub use self::disjoint_set_union::DisjointSetUnion;
+pub use self::dynamic_connectivity::DynamicConnectivity;
 pub use self::eulerian_path::EulerianPath;
+pub use self::flow_with_lower_bounds::{BoundedFlow, InfeasibleFlow, LowerBoundedFlow};
 pub use self::floyd_warshall::floyd_warshall;
-pub use self::ford_fulkerson::ford_fulkerson;
+pub use self::ford_fulkerson::{ford_fulkerson, ford_fulkerson_min_cut};
 pub use self::graph_enumeration::enumerate_graph;
+pub use self::graph_formats::{
+    parse_adjacency_list, parse_dimacs_max_flow, parse_dimacs_shortest_path, parse_dot,
+    parse_edge_list, write_adjacency_list, write_dimacs_max_flow, write_dimacs_shortest_path,
+    write_dot, write_dot_highlighted, write_edge_list, write_flow_dot, EdgeList, ParseError,
+};
 pub use self::heavy_light_decomposition::HeavyLightDecomposition;
+pub use self::heavy_light_queries::{HeavyLightQueries, ValueLocation};
+pub use self::hungarian_algorithm::{hungarian, Objective};
+pub use self::johnson::johnson;
+pub use self::k_shortest_paths::{k_shortest_paths, yen_k_shortest_paths, KShortestPaths};
 pub use self::kosaraju::kosaraju;
 pub use self::lee_breadth_first_search::lee;
-pub use self::lowest_common_ancestor::{LowestCommonAncestorOffline, LowestCommonAncestorOnline};
+pub use self::link_cut_tree::LinkCutTree;
+pub use self::lowest_common_ancestor::{
+    EulerTourLowestCommonAncestor, LowestCommonAncestorOffline, LowestCommonAncestorOnline,
+};
+pub use self::min_cost_max_flow::MinCostMaxFlow;
 pub use self::minimum_spanning_tree::kruskal;
 pub use self::prim::{prim, prim_with_start};
 pub use self::prufer_code::{prufer_decode, prufer_encode};
+pub use self::rollback_disjoint_set_union::RollbackDisjointSetUnion;
+pub use self::stoer_wagner::stoer_wagner;
 pub use self::strongly_connected_components::StronglyConnectedComponents;
 pub use self::tarjans_ssc::tarjan_scc;
-pub use self::topological_sort::topological_sort;
-pub use self::two_satisfiability::solve_two_satisfiability;
+pub use self::topological_sort::{
+    lexicographic_topological_sort, topological_sort, topological_sort_csr, topological_sort_graph,
+    IncrementalTopologicalSort, TopoligicalSortError,
+};
+pub use self::tree::{
+    canonical_form, isomorphic, reroot, rooted_canonical_form, rooted_isomorphic, tree_center,
+    tree_centroid, tree_diameter,
+};
+pub use self::two_satisfiability::{
+    solve_two_satisfiability, Contradiction, Literal, TwoSatisfiability,
+};
+pub use self::weighted_graph::WeightedGraph;
@@ -2,6 +2,8 @@ use std::cmp::Reverse;
 use std::collections::{BTreeMap, BinaryHeap};
 use std::ops::Add;
 
+use super::WeightedGraph;
+
 type Graph<V, E> = BTreeMap<V, BTreeMap<V, E>>;
 
 fn add_edge<V: Ord + Copy, E: Ord + Add + Copy>(graph: &mut Graph<V, E>, v1: V, v2: V, c: E) {
@@ -10,21 +12,28 @@ fn add_edge<V: Ord + Copy, E: Ord + Add + Copy>(graph: &mut Graph<V, E>, v1: V,
 }
 
 // selects a start and run the algorithm from it
-pub fn prim<V: Ord + Copy + std::fmt::Debug, E: Ord + Add + Copy + std::fmt::Debug>(
-    graph: &Graph<V, E>,
-) -> Graph<V, E> {
-    match graph.keys().next() {
-        Some(v) => prim_with_start(graph, *v),
+pub fn prim<V, E>(graph: &impl WeightedGraph<Vertex = V, Weight = E>) -> Graph<V, E>
+where
+    V: Ord + Copy + std::fmt::Debug,
+    E: Ord + Add + Copy + std::fmt::Debug,
+{
+    match graph.vertices().next() {
+        Some(v) => prim_with_start(graph, v),
         None => BTreeMap::new(),
     }
 }
 
 // only works for a connected graph
 // if the given graph is not connected it will return the MST of the connected subgraph
-pub fn prim_with_start<V: Ord + Copy, E: Ord + Add + Copy>(
-    graph: &Graph<V, E>,
+// the graph must be undirected, i.e. store every edge in both directions
+pub fn prim_with_start<V, E>(
+    graph: &impl WeightedGraph<Vertex = V, Weight = E>,
     start: V,
-) -> Graph<V, E> {
+) -> Graph<V, E>
+where
+    V: Ord + Copy,
+    E: Ord + Add + Copy,
+{
     // will contain the MST
     let mut mst: Graph<V, E> = Graph::new();
     // a priority queue based on a binary heap, used to get the cheapest edge
@@ -33,23 +42,23 @@ pub fn prim_with_start<V: Ord + Copy, E: Ord + Add + Copy>(
 
     mst.insert(start, BTreeMap::new());
 
-    for (v, c) in &graph[&start] {
+    for (v, c) in graph.neighbors(start) {
         // the heap is a max heap, we have to use Reverse when adding to simulate a min heap
-        prio.push(Reverse((*c, v, start)));
+        prio.push(Reverse((c, v, start)));
     }
 
     while let Some(Reverse((dist, t, prev))) = prio.pop() {
         // the destination of the edge has already been seen
-        if mst.contains_key(t) {
+        if mst.contains_key(&t) {
             continue;
         }
 
         // the destination is a new vertex
-        add_edge(&mut mst, prev, *t, dist);
+        add_edge(&mut mst, prev, t, dist);
 
-        for (v, c) in &graph[t] {
-            if !mst.contains_key(v) {
-                prio.push(Reverse((*c, v, *t)));
+        for (v, c) in graph.neighbors(t) {
+            if !mst.contains_key(&v) {
+                prio.push(Reverse((c, v, t)));
             }
         }
     }
@@ -64,7 +73,7 @@ mod tests {
 
     #[test]
     fn empty() {
-        assert_eq!(prim::<usize, usize>(&BTreeMap::new()), BTreeMap::new());
+        assert_eq!(prim::<usize, usize>(&Graph::new()), BTreeMap::new());
     }
 
     #[test]
@@ -1,6 +1,6 @@
 use std::collections::{BTreeMap, BTreeSet, BinaryHeap};
 
-type Graph<V> = BTreeMap<V, Vec<V>>;
+pub(crate) type Graph<V> = BTreeMap<V, Vec<V>>;
 
 pub fn prufer_encode<V: Ord + Copy>(tree: &Graph<V>) -> Vec<V> {
     if tree.len() <= 2 {
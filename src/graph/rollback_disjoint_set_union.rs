@@ -0,0 +1,199 @@
+/*
+Disjoint Set Union with rollback:
+Unlike `DisjointSetUnion`, paths are not compressed, so that every merge only
+changes the root of one set and can be undone. The sets are merged by rank,
+which keeps the trees of depth O(log n) and `find_set` in O(log n).
+
+`snapshot` returns the current point in the history of merges, and
+`rollback` undoes every merge done since a snapshot, the most recent first.
+This is what offline algorithms need when they explore a tree of states with a
+DFS, such as `DynamicConnectivity`.
+
+Each set also knows its size and an aggregate of the values of its members,
+combined with an associative `merge` function (e.g. a sum, a min or a max).
+*/
+
+pub struct RollbackDisjointSetUnion<T> {
+    parent: Vec<usize>,
+    rank: Vec<usize>,
+    size: Vec<usize>,
+    // The aggregate of the values of each set, stored at its root
+    value: Vec<T>,
+    merge: fn(T, T) -> T,
+    num_sets: usize,
+    // For each merge: the root that was attached, the root it was attached
+    // to, and the rank and value of the latter before the merge
+    history: Vec<(usize, usize, usize, T)>,
+}
+
+impl RollbackDisjointSetUnion<()> {
+    // Create n+1 sets [0, n], without values
+    pub fn new(n: usize) -> Self {
+        Self::with_values(vec![(); n + 1], |_, _| ())
+    }
+}
+
+impl<T: Clone> RollbackDisjointSetUnion<T> {
+    // Create one set per value, numbered from 0. The aggregate of a set is
+    // obtained by combining the values of its members with `merge`.
+    pub fn with_values(values: Vec<T>, merge: fn(T, T) -> T) -> Self {
+        let n = values.len();
+        RollbackDisjointSetUnion {
+            parent: (0..n).collect(),
+            rank: vec![0; n],
+            size: vec![1; n],
+            value: values,
+            merge,
+            num_sets: n,
+            history: Vec::new(),
+        }
+    }
+    pub fn find_set(&self, mut v: usize) -> usize {
+        while v != self.parent[v] {
+            v = self.parent[v];
+        }
+        v
+    }
+    pub fn same_set(&self, u: usize, v: usize) -> bool {
+        self.find_set(u) == self.find_set(v)
+    }
+    // Returns the new component of the merged sets,
+    // or usize::MAX if they were the same.
+    pub fn merge(&mut self, u: usize, v: usize) -> usize {
+        let mut a = self.find_set(u);
+        let mut b = self.find_set(v);
+        if a == b {
+            return usize::MAX;
+        }
+        if self.rank[a] < self.rank[b] {
+            std::mem::swap(&mut a, &mut b);
+        }
+        self.history
+            .push((b, a, self.rank[a], self.value[a].clone()));
+        self.parent[b] = a;
+        self.size[a] += self.size[b];
+        if self.rank[a] == self.rank[b] {
+            self.rank[a] += 1;
+        }
+        self.value[a] = (self.merge)(self.value[a].clone(), self.value[b].clone());
+        self.num_sets -= 1;
+        a
+    }
+    // The number of elements in the set of v
+    pub fn set_size(&self, v: usize) -> usize {
+        self.size[self.find_set(v)]
+    }
+    // The aggregate of the values in the set of v
+    pub fn set_value(&self, v: usize) -> &T {
+        &self.value[self.find_set(v)]
+    }
+    pub fn num_sets(&self) -> usize {
+        self.num_sets
+    }
+    // The current point in the history, to be given to `rollback`
+    pub fn snapshot(&self) -> usize {
+        self.history.len()
+    }
+    // Undo every merge done since `snapshot` was taken
+    pub fn rollback(&mut self, snapshot: usize) {
+        while self.history.len() > snapshot {
+            let (b, a, rank, value) = self.history.pop().unwrap();
+            self.parent[b] = b;
+            self.size[a] -= self.size[b];
+            self.rank[a] = rank;
+            self.value[a] = value;
+            self.num_sets += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::DisjointSetUnion;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn merge_and_rollback() {
+        let mut dsu = RollbackDisjointSetUnion::new(5);
+        assert_eq!(dsu.num_sets(), 6);
+        dsu.merge(1, 2);
+        let snapshot = dsu.snapshot();
+        dsu.merge(3, 4);
+        dsu.merge(2, 4);
+        assert_eq!(dsu.merge(1, 3), usize::MAX);
+        assert!(dsu.same_set(1, 4));
+        assert_eq!(dsu.set_size(3), 4);
+        assert_eq!(dsu.num_sets(), 3);
+
+        dsu.rollback(snapshot);
+        assert!(dsu.same_set(1, 2));
+        assert!(!dsu.same_set(1, 4));
+        assert!(!dsu.same_set(3, 4));
+        assert_eq!(dsu.set_size(1), 2);
+        assert_eq!(dsu.set_size(4), 1);
+        assert_eq!(dsu.num_sets(), 5);
+
+        dsu.rollback(0);
+        assert!(!dsu.same_set(1, 2));
+        assert_eq!(dsu.num_sets(), 6);
+    }
+
+    #[test]
+    fn aggregates() {
+        // The minimum and the sum of the values of each set
+        let values: Vec<(i32, i32)> = [5, -2, 7, 3].iter().map(|&x| (x, x)).collect();
+        let mut dsu =
+            RollbackDisjointSetUnion::with_values(values, |a, b| (a.0.min(b.0), a.1 + b.1));
+        dsu.merge(0, 2);
+        assert_eq!(dsu.set_value(2), &(5, 12));
+        let snapshot = dsu.snapshot();
+        dsu.merge(1, 3);
+        dsu.merge(3, 0);
+        assert_eq!(dsu.set_value(0), &(-2, 13));
+        dsu.rollback(snapshot);
+        assert_eq!(dsu.set_value(0), &(5, 12));
+        assert_eq!(dsu.set_value(1), &(-2, -2));
+        assert_eq!(dsu.set_value(3), &(3, 3));
+    }
+
+    #[test]
+    fn random_operations_match_rebuilt_dsu() {
+        let mut rng = StdRng::seed_from_u64(9);
+        let n = 30;
+        let mut dsu = RollbackDisjointSetUnion::with_values((0..=n).collect(), |a, b| a + b);
+        // The merges that are still applied, and the snapshots taken
+        let mut merges: Vec<(usize, usize)> = Vec::new();
+        let mut snapshots: Vec<(usize, usize)> = Vec::new();
+        for _ in 0..2000 {
+            match rng.gen_range(0..10) {
+                0 => snapshots.push((dsu.snapshot(), merges.len())),
+                1 if !snapshots.is_empty() => {
+                    let (snapshot, len) = snapshots.pop().unwrap();
+                    dsu.rollback(snapshot);
+                    merges.truncate(len);
+                }
+                _ => {
+                    let (u, v) = (rng.gen_range(0..=n), rng.gen_range(0..=n));
+                    dsu.merge(u, v);
+                    merges.push((u, v));
+                }
+            }
+            let mut expected = DisjointSetUnion::new(n);
+            for &(u, v) in &merges {
+                expected.merge(u, v);
+            }
+            let (u, v) = (rng.gen_range(0..=n), rng.gen_range(0..=n));
+            assert_eq!(
+                dsu.same_set(u, v),
+                expected.find_set(u) == expected.find_set(v)
+            );
+            let members: Vec<usize> = (0..=n)
+                .filter(|&w| expected.find_set(w) == expected.find_set(u))
+                .collect();
+            assert_eq!(dsu.set_size(u), members.len());
+            assert_eq!(*dsu.set_value(u), members.iter().sum::<usize>());
+        }
+    }
+}
@@ -0,0 +1,183 @@
+/*
+Stoer-Wagner global minimum cut:
+Finds a way to split the vertices of an undirected weighted graph into two
+non-empty sides, such that the total weight of the edges between the two sides
+is as small as possible. Unlike an s-t cut, no source or sink is given.
+
+The algorithm runs n - 1 phases. A phase grows a set A from an arbitrary
+vertex, always adding the vertex that is the most tightly connected to A. If s
+and t are the last two vertices added, the weight between t and the rest of the
+graph is a minimum s-t cut ("cut of the phase"). Then s and t are merged, as
+the minimum cut either separates them (and was just found) or doesn't.
+
+Complexity: O(V^3) with an adjacency matrix. Edge weights must not be negative.
+*/
+
+use std::collections::BTreeMap;
+use std::ops::Add;
+
+use super::WeightedGraph;
+
+/// Returns the weight of a global minimum cut of `graph`, and the vertices
+/// on one side of it, or `None` if the graph has fewer than two vertices.
+/// The graph is undirected: every edge must be stored in both directions.
+pub fn stoer_wagner<V, E>(graph: &impl WeightedGraph<Vertex = V, Weight = E>) -> Option<(E, Vec<V>)>
+where
+    V: Ord + Clone,
+    E: Ord + Add<Output = E> + Copy + Default,
+{
+    let vertices: Vec<V> = graph.vertices().collect();
+    let n = vertices.len();
+    if n < 2 {
+        return None;
+    }
+    let index: BTreeMap<&V, usize> = vertices.iter().enumerate().map(|(i, v)| (v, i)).collect();
+    let mut weight = vec![vec![E::default(); n]; n];
+    for (u, v, w) in graph.edges() {
+        let (u, v) = (index[&u], index[&v]);
+        if u != v {
+            weight[u][v] = weight[u][v] + w;
+        }
+    }
+
+    // The original vertices that were merged into each vertex
+    let mut merged: Vec<Vec<usize>> = (0..n).map(|v| vec![v]).collect();
+    let mut active: Vec<usize> = (0..n).collect();
+    let mut best: Option<(E, Vec<usize>)> = None;
+
+    while active.len() > 1 {
+        // How tightly each vertex is connected to the set A
+        let mut connection = vec![E::default(); n];
+        let mut in_a = vec![false; n];
+        let (mut s, mut t) = (active[0], active[0]);
+        for _ in 0..active.len() {
+            let next = *active
+                .iter()
+                .filter(|&&v| !in_a[v])
+                .max_by_key(|&&v| connection[v])
+                .unwrap();
+            in_a[next] = true;
+            (s, t) = (t, next);
+            for &v in &active {
+                connection[v] = connection[v] + weight[next][v];
+            }
+        }
+
+        if best.as_ref().is_none_or(|(w, _)| connection[t] < *w) {
+            best = Some((connection[t], merged[t].clone()));
+        }
+
+        // Merge t into s
+        let moved = std::mem::take(&mut merged[t]);
+        merged[s].extend(moved);
+        for &v in &active {
+            weight[s][v] = weight[s][v] + weight[t][v];
+            weight[v][s] = weight[s][v];
+        }
+        weight[s][s] = E::default();
+        active.retain(|&v| v != t);
+    }
+
+    best.map(|(w, side)| {
+        let mut side: Vec<V> = side.into_iter().map(|v| vertices[v].clone()).collect();
+        side.sort();
+        (w, side)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    type Graph<V, E> = BTreeMap<V, BTreeMap<V, E>>;
+
+    fn add_edge<V: Ord + Copy, E: Copy>(graph: &mut Graph<V, E>, v1: V, v2: V, c: E) {
+        graph.entry(v1).or_default().insert(v2, c);
+        graph.entry(v2).or_default().insert(v1, c);
+    }
+
+    // The weight of the edges leaving `side`
+    fn cut_weight(graph: &Graph<usize, u32>, side: &[usize]) -> u32 {
+        graph
+            .edges()
+            .filter(|(u, v, _)| side.contains(u) && !side.contains(v))
+            .map(|(_, _, w)| w)
+            .sum()
+    }
+
+    #[test]
+    fn paper_example() {
+        // The example of Stoer and Wagner's paper
+        let mut graph = Graph::new();
+        for (u, v, w) in [
+            (1, 2, 2),
+            (1, 5, 3),
+            (2, 3, 3),
+            (2, 5, 2),
+            (2, 6, 2),
+            (3, 4, 4),
+            (3, 7, 2),
+            (4, 7, 2),
+            (4, 8, 2),
+            (5, 6, 3),
+            (6, 7, 1),
+            (7, 8, 3),
+        ] {
+            add_edge(&mut graph, u, v, w);
+        }
+        let (weight, mut side) = stoer_wagner(&graph).unwrap();
+        assert_eq!(weight, 4);
+        if side.contains(&1) {
+            side = (1..=8).filter(|v| !side.contains(v)).collect();
+        }
+        assert_eq!(side, vec![3, 4, 7, 8]);
+    }
+
+    #[test]
+    fn small_graphs() {
+        let mut graph: Graph<char, u32> = Graph::new();
+        assert_eq!(stoer_wagner(&graph), None);
+        graph.insert('a', BTreeMap::new());
+        assert_eq!(stoer_wagner(&graph), None);
+
+        // A disconnected graph can be cut for free
+        graph.insert('b', BTreeMap::new());
+        add_edge(&mut graph, 'c', 'd', 7);
+        add_edge(&mut graph, 'b', 'c', 1);
+        let (weight, side) = stoer_wagner(&graph).unwrap();
+        assert_eq!(weight, 0);
+        assert!(side == vec!['a'] || side == vec!['b', 'c', 'd']);
+    }
+
+    #[test]
+    fn random_graphs_match_brute_force() {
+        let mut rng = StdRng::seed_from_u64(3);
+        for _ in 0..100 {
+            let n = rng.gen_range(2..=9);
+            let mut graph: Graph<usize, u32> = (0..n).map(|v| (v, BTreeMap::new())).collect();
+            for _ in 0..rng.gen_range(0..=n * 2) {
+                let (u, v) = (rng.gen_range(0..n), rng.gen_range(0..n));
+                if u != v {
+                    add_edge(&mut graph, u, v, rng.gen_range(1..=10));
+                }
+            }
+            // Every non-empty proper subset containing vertex 0
+            let expected = (1..1u32 << (n - 1))
+                .map(|mask| {
+                    let side: Vec<usize> = (0..n)
+                        .filter(|&v| v == 0 || mask & (1 << (v - 1)) == 0)
+                        .collect();
+                    cut_weight(&graph, &side)
+                })
+                .min()
+                .unwrap();
+
+            let (weight, side) = stoer_wagner(&graph).unwrap();
+            assert_eq!(weight, expected);
+            assert!(!side.is_empty() && side.len() < n);
+            assert_eq!(cut_weight(&graph, &side), weight);
+        }
+    }
+}
@@ -6,8 +6,11 @@ components in some (reverse) topologically sorted order.
 We assume that graph is represented using (compressed) adjacency matrix
 and its vertices are numbered from 1 to n. If this is not the case, one
 can use `src/graph/graph_enumeration.rs` to convert their graph.
+Any `WeightedGraph` over `usize` vertices can be used, e.g. `Vec<Vec<usize>>`.
 */
 
+use super::WeightedGraph;
+
 pub struct StronglyConnectedComponents {
     // The number of the SCC the vertex is in, starting from 1
     pub component: Vec<usize>,
@@ -61,14 +64,14 @@ impl StronglyConnectedComponents {
             current_time: 1,
         }
     }
-    fn dfs(&mut self, v: usize, adj: &[Vec<usize>]) -> u64 {
+    fn dfs<G: WeightedGraph<Vertex = usize>>(&mut self, v: usize, adj: &G) -> u64 {
         let mut min_disc = self.current_time as u64;
         // self.state[v] = NOT_DONE + min_disc
         self.state[v] ^= min_disc;
         self.current_time += 1;
         self.stack.push(v);
 
-        for &u in adj[v].iter() {
+        for (u, _) in adj.neighbors(v) {
             if is_unvisited(self.state[u]) {
                 min_disc = std::cmp::min(self.dfs(u, adj), min_disc);
             } else if is_in_stack(self.state[u]) {
@@ -92,9 +95,9 @@ impl StronglyConnectedComponents {
 
         min_disc
     }
-    pub fn find_components(&mut self, adj: &[Vec<usize>]) {
+    pub fn find_components<G: WeightedGraph<Vertex = usize>>(&mut self, adj: &G) {
         self.state[0] = 0;
-        for v in 1..adj.len() {
+        for v in adj.vertices() {
             if is_unvisited(self.state[v]) {
                 self.dfs(v, adj);
             }
@@ -1,3 +1,6 @@
+use super::weighted_graph::index_graph;
+use super::WeightedGraph;
+
 pub struct Graph {
     n: usize,
     adj_list: Vec<Vec<usize>>,
@@ -15,7 +18,32 @@ impl Graph {
         self.adj_list[u].push(v);
     }
 }
-pub fn tarjan_scc(graph: &Graph) -> Vec<Vec<usize>> {
+impl WeightedGraph for Graph {
+    type Vertex = usize;
+    type Weight = ();
+
+    fn vertices(&self) -> impl Iterator<Item = usize> + '_ {
+        0..self.n
+    }
+
+    fn neighbors(&self, vertex: usize) -> impl Iterator<Item = (usize, ())> + '_ {
+        self.adj_list[vertex].iter().map(|&v| (v, ()))
+    }
+
+    fn num_vertices(&self) -> usize {
+        self.n
+    }
+}
+
+pub fn tarjan_scc<V: Ord + Copy>(graph: &impl WeightedGraph<Vertex = V>) -> Vec<Vec<V>> {
+    let (vertices, adj) = index_graph(graph);
+    tarjan_scc_indexed(&adj)
+        .into_iter()
+        .map(|component| component.into_iter().map(|v| vertices[v]).collect())
+        .collect()
+}
+
+fn tarjan_scc_indexed(adj: &[Vec<usize>]) -> Vec<Vec<usize>> {
     struct TarjanState {
         index: i32,
         stack: Vec<usize>,
@@ -28,22 +56,22 @@ pub fn tarjan_scc(graph: &Graph) -> Vec<Vec<usize>> {
     let mut state = TarjanState {
         index: 0,
         stack: Vec::new(),
-        on_stack: vec![false; graph.n],
-        index_of: vec![-1; graph.n],
-        lowlink_of: vec![-1; graph.n],
+        on_stack: vec![false; adj.len()],
+        index_of: vec![-1; adj.len()],
+        lowlink_of: vec![-1; adj.len()],
         components: Vec::new(),
     };
 
-    fn strong_connect(v: usize, graph: &Graph, state: &mut TarjanState) {
+    fn strong_connect(v: usize, adj: &[Vec<usize>], state: &mut TarjanState) {
         state.index_of[v] = state.index;
         state.lowlink_of[v] = state.index;
         state.index += 1;
         state.stack.push(v);
         state.on_stack[v] = true;
 
-        for &w in &graph.adj_list[v] {
+        for &w in &adj[v] {
             if state.index_of[w] == -1 {
-                strong_connect(w, graph, state);
+                strong_connect(w, adj, state);
                 state.lowlink_of[v] = state.lowlink_of[v].min(state.lowlink_of[w]);
             } else if state.on_stack[w] {
                 state.lowlink_of[v] = state.lowlink_of[v].min(state.index_of[w]);
@@ -63,9 +91,9 @@ pub fn tarjan_scc(graph: &Graph) -> Vec<Vec<usize>> {
         }
     }
 
-    for v in 0..graph.n {
+    for v in 0..adj.len() {
         if state.index_of[v] == -1 {
-            strong_connect(v, graph, &mut state);
+            strong_connect(v, adj, &mut state);
         }
     }
 
@@ -1,13 +1,19 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
 use std::collections::HashMap;
 use std::collections::VecDeque;
 use std::hash::Hash;
 
+use super::{CsrGraph, WeightedGraph};
+
 #[derive(Debug, Eq, PartialEq)]
-pub enum TopoligicalSortError {
-    CycleDetected,
+pub enum TopoligicalSortError<Node> {
+    // The vertices of a cycle, in the order of its edges: cycle[i] -> cycle[i + 1],
+    // and the last vertex goes back to the first one
+    CycleDetected(Vec<Node>),
 }
 
-type TopologicalSortResult<Node> = Result<Vec<Node>, TopoligicalSortError>;
+type TopologicalSortResult<Node> = Result<Vec<Node>, TopoligicalSortError<Node>>;
 
 /// Given a directed graph, modeled as a list of edges from source to destination
 /// Uses Kahn's algorithm to either:
@@ -30,7 +36,76 @@ pub fn topological_sort<Node: Hash + Eq + Copy>(
         // then make destination have one more incoming edge
         *incoming_edges_count.entry(*destination).or_insert(0) += 1;
     }
+    kahn(incoming_edges_count, |node| {
+        edges_by_source.get(&node).into_iter().flatten().copied()
+    })
+}
+
+/// Same as `topological_sort`, for a directed graph given as any `WeightedGraph`.
+/// Unlike the edge list version, vertices without any edge are part of the result.
+///
+/// Only the in-degrees are stored, in a map, and the successors are read from
+/// the graph. `topological_sort_csr` keeps them in a `Vec` instead.
+pub fn topological_sort_graph<Node: Hash + Eq + Copy>(
+    graph: &impl WeightedGraph<Vertex = Node>,
+) -> TopologicalSortResult<Node> {
+    let mut incoming_edges_count: HashMap<Node, usize> =
+        graph.vertices().map(|node| (node, 0)).collect();
+    for (_, destination, _) in graph.edges() {
+        *incoming_edges_count.entry(destination).or_insert(0) += 1;
+    }
+    kahn(incoming_edges_count, |node| {
+        graph.neighbors(node).map(|(destination, _)| destination)
+    })
+}
+
+/// Same as `topological_sort_graph` for a `CsrGraph`, whose vertices are
+/// `0..n`: the in-degrees are kept in a `Vec`, so the only memory used besides
+/// the graph is O(V).
+pub fn topological_sort_csr<E: Clone>(graph: &CsrGraph<E>) -> TopologicalSortResult<usize> {
+    let n = graph.num_vertices();
+    let mut incoming_edges_count = vec![0; n];
+    for node in 0..n {
+        for &destination in graph.targets(node) {
+            incoming_edges_count[destination] += 1;
+        }
+    }
+    let mut no_incoming_edges_q: VecDeque<usize> = (0..n)
+        .filter(|&node| incoming_edges_count[node] == 0)
+        .collect();
+    let mut sorted = Vec::with_capacity(n);
+    while let Some(node) = no_incoming_edges_q.pop_back() {
+        sorted.push(node);
+        for &neighbour in graph.targets(node) {
+            incoming_edges_count[neighbour] -= 1;
+            if incoming_edges_count[neighbour] == 0 {
+                no_incoming_edges_q.push_front(neighbour);
+            }
+        }
+    }
+    if sorted.len() == n {
+        return Ok(sorted);
+    }
+    // the unsorted nodes are the ones with incoming edges left
+    let remaining: HashMap<usize, usize> = incoming_edges_count
+        .into_iter()
+        .enumerate()
+        .filter(|&(_, count)| count > 0)
+        .collect();
+    Err(TopoligicalSortError::CycleDetected(find_cycle(
+        &remaining,
+        |node| graph.targets(node).iter().copied(),
+    )))
+}
 
+fn kahn<Node, I>(
+    mut incoming_edges_count: HashMap<Node, usize>,
+    successors: impl Fn(Node) -> I,
+) -> TopologicalSortResult<Node>
+where
+    Node: Hash + Eq + Copy,
+    I: IntoIterator<Item = Node>,
+{
     // Now Kahn's algorithm:
     // Add nodes that have no incoming edges to a queue
     let mut no_incoming_edges_q = VecDeque::default();
@@ -45,13 +120,13 @@ pub fn topological_sort<Node: Hash + Eq + Copy>(
         sorted.push(no_incoming_edges); // since the node has no dependency, it can be safely pushed to the sorted result
         incoming_edges_count.remove(&no_incoming_edges);
         // For each node having this one as dependency
-        for neighbour in edges_by_source.get(&no_incoming_edges).unwrap_or(&vec![]) {
-            if let Some(count) = incoming_edges_count.get_mut(neighbour) {
+        for neighbour in successors(no_incoming_edges) {
+            if let Some(count) = incoming_edges_count.get_mut(&neighbour) {
                 *count -= 1; // decrement the count of incoming edges for the dependent node
                 if *count == 0 {
                     // `node` was the last node `neighbour` was dependent on
-                    incoming_edges_count.remove(neighbour); // let's remove it from the map, so that we can know if we covered the whole graph
-                    no_incoming_edges_q.push_front(*neighbour); // it has no incoming edges anymore => push it to the queue
+                    incoming_edges_count.remove(&neighbour); // let's remove it from the map, so that we can know if we covered the whole graph
+                    no_incoming_edges_q.push_front(neighbour); // it has no incoming edges anymore => push it to the queue
                 }
             }
         }
@@ -61,14 +136,233 @@ pub fn topological_sort<Node: Hash + Eq + Copy>(
         Ok(sorted)
     } else {
         // some nodes haven't been visited, meaning there's a cycle in the graph
-        Err(TopoligicalSortError::CycleDetected)
+        Err(TopoligicalSortError::CycleDetected(find_cycle(
+            &incoming_edges_count,
+            successors,
+        )))
+    }
+}
+
+/// Same as `topological_sort`, but returns the lexicographically smallest order:
+/// among the nodes without remaining dependencies, the smallest one always comes first.
+pub fn lexicographic_topological_sort<Node: Hash + Ord + Copy>(
+    edges: &[(Node, Node)],
+) -> TopologicalSortResult<Node> {
+    let mut edges_by_source: HashMap<Node, Vec<Node>> = HashMap::default();
+    let mut incoming_edges_count: HashMap<Node, usize> = HashMap::default();
+    for &(source, destination) in edges {
+        incoming_edges_count.entry(source).or_insert(0);
+        edges_by_source.entry(source).or_default().push(destination);
+        *incoming_edges_count.entry(destination).or_insert(0) += 1;
+    }
+    // Kahn's algorithm with a min-heap instead of a queue
+    let mut no_incoming_edges: BinaryHeap<Reverse<Node>> = incoming_edges_count
+        .iter()
+        .filter(|&(_, &count)| count == 0)
+        .map(|(&node, _)| Reverse(node))
+        .collect();
+    let mut sorted = Vec::default();
+    while let Some(Reverse(node)) = no_incoming_edges.pop() {
+        sorted.push(node);
+        incoming_edges_count.remove(&node);
+        for neighbour in edges_by_source.get(&node).unwrap_or(&vec![]) {
+            if let Some(count) = incoming_edges_count.get_mut(neighbour) {
+                *count -= 1;
+                if *count == 0 {
+                    incoming_edges_count.remove(neighbour);
+                    no_incoming_edges.push(Reverse(*neighbour));
+                }
+            }
+        }
+    }
+    if incoming_edges_count.is_empty() {
+        Ok(sorted)
+    } else {
+        Err(TopoligicalSortError::CycleDetected(find_cycle(
+            &incoming_edges_count,
+            |node| edges_by_source.get(&node).into_iter().flatten().copied(),
+        )))
+    }
+}
+
+// Finds a cycle among the nodes Kahn's algorithm couldn't sort. Each of them still
+// has an incoming edge from another one, so following these edges backwards
+// eventually comes back to a node already seen.
+fn find_cycle<Node, I>(
+    remaining: &HashMap<Node, usize>,
+    successors: impl Fn(Node) -> I,
+) -> Vec<Node>
+where
+    Node: Hash + Eq + Copy,
+    I: IntoIterator<Item = Node>,
+{
+    let mut predecessor: HashMap<Node, Node> = HashMap::default();
+    for &source in remaining.keys() {
+        for destination in successors(source) {
+            if remaining.contains_key(&destination) {
+                predecessor.insert(destination, source);
+            }
+        }
+    }
+    let mut node = *remaining.keys().next().unwrap();
+    let mut seen: HashMap<Node, usize> = HashMap::default();
+    let mut path = vec![];
+    while !seen.contains_key(&node) {
+        seen.insert(node, path.len());
+        path.push(node);
+        node = predecessor[&node];
+    }
+    let mut cycle = path.split_off(seen[&node]);
+    // the path was built following the edges backwards
+    cycle.reverse();
+    cycle.rotate_right(1);
+    cycle
+}
+
+/// Maintains a topological order of a directed acyclic graph while edges are
+/// added one at a time (Pearce and Kelly's algorithm).
+///
+/// When an edge u -> v goes against the current order, only the nodes placed
+/// between v and u can have to move: those reachable from v and those that
+/// reach u. They are found with two bounded DFSs, and take the same positions
+/// again, in a valid order. An edge that would close a cycle is rejected.
+pub struct IncrementalTopologicalSort<Node> {
+    index: HashMap<Node, usize>,
+    nodes: Vec<Node>,
+    successors: Vec<Vec<usize>>,
+    predecessors: Vec<Vec<usize>>,
+    // position[v] is the place of v in the order, and order[p] the node at place p
+    position: Vec<usize>,
+    order: Vec<usize>,
+}
+
+impl<Node: Hash + Eq + Copy> Default for IncrementalTopologicalSort<Node> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<Node: Hash + Eq + Copy> IncrementalTopologicalSort<Node> {
+    pub fn new() -> Self {
+        IncrementalTopologicalSort {
+            index: HashMap::default(),
+            nodes: vec![],
+            successors: vec![],
+            predecessors: vec![],
+            position: vec![],
+            order: vec![],
+        }
+    }
+
+    /// Adds a node at the end of the order, if it isn't in the graph yet
+    pub fn add_node(&mut self, node: Node) -> usize {
+        if let Some(&v) = self.index.get(&node) {
+            return v;
+        }
+        let v = self.nodes.len();
+        self.index.insert(node, v);
+        self.nodes.push(node);
+        self.successors.push(vec![]);
+        self.predecessors.push(vec![]);
+        self.position.push(v);
+        self.order.push(v);
+        v
+    }
+
+    /// Adds the edge `source -> destination`, and the nodes if needed. If the
+    /// edge would close a cycle, the graph is left unchanged and the cycle is
+    /// returned, starting with `source`.
+    pub fn add_edge(
+        &mut self,
+        source: Node,
+        destination: Node,
+    ) -> Result<(), TopoligicalSortError<Node>> {
+        if source == destination {
+            return Err(TopoligicalSortError::CycleDetected(vec![source]));
+        }
+        let u = self.add_node(source);
+        let v = self.add_node(destination);
+        let (lower, upper) = (self.position[v], self.position[u]);
+        if lower < upper {
+            // the nodes reachable from v that are placed before u
+            let mut forward = vec![];
+            let mut parent = vec![None; self.nodes.len()];
+            let mut stack = vec![v];
+            parent[v] = Some(v);
+            while let Some(x) = stack.pop() {
+                forward.push(x);
+                for &y in &self.successors[x] {
+                    if y == u {
+                        // v reaches u: report the cycle u -> v -> ... -> x -> u
+                        let mut cycle = vec![x];
+                        let mut z = x;
+                        while z != v {
+                            z = parent[z].unwrap();
+                            cycle.push(z);
+                        }
+                        cycle.push(u);
+                        cycle.reverse();
+                        let cycle = cycle.into_iter().map(|z| self.nodes[z]).collect();
+                        return Err(TopoligicalSortError::CycleDetected(cycle));
+                    }
+                    if self.position[y] < upper && parent[y].is_none() {
+                        parent[y] = Some(x);
+                        stack.push(y);
+                    }
+                }
+            }
+            // the nodes that reach u and are placed after v
+            let mut backward = vec![];
+            let mut seen = vec![false; self.nodes.len()];
+            let mut stack = vec![u];
+            seen[u] = true;
+            while let Some(x) = stack.pop() {
+                backward.push(x);
+                for &y in &self.predecessors[x] {
+                    if self.position[y] > lower && !seen[y] {
+                        seen[y] = true;
+                        stack.push(y);
+                    }
+                }
+            }
+            // both keep their relative order, and backward now comes before forward
+            let by_position = |a: &usize, b: &usize| self.position[*a].cmp(&self.position[*b]);
+            forward.sort_by(by_position);
+            backward.sort_by(by_position);
+            let mut places: Vec<usize> = forward
+                .iter()
+                .chain(&backward)
+                .map(|&x| self.position[x])
+                .collect();
+            places.sort_unstable();
+            for (x, place) in backward.into_iter().chain(forward).zip(places) {
+                self.position[x] = place;
+                self.order[place] = x;
+            }
+        }
+        self.successors[u].push(v);
+        self.predecessors[v].push(u);
+        Ok(())
+    }
+
+    /// The nodes in a topological order of the graph built so far
+    pub fn order(&self) -> Vec<Node> {
+        self.order.iter().map(|&v| self.nodes[v]).collect()
+    }
+
+    /// Whether `a` comes before `b` in the current order
+    pub fn comes_before(&self, a: Node, b: Node) -> Option<bool> {
+        let (a, b) = (self.index.get(&a)?, self.index.get(&b)?);
+        Some(self.position[*a] < self.position[*b])
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::topological_sort;
-    use crate::graph::topological_sort::TopoligicalSortError;
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    use std::collections::BTreeMap;
 
     fn is_valid_sort<Node: Eq>(sorted: &[Node], graph: &[(Node, Node)]) -> bool {
         for (source, dest) in graph {
@@ -118,6 +412,141 @@ mod tests {
         let graph = vec![(1, 2), (2, 3), (3, 4), (4, 5), (4, 2)];
         let sort = topological_sort(&graph);
         assert!(sort.is_err());
-        assert_eq!(sort.err().unwrap(), TopoligicalSortError::CycleDetected);
+        let TopoligicalSortError::CycleDetected(mut cycle) = sort.err().unwrap();
+        let first = cycle.iter().position(|&v| v == 2).unwrap();
+        cycle.rotate_left(first);
+        assert_eq!(cycle, vec![2, 3, 4]);
+    }
+
+    // Whether the nodes form a cycle of the graph, in order
+    fn is_cycle<Node: Eq>(cycle: &[Node], graph: &[(Node, Node)]) -> bool {
+        !cycle.is_empty()
+            && (0..cycle.len()).all(|i| {
+                let edge = (&cycle[i], &cycle[(i + 1) % cycle.len()]);
+                graph.iter().any(|(u, v)| (u, v) == edge)
+            })
+    }
+
+    #[test]
+    fn lexicographic_order() {
+        let graph = vec![
+            (5, 11),
+            (7, 11),
+            (7, 8),
+            (3, 8),
+            (3, 10),
+            (11, 2),
+            (11, 9),
+            (11, 10),
+            (8, 9),
+        ];
+        assert_eq!(
+            lexicographic_topological_sort(&graph),
+            Ok(vec![3, 5, 7, 8, 11, 2, 9, 10])
+        );
+        let graph = vec![('b', 'a'), ('c', 'a'), ('a', 'd'), ('d', 'c')];
+        let TopoligicalSortError::CycleDetected(cycle) =
+            lexicographic_topological_sort(&graph).unwrap_err();
+        assert!(is_cycle(&cycle, &graph));
+        assert_eq!(cycle.len(), 3);
+    }
+
+    #[test]
+    fn incremental_order() {
+        let mut sort = IncrementalTopologicalSort::new();
+        sort.add_node("lib");
+        assert_eq!(sort.add_edge("app", "lib"), Ok(()));
+        assert_eq!(sort.order(), vec!["app", "lib"]);
+        sort.add_edge("lib", "core").unwrap();
+        sort.add_edge("core", "alloc").unwrap();
+        sort.add_edge("test", "app").unwrap();
+        assert_eq!(sort.order(), vec!["test", "app", "lib", "core", "alloc"]);
+        assert_eq!(sort.comes_before("app", "alloc"), Some(true));
+        assert_eq!(sort.comes_before("app", "missing"), None);
+
+        // the edge that would close a cycle is rejected
+        assert_eq!(
+            sort.add_edge("alloc", "app"),
+            Err(TopoligicalSortError::CycleDetected(vec![
+                "alloc", "app", "lib", "core"
+            ]))
+        );
+        assert_eq!(
+            sort.add_edge("core", "core"),
+            Err(TopoligicalSortError::CycleDetected(vec!["core"]))
+        );
+        assert_eq!(
+            sort.add_edge("std", "std"),
+            Err(TopoligicalSortError::CycleDetected(vec!["std"]))
+        );
+        assert_eq!(sort.order(), vec!["test", "app", "lib", "core", "alloc"]);
+        assert_eq!(sort.comes_before("std", "app"), None);
+    }
+
+    #[test]
+    fn random_incremental_order() {
+        let mut rng = StdRng::seed_from_u64(19);
+        for _ in 0..50 {
+            let n = rng.gen_range(1..=12);
+            let mut sort = IncrementalTopologicalSort::new();
+            let mut edges = vec![];
+            for _ in 0..3 * n {
+                let (u, v) = (rng.gen_range(0..n), rng.gen_range(0..n));
+                match sort.add_edge(u, v) {
+                    Ok(()) => {
+                        edges.push((u, v));
+                        let order = sort.order();
+                        assert!(is_valid_sort(&order, &edges));
+                        assert_eq!(order.len(), sort.nodes.len());
+                    }
+                    Err(TopoligicalSortError::CycleDetected(cycle)) => {
+                        let mut with_edge = edges.clone();
+                        with_edge.push((u, v));
+                        assert!(is_cycle(&cycle, &with_edge));
+                        assert!(topological_sort(&with_edge).is_err());
+                    }
+                }
+            }
+            // the accepted edges form a DAG, sorted the same way from scratch
+            if let Ok(lexicographic) = lexicographic_topological_sort(&edges) {
+                assert!(is_valid_sort(&lexicographic, &edges));
+                let mut sorted = lexicographic.clone();
+                sorted.sort();
+                sorted.dedup();
+                assert_eq!(sorted.len(), lexicographic.len());
+            }
+        }
+    }
+
+    #[test]
+    fn test_graph_input() {
+        let edges = [
+            (5, 11),
+            (7, 11),
+            (7, 8),
+            (3, 8),
+            (3, 10),
+            (11, 2),
+            (11, 9),
+            (8, 9),
+        ];
+        let mut graph: BTreeMap<i32, Vec<i32>> = BTreeMap::new();
+        for &(source, dest) in &edges {
+            graph.entry(source).or_default().push(dest);
+            graph.entry(dest).or_default();
+        }
+        graph.insert(42, vec![]);
+        let sort = topological_sort_graph(&graph).unwrap();
+        assert_eq!(sort.len(), 9);
+        assert!(sort.contains(&42));
+        assert!(is_valid_sort(&sort, &edges));
+
+        graph.entry(9).or_default().push(7);
+        let TopoligicalSortError::CycleDetected(mut cycle) =
+            topological_sort_graph(&graph).unwrap_err();
+        let first = cycle.iter().position(|&v| v == 7).unwrap();
+        cycle.rotate_left(first);
+        // 9 -> 7 closes two cycles
+        assert!(cycle == vec![7, 8, 9] || cycle == vec![7, 11, 9]);
     }
 }
@@ -0,0 +1,401 @@
+/*
+Tree utilities:
+Algorithms on unrooted trees, given like in `prufer_code` as a map from every
+vertex to its neighbours, with each edge in both directions.
+
+- The diameter is the longest path. A BFS from any vertex ends on one end of a
+  diameter, and a second BFS from there finds the other end.
+- The center is the middle of the diameter: one or two vertices minimizing the
+  largest distance to another vertex.
+- The centroid is one or two vertices whose removal leaves components of at
+  most n / 2 vertices.
+- Rerooting computes a subtree DP for every possible root with two passes: the
+  first one computes the DP of every subtree for an arbitrary root, the second
+  one the DP of the "upper" part of the tree above every vertex.
+- AHU (Aho, Hopcroft and Ullman) gives every rooted tree a canonical name built
+  from the sorted names of the subtrees of its root, so two rooted trees are
+  isomorphic when their names are equal. An unrooted tree is named from its
+  center, as every isomorphism maps a center onto a center.
+
+Complexity: O(n log n), except `rooted_canonical_form` whose strings have a
+total length of O(n * height).
+*/
+
+use std::collections::BTreeMap;
+
+use super::prufer_code::Graph;
+
+// The vertices in BFS order from root, and the parent of every other vertex
+fn bfs_order<V: Ord + Copy>(tree: &Graph<V>, root: V) -> (Vec<V>, BTreeMap<V, V>) {
+    let mut order = vec![root];
+    let mut parent = BTreeMap::new();
+    let mut i = 0;
+    while i < order.len() {
+        let v = order[i];
+        for &u in &tree[&v] {
+            if u != root && !parent.contains_key(&u) {
+                parent.insert(u, v);
+                order.push(u);
+            }
+        }
+        i += 1;
+    }
+    (order, parent)
+}
+
+/// Returns the number of edges of a longest path of the tree, and its vertices.
+/// An empty tree gives an empty path.
+pub fn tree_diameter<V: Ord + Copy>(tree: &Graph<V>) -> (usize, Vec<V>) {
+    let Some(&start) = tree.keys().next() else {
+        return (0, vec![]);
+    };
+    let (order, _) = bfs_order(tree, start);
+    let end = *order.last().unwrap();
+    let (order, parent) = bfs_order(tree, end);
+    let mut path = vec![*order.last().unwrap()];
+    while let Some(&v) = parent.get(path.last().unwrap()) {
+        path.push(v);
+    }
+    (path.len() - 1, path)
+}
+
+/// Returns the center of the tree: the vertices whose largest distance to
+/// another vertex is the smallest. There are one or two of them, in increasing
+/// order, and none for an empty tree.
+pub fn tree_center<V: Ord + Copy>(tree: &Graph<V>) -> Vec<V> {
+    let (length, path) = tree_diameter(tree);
+    if path.is_empty() {
+        return vec![];
+    }
+    let mut center = vec![path[length / 2], path[length.div_ceil(2)]];
+    center.sort();
+    center.dedup();
+    center
+}
+
+/// Returns the centroid of the tree: the vertices whose removal leaves
+/// components of at most half of the vertices. There are one or two of them,
+/// in increasing order, and none for an empty tree.
+pub fn tree_centroid<V: Ord + Copy>(tree: &Graph<V>) -> Vec<V> {
+    let Some(&root) = tree.keys().next() else {
+        return vec![];
+    };
+    let (order, parent) = bfs_order(tree, root);
+    let n = order.len();
+    let mut size: BTreeMap<V, usize> = order.iter().map(|&v| (v, 1)).collect();
+    for v in order.iter().skip(1).rev() {
+        *size.get_mut(&parent[v]).unwrap() += size[v];
+    }
+    let mut centroid: Vec<V> = order
+        .iter()
+        .copied()
+        .filter(|v| {
+            let largest_child = tree[v]
+                .iter()
+                .filter(|&u| parent.get(u) == Some(v))
+                .map(|u| size[u])
+                .max()
+                .unwrap_or(0);
+            2 * largest_child.max(n - size[v]) <= n
+        })
+        .collect();
+    centroid.sort();
+    centroid
+}
+
+/// Computes a subtree DP for every root of the tree at once.
+///
+/// The value of a tree rooted at v is `attach(children, v)`, where `children`
+/// merges the values of the subtrees of the children of v with `merge`,
+/// starting from `identity`. `merge` must be associative and commutative.
+/// Returns the value of the whole tree rooted at each vertex.
+pub fn reroot<V, T>(
+    tree: &Graph<V>,
+    identity: T,
+    merge: impl Fn(&T, &T) -> T,
+    attach: impl Fn(&T, V) -> T,
+) -> BTreeMap<V, T>
+where
+    V: Ord + Copy,
+    T: Clone,
+{
+    let Some(&root) = tree.keys().next() else {
+        return BTreeMap::new();
+    };
+    let (order, parent) = bfs_order(tree, root);
+    let parent = &parent;
+    let children = |v: V| {
+        tree[&v]
+            .iter()
+            .copied()
+            .filter(move |u| parent.get(u) == Some(&v))
+    };
+
+    // The value of the subtree of every vertex, for the first root
+    let mut down: BTreeMap<V, T> = BTreeMap::new();
+    for &v in order.iter().rev() {
+        let merged = children(v).fold(identity.clone(), |acc, u| merge(&acc, &down[&u]));
+        down.insert(v, attach(&merged, v));
+    }
+
+    // The value of the part of the tree above every vertex, rooted at its parent
+    let mut up: BTreeMap<V, T> = BTreeMap::new();
+    let mut result = BTreeMap::new();
+    for &v in &order {
+        let neighbours: Vec<V> = children(v).collect();
+        let above = up.remove(&v);
+        let mut values: Vec<&T> = neighbours.iter().map(|u| &down[u]).collect();
+        values.extend(&above);
+        // prefix[i] merges values[..i], suffix[i] merges values[i..]
+        let mut prefix = vec![identity.clone()];
+        for value in &values {
+            prefix.push(merge(prefix.last().unwrap(), value));
+        }
+        let mut suffix = vec![identity.clone(); values.len() + 1];
+        for i in (0..values.len()).rev() {
+            suffix[i] = merge(values[i], &suffix[i + 1]);
+        }
+        for (i, &u) in neighbours.iter().enumerate() {
+            up.insert(u, attach(&merge(&prefix[i], &suffix[i + 1]), v));
+        }
+        result.insert(v, attach(&prefix[values.len()], v));
+    }
+    result
+}
+
+/// Returns the AHU canonical form of the tree rooted at `root`, as balanced
+/// parentheses: two rooted trees are isomorphic if and only if they have the
+/// same form.
+pub fn rooted_canonical_form<V: Ord + Copy>(tree: &Graph<V>, root: V) -> String {
+    let (order, parent) = bfs_order(tree, root);
+    let mut forms: BTreeMap<V, Vec<String>> = BTreeMap::new();
+    let mut root_form = String::new();
+    for &v in order.iter().rev() {
+        let mut children = forms.remove(&v).unwrap_or_default();
+        children.sort();
+        let form = format!("({})", children.concat());
+        match parent.get(&v) {
+            Some(p) => forms.entry(*p).or_default().push(form),
+            None => root_form = form,
+        }
+    }
+    root_form
+}
+
+/// Returns a canonical form of the unrooted tree: two trees are isomorphic if
+/// and only if they have the same form. An empty tree gives an empty string.
+pub fn canonical_form<V: Ord + Copy>(tree: &Graph<V>) -> String {
+    tree_center(tree)
+        .into_iter()
+        .map(|c| rooted_canonical_form(tree, c))
+        .min()
+        .unwrap_or_default()
+}
+
+// The AHU name of the tree rooted at root: the name of a subtree is the index
+// of the sorted names of its children in `names`, so equal names mean isomorphic
+// subtrees. `names` is shared between trees to compare them.
+fn ahu_name<V: Ord + Copy>(
+    tree: &Graph<V>,
+    root: V,
+    names: &mut BTreeMap<Vec<usize>, usize>,
+) -> usize {
+    let (order, parent) = bfs_order(tree, root);
+    let mut children: BTreeMap<V, Vec<usize>> = BTreeMap::new();
+    for &v in order.iter().rev() {
+        let mut key = children.remove(&v).unwrap_or_default();
+        key.sort_unstable();
+        let next = names.len();
+        let name = *names.entry(key).or_insert(next);
+        match parent.get(&v) {
+            Some(p) => children.entry(*p).or_default().push(name),
+            None => return name,
+        }
+    }
+    unreachable!()
+}
+
+/// Whether there is an isomorphism between the trees that maps `root1` to `root2`
+pub fn rooted_isomorphic<V: Ord + Copy, U: Ord + Copy>(
+    tree1: &Graph<V>,
+    root1: V,
+    tree2: &Graph<U>,
+    root2: U,
+) -> bool {
+    let mut names = BTreeMap::new();
+    tree1.len() == tree2.len()
+        && ahu_name(tree1, root1, &mut names) == ahu_name(tree2, root2, &mut names)
+}
+
+/// Whether the unrooted trees are isomorphic
+pub fn isomorphic<V: Ord + Copy, U: Ord + Copy>(tree1: &Graph<V>, tree2: &Graph<U>) -> bool {
+    let (center1, center2) = (tree_center(tree1), tree_center(tree2));
+    if tree1.len() != tree2.len() || center1.len() != center2.len() {
+        return false;
+    }
+    match center1.first() {
+        None => true,
+        Some(&c1) => center2
+            .iter()
+            .any(|&c2| rooted_isomorphic(tree1, c1, tree2, c2)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::prufer_decode;
+    use rand::rngs::StdRng;
+    use rand::seq::SliceRandom;
+    use rand::{Rng, SeedableRng};
+
+    fn from_edges(n: usize, edges: &[(usize, usize)]) -> Graph<usize> {
+        let mut tree: Graph<usize> = (0..n).map(|v| (v, vec![])).collect();
+        for &(u, v) in edges {
+            tree.get_mut(&u).unwrap().push(v);
+            tree.get_mut(&v).unwrap().push(u);
+        }
+        tree
+    }
+
+    fn random_tree(rng: &mut StdRng, n: usize) -> Graph<usize> {
+        if n <= 2 {
+            let edges: Vec<_> = (1..n).map(|v| (0, v)).collect();
+            return from_edges(n, &edges);
+        }
+        let code: Vec<usize> = (0..n - 2).map(|_| rng.gen_range(0..n)).collect();
+        prufer_decode(&code, &(0..n).collect::<Vec<_>>())
+    }
+
+    // The distances from v to every vertex
+    fn distances(tree: &Graph<usize>, v: usize) -> BTreeMap<usize, usize> {
+        let (order, parent) = bfs_order(tree, v);
+        let mut dist = BTreeMap::from([(v, 0)]);
+        for u in order.iter().skip(1) {
+            dist.insert(*u, dist[&parent[u]] + 1);
+        }
+        dist
+    }
+
+    #[test]
+    fn diameter_center_centroid() {
+        //   0 - 1 - 2 - 3 - 4
+        //       |
+        //       5 - 6
+        let tree = from_edges(7, &[(0, 1), (1, 2), (2, 3), (3, 4), (1, 5), (5, 6)]);
+        let (length, path) = tree_diameter(&tree);
+        assert_eq!(length, 5);
+        assert!(path == vec![6, 5, 1, 2, 3, 4] || path == vec![4, 3, 2, 1, 5, 6]);
+        assert_eq!(tree_center(&tree), vec![1, 2]);
+        assert_eq!(tree_centroid(&tree), vec![1]);
+
+        let star = from_edges(5, &[(0, 1), (0, 2), (0, 3), (0, 4)]);
+        assert_eq!(tree_diameter(&star).0, 2);
+        assert_eq!(tree_center(&star), vec![0]);
+        assert_eq!(tree_centroid(&star), vec![0]);
+
+        let pair = from_edges(2, &[(0, 1)]);
+        assert_eq!(tree_centroid(&pair), vec![0, 1]);
+        assert_eq!(tree_diameter(&from_edges(1, &[])), (0, vec![0]));
+        assert_eq!(tree_center(&Graph::<u8>::new()), vec![]);
+    }
+
+    #[test]
+    fn sum_of_distances_by_rerooting() {
+        let tree = from_edges(6, &[(0, 1), (0, 2), (2, 3), (2, 4), (2, 5)]);
+        // (number of vertices, sum of their distances to the root)
+        let sums = reroot(
+            &tree,
+            (0, 0),
+            |a: &(usize, usize), b| (a.0 + b.0, a.1 + b.1),
+            |children, _| (children.0 + 1, children.1 + children.0),
+        );
+        let sums: Vec<usize> = sums.values().map(|&(_, sum)| sum).collect();
+        assert_eq!(sums, vec![8, 12, 6, 10, 10, 10]);
+    }
+
+    #[test]
+    fn isomorphisms() {
+        // both are a path 0 - 1 - 2 - 3 with a leaf on 1, labelled differently
+        let a = from_edges(5, &[(0, 1), (1, 2), (2, 3), (1, 4)]);
+        let b = from_edges(5, &[(4, 3), (3, 2), (2, 1), (3, 0)]);
+        let path = from_edges(5, &[(0, 1), (1, 2), (2, 3), (3, 4)]);
+        assert!(isomorphic(&a, &b));
+        assert!(!isomorphic(&a, &path));
+        assert_eq!(canonical_form(&a), canonical_form(&b));
+        assert_ne!(canonical_form(&a), canonical_form(&path));
+        assert!(rooted_isomorphic(&a, 0, &b, 4));
+        assert!(!rooted_isomorphic(&a, 0, &b, 1));
+        assert_eq!(rooted_canonical_form(&a, 4), "(((())()))");
+
+        let mut chars: Graph<char> = BTreeMap::new();
+        for (u, v) in [('a', 'b'), ('b', 'c'), ('c', 'd'), ('b', 'e')] {
+            chars.entry(u).or_default().push(v);
+            chars.entry(v).or_default().push(u);
+        }
+        assert!(isomorphic(&a, &chars));
+    }
+
+    #[test]
+    fn random_trees() {
+        let mut rng = StdRng::seed_from_u64(22);
+        for _ in 0..100 {
+            let n = rng.gen_range(1..=12);
+            let tree = random_tree(&mut rng, n);
+            let all: Vec<BTreeMap<usize, usize>> = (0..n).map(|v| distances(&tree, v)).collect();
+            let eccentricity: Vec<usize> = all.iter().map(|d| *d.values().max().unwrap()).collect();
+
+            let (length, path) = tree_diameter(&tree);
+            assert_eq!(length, *eccentricity.iter().max().unwrap());
+            assert_eq!(all[path[0]][&path[length]], length);
+            let radius = *eccentricity.iter().min().unwrap();
+            let center: Vec<usize> = (0..n).filter(|&v| eccentricity[v] == radius).collect();
+            assert_eq!(tree_center(&tree), center);
+            for c in tree_centroid(&tree) {
+                // removing c leaves components of at most n / 2 vertices
+                for &u in &tree[&c] {
+                    let side = (0..n).filter(|&w| all[u][&w] < all[c][&w]).count();
+                    assert!(2 * side <= n);
+                }
+            }
+
+            let sums = reroot(
+                &tree,
+                (0, 0),
+                |a: &(usize, usize), b| (a.0 + b.0, a.1 + b.1),
+                |children, _| (children.0 + 1, children.1 + children.0),
+            );
+            for v in 0..n {
+                assert_eq!(sums[&v], (n, all[v].values().sum()));
+            }
+
+            // a relabelled copy is isomorphic
+            let mut labels: Vec<usize> = (0..n).collect();
+            labels.shuffle(&mut rng);
+            let relabelled: Graph<usize> = tree
+                .iter()
+                .map(|(v, adj)| (labels[*v], adj.iter().map(|u| labels[*u]).collect()))
+                .collect();
+            assert!(isomorphic(&tree, &relabelled));
+            assert_eq!(canonical_form(&tree), canonical_form(&relabelled));
+            let root = rng.gen_range(0..n);
+            assert!(rooted_isomorphic(&tree, root, &relabelled, labels[root]));
+            assert_eq!(
+                rooted_canonical_form(&tree, root),
+                rooted_canonical_form(&relabelled, labels[root])
+            );
+
+            // the forms agree with the isomorphism test on another tree
+            let other = random_tree(&mut rng, n);
+            assert_eq!(
+                isomorphic(&tree, &other),
+                canonical_form(&tree) == canonical_form(&other)
+            );
+            let other_root = rng.gen_range(0..n);
+            assert_eq!(
+                rooted_isomorphic(&tree, root, &other, other_root),
+                rooted_canonical_form(&tree, root) == rooted_canonical_form(&other, other_root)
+            );
+        }
+    }
+}
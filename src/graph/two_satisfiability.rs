@@ -1,3 +1,7 @@
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt::{self, Debug, Display};
+use std::ops::Not;
+
 use super::strongly_connected_components::StronglyConnectedComponents as SCCs;
 
 pub type Condition = (i64, i64);
@@ -44,6 +48,195 @@ pub fn solve_two_satisfiability(
     Ok(result)
 }
 
+/// A variable of a `TwoSatisfiability` problem, or its negation (with `!`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Literal(usize);
+
+impl Not for Literal {
+    type Output = Literal;
+    fn not(self) -> Literal {
+        Literal(self.0 ^ 1)
+    }
+}
+
+/// The error returned when the constraints can't be satisfied: a chain of
+/// implications `chain[0] => chain[1] => ...` that forces a variable to be
+/// both true and false, given as `(variable, value)` pairs. The chain starts
+/// and ends with the same literal, and goes through its negation.
+///
+/// The auxiliary variables introduced by `at_most_one` are left out, so two
+/// consecutive literals may be linked by several clauses.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Contradiction<N> {
+    pub chain: Vec<(N, bool)>,
+}
+
+impl<N: Debug> Display for Contradiction<N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let steps: Vec<String> = self
+            .chain
+            .iter()
+            .map(|(name, value)| format!("{name:?} = {value}"))
+            .collect();
+        write!(f, "contradiction: {}", steps.join(" => "))
+    }
+}
+
+impl<N: Debug> std::error::Error for Contradiction<N> {}
+
+/// A 2-SAT problem over named boolean variables, built clause by clause.
+///
+/// Every constraint is turned into clauses `a or b`, i.e. into the
+/// implications `!a => b` and `!b => a`. The problem is satisfiable iff no
+/// variable is in the same strongly connected component as its negation.
+pub struct TwoSatisfiability<N> {
+    // The name of each variable, None for auxiliary variables
+    names: Vec<Option<N>>,
+    index: BTreeMap<N, usize>,
+    // The implication graph: variable v is the vertex 2 * (v + 1), its
+    // negation 2 * (v + 1) + 1 (vertices 0 and 1 are unused)
+    adj: Graph,
+}
+
+impl<N: Ord + Clone> Default for TwoSatisfiability<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<N: Ord + Clone> TwoSatisfiability<N> {
+    pub fn new() -> Self {
+        TwoSatisfiability {
+            names: vec![],
+            index: BTreeMap::new(),
+            adj: vec![vec![]; 2],
+        }
+    }
+
+    fn new_variable(&mut self, name: Option<N>) -> Literal {
+        self.names.push(name);
+        self.adj.push(vec![]);
+        self.adj.push(vec![]);
+        Literal(2 * self.names.len())
+    }
+
+    /// The literal of the variable `name`, which is created the first time.
+    pub fn var(&mut self, name: N) -> Literal {
+        match self.index.get(&name) {
+            Some(&v) => Literal(2 * (v + 1)),
+            None => {
+                self.index.insert(name.clone(), self.names.len());
+                self.new_variable(Some(name))
+            }
+        }
+    }
+
+    /// `a or b`
+    pub fn or(&mut self, a: Literal, b: Literal) {
+        self.adj[(!a).0].push(b.0);
+        self.adj[(!b).0].push(a.0);
+    }
+
+    /// `a => b`
+    pub fn implies(&mut self, a: Literal, b: Literal) {
+        self.or(!a, b);
+    }
+
+    /// Exactly one of `a` and `b`
+    pub fn xor(&mut self, a: Literal, b: Literal) {
+        self.or(a, b);
+        self.or(!a, !b);
+    }
+
+    /// `a` must be true
+    pub fn must(&mut self, a: Literal) {
+        self.or(a, a);
+    }
+
+    /// At most one of `literals` is true. Uses a prefix encoding with one
+    /// auxiliary variable per literal, "one of the literals so far is true",
+    /// so that it only takes O(k) clauses instead of O(k^2).
+    pub fn at_most_one(&mut self, literals: &[Literal]) {
+        let mut previous: Option<Literal> = None;
+        for &literal in literals {
+            let prefix = self.new_variable(None);
+            self.implies(literal, prefix);
+            if let Some(previous) = previous {
+                self.implies(previous, prefix);
+                self.implies(previous, !literal);
+            }
+            previous = Some(prefix);
+        }
+    }
+
+    /// Returns a value for every named variable that satisfies all the
+    /// constraints, or a chain of implications showing that there is none.
+    pub fn solve(&self) -> Result<BTreeMap<N, bool>, Contradiction<N>> {
+        let num_verts = self.adj.len() - 1;
+        let mut sccs = SCCs::new(num_verts);
+        sccs.find_components(&self.adj);
+        // look for a contradiction on a named variable first, for a readable chain
+        let mut conflicts: Vec<usize> = (0..self.names.len())
+            .filter(|&v| {
+                let x = 2 * (v + 1);
+                sccs.component[x] == sccs.component[x ^ 1]
+            })
+            .collect();
+        conflicts.sort_by_key(|&v| self.names[v].is_none());
+        if let Some(&v) = conflicts.first() {
+            return Err(self.contradiction(Literal(2 * (v + 1))));
+        }
+        Ok(self
+            .index
+            .iter()
+            .map(|(name, &v)| {
+                let x = 2 * (v + 1);
+                // components are numbered in reverse topological order
+                (name.clone(), sccs.component[x] < sccs.component[x ^ 1])
+            })
+            .collect())
+    }
+
+    // The chain x => ... => !x => ... => x, for a literal x in the same
+    // component as its negation
+    fn contradiction(&self, x: Literal) -> Contradiction<N> {
+        let mut path = self.implication_path(x, !x);
+        path.pop();
+        path.extend(self.implication_path(!x, x));
+        let chain = path
+            .into_iter()
+            .filter_map(|Literal(u)| self.names[u / 2 - 1].clone().map(|name| (name, u % 2 == 0)))
+            .collect();
+        Contradiction { chain }
+    }
+
+    // A shortest path of implications from a to b, found with a BFS
+    fn implication_path(&self, a: Literal, b: Literal) -> Vec<Literal> {
+        let mut parent = vec![None; self.adj.len()];
+        let mut queue = VecDeque::from([a.0]);
+        parent[a.0] = Some(a.0);
+        while let Some(u) = queue.pop_front() {
+            if u == b.0 {
+                break;
+            }
+            for &v in &self.adj[u] {
+                if parent[v].is_none() {
+                    parent[v] = Some(u);
+                    queue.push_back(v);
+                }
+            }
+        }
+        let mut path = vec![b];
+        let mut u = b.0;
+        while u != a.0 {
+            u = parent[u].unwrap();
+            path.push(Literal(u));
+        }
+        path.reverse();
+        path
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::thread;
@@ -113,4 +306,118 @@ mod tests {
             .unwrap();
         handler.join().unwrap();
     }
+
+    #[test]
+    fn named_variables() {
+        let mut sat = TwoSatisfiability::new();
+        let (rain, umbrella, wet) = (sat.var("rain"), sat.var("umbrella"), sat.var("wet"));
+        sat.must(rain);
+        sat.implies(rain, umbrella);
+        sat.xor(umbrella, wet);
+        let res = sat.solve().unwrap();
+        assert_eq!(
+            res,
+            BTreeMap::from([("rain", true), ("umbrella", true), ("wet", false)])
+        );
+        assert_eq!(sat.var("rain"), rain);
+
+        sat.implies(rain, wet);
+        let err = sat.solve().unwrap_err();
+        assert_eq!(err.chain.first(), err.chain.last());
+        assert!(err.chain.contains(&("rain", false)));
+        assert_eq!(
+            err.to_string(),
+            "contradiction: \"rain\" = true => \"umbrella\" = true => \"wet\" = false \
+             => \"rain\" = false => \"rain\" = true"
+        );
+    }
+
+    #[test]
+    fn at_most_one() {
+        let mut sat = TwoSatisfiability::new();
+        let vars: Vec<Literal> = (0..5).map(|i| sat.var(i)).collect();
+        sat.at_most_one(&vars);
+        sat.must(vars[3]);
+        let res = sat.solve().unwrap();
+        assert_eq!(res.values().filter(|&&v| v).count(), 1);
+        assert!(res[&3]);
+        // the auxiliary variables are not in the answer
+        assert_eq!(res.len(), 5);
+
+        sat.must(vars[1]);
+        let err = sat.solve().unwrap_err();
+        assert!(err.chain.contains(&(1, true)) || err.chain.contains(&(3, true)));
+    }
+
+    // Whether the assignment satisfies the clauses over variables 0..n
+    fn satisfies(clauses: &[(Literal, Literal)], value: &dyn Fn(Literal) -> bool) -> bool {
+        clauses.iter().all(|&(a, b)| value(a) || value(b))
+    }
+
+    #[test]
+    fn random_formulas_match_brute_force() {
+        use rand::rngs::StdRng;
+        use rand::{Rng, SeedableRng};
+        let mut rng = StdRng::seed_from_u64(18);
+        for _ in 0..300 {
+            let n = rng.gen_range(1..=6);
+            let mut sat = TwoSatisfiability::new();
+            let vars: Vec<Literal> = (0..n).map(|i| sat.var(i)).collect();
+            let literal = |rng: &mut StdRng| {
+                let v = vars[rng.gen_range(0..n)];
+                if rng.gen() {
+                    v
+                } else {
+                    !v
+                }
+            };
+            let mut clauses = vec![];
+            for _ in 0..rng.gen_range(0..=2 * n) {
+                let (a, b) = (literal(&mut rng), literal(&mut rng));
+                sat.or(a, b);
+                clauses.push((a, b));
+            }
+            let at_most_one: Vec<Literal> = vars.iter().copied().filter(|_| rng.gen()).collect();
+            sat.at_most_one(&at_most_one);
+
+            let vars = &vars;
+            let value_in = |mask: usize| {
+                move |l: Literal| {
+                    let v = vars.iter().position(|&x| x == l || x == !l).unwrap();
+                    (mask >> v & 1 == 1) == (l == vars[v])
+                }
+            };
+            let valid = |mask: usize| {
+                satisfies(&clauses, &value_in(mask))
+                    && at_most_one.iter().filter(|&&l| value_in(mask)(l)).count() <= 1
+            };
+            let expected = (0..1 << n).any(valid);
+            match sat.solve() {
+                Ok(res) => {
+                    let mask = (0..n).filter(|v| res[v]).map(|v| 1 << v).sum();
+                    assert!(valid(mask));
+                }
+                Err(err) => {
+                    assert!(!expected);
+                    assert_eq!(err.chain.first(), err.chain.last());
+                    let (name, value) = err.chain[0];
+                    assert!(err.chain.contains(&(name, !value)));
+                    if at_most_one.len() <= 1 {
+                        // without auxiliary variables, every step is a clause
+                        for step in err.chain.windows(2) {
+                            let lit = |(v, value): (usize, bool)| {
+                                if value {
+                                    vars[v]
+                                } else {
+                                    !vars[v]
+                                }
+                            };
+                            let (a, b) = (lit(step[0]), lit(step[1]));
+                            assert!(clauses.contains(&(!a, b)) || clauses.contains(&(b, !a)));
+                        }
+                    }
+                }
+            }
+        }
+    }
 }
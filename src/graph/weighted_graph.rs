@@ -0,0 +1,211 @@
+use std::collections::BTreeMap;
+
+/// A read-only view of a directed graph whose edges carry a weight.
+///
+/// Every algorithm of the `graph` module that is not tied to a special
+/// representation accepts any implementor of this trait, so a graph built once
+/// can be handed to Dijkstra, Prim, the SCC algorithms or a max-flow solver
+/// without being converted first.
+///
+/// Undirected graphs are represented by storing every edge in both directions.
+/// Unweighted graphs use `()` as their weight.
+///
+/// Every vertex that appears as the destination of an edge is expected to be
+/// yielded by `vertices` as well (possibly without any outgoing edge).
+pub trait WeightedGraph {
+    type Vertex: Clone;
+    type Weight: Clone;
+
+    /// Iterates over the vertices of the graph, each one exactly once.
+    fn vertices(&self) -> impl Iterator<Item = Self::Vertex> + '_;
+
+    /// Iterates over the outgoing edges of `vertex` as `(destination, weight)`
+    /// pairs. A vertex that is not in the graph has no neighbours.
+    fn neighbors(
+        &self,
+        vertex: Self::Vertex,
+    ) -> impl Iterator<Item = (Self::Vertex, Self::Weight)> + '_;
+
+    fn num_vertices(&self) -> usize {
+        self.vertices().count()
+    }
+
+    fn out_degree(&self, vertex: Self::Vertex) -> usize {
+        self.neighbors(vertex).count()
+    }
+
+    /// Iterates over every edge of the graph as `(source, destination, weight)`.
+    fn edges(&self) -> impl Iterator<Item = (Self::Vertex, Self::Vertex, Self::Weight)> + '_ {
+        self.vertices().flat_map(move |u| {
+            self.neighbors(u.clone())
+                .map(move |(v, weight)| (u.clone(), v, weight))
+        })
+    }
+
+    fn num_edges(&self) -> usize {
+        self.edges().count()
+    }
+}
+
+/// The map-of-maps representation used by `dijkstra`, `prim`, `bellman_ford`,
+/// `floyd_warshall` and `astar`: `graph[u][v]` is the weight of the edge `u -> v`.
+impl<V: Ord + Clone, E: Clone> WeightedGraph for BTreeMap<V, BTreeMap<V, E>> {
+    type Vertex = V;
+    type Weight = E;
+
+    fn vertices(&self) -> impl Iterator<Item = V> + '_ {
+        self.keys().cloned()
+    }
+
+    fn neighbors(&self, vertex: V) -> impl Iterator<Item = (V, E)> + '_ {
+        self.get(&vertex)
+            .into_iter()
+            .flatten()
+            .map(|(v, weight)| (v.clone(), weight.clone()))
+    }
+
+    fn num_vertices(&self) -> usize {
+        self.len()
+    }
+
+    fn out_degree(&self, vertex: V) -> usize {
+        self.get(&vertex).map_or(0, BTreeMap::len)
+    }
+}
+
+/// An unweighted adjacency list keyed by arbitrary vertices, as used by
+/// `prufer_code` and `graph_enumeration`. Neighbours keep their insertion order.
+impl<V: Ord + Clone> WeightedGraph for BTreeMap<V, Vec<V>> {
+    type Vertex = V;
+    type Weight = ();
+
+    fn vertices(&self) -> impl Iterator<Item = V> + '_ {
+        self.keys().cloned()
+    }
+
+    fn neighbors(&self, vertex: V) -> impl Iterator<Item = (V, ())> + '_ {
+        self.get(&vertex)
+            .into_iter()
+            .flatten()
+            .map(|v| (v.clone(), ()))
+    }
+
+    fn num_vertices(&self) -> usize {
+        self.len()
+    }
+
+    fn out_degree(&self, vertex: V) -> usize {
+        self.get(&vertex).map_or(0, Vec::len)
+    }
+}
+
+/// An unweighted adjacency list whose vertices are `0..adj.len()`.
+/// Algorithms that number their vertices from 1 simply leave vertex 0 isolated.
+impl WeightedGraph for Vec<Vec<usize>> {
+    type Vertex = usize;
+    type Weight = ();
+
+    fn vertices(&self) -> impl Iterator<Item = usize> + '_ {
+        0..self.len()
+    }
+
+    fn neighbors(&self, vertex: usize) -> impl Iterator<Item = (usize, ())> + '_ {
+        self.get(vertex).into_iter().flatten().map(|&v| (v, ()))
+    }
+
+    fn num_vertices(&self) -> usize {
+        self.len()
+    }
+
+    fn out_degree(&self, vertex: usize) -> usize {
+        self.get(vertex).map_or(0, Vec::len)
+    }
+}
+
+/// Numbers the vertices of `graph` from 0 in the order `vertices` yields them.
+/// Returns the vertices by number and the adjacency lists over those numbers.
+pub(crate) fn index_graph<G: WeightedGraph>(graph: &G) -> (Vec<G::Vertex>, Vec<Vec<usize>>)
+where
+    G::Vertex: Ord,
+{
+    let vertices: Vec<G::Vertex> = graph.vertices().collect();
+    let index: BTreeMap<&G::Vertex, usize> =
+        vertices.iter().enumerate().map(|(i, v)| (v, i)).collect();
+    let adj = vertices
+        .iter()
+        .map(|u| graph.neighbors(u.clone()).map(|(v, _)| index[&v]).collect())
+        .collect();
+    (vertices, adj)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn map_of_maps() {
+        let mut graph: BTreeMap<char, BTreeMap<char, i32>> = BTreeMap::new();
+        graph.entry('a').or_default().insert('b', 3);
+        graph.entry('a').or_default().insert('c', 5);
+        graph.entry('b').or_default().insert('c', -1);
+        graph.entry('c').or_default();
+
+        assert_eq!(graph.num_vertices(), 3);
+        assert_eq!(graph.num_edges(), 3);
+        assert_eq!(graph.vertices().collect::<Vec<_>>(), vec!['a', 'b', 'c']);
+        assert_eq!(
+            graph.neighbors('a').collect::<Vec<_>>(),
+            vec![('b', 3), ('c', 5)]
+        );
+        assert_eq!(graph.out_degree('c'), 0);
+        assert_eq!(graph.neighbors('z').count(), 0);
+        assert_eq!(
+            graph.edges().collect::<Vec<_>>(),
+            vec![('a', 'b', 3), ('a', 'c', 5), ('b', 'c', -1)]
+        );
+    }
+
+    #[test]
+    fn adjacency_lists() {
+        let adj = vec![vec![], vec![2, 3], vec![3], vec![1]];
+        assert_eq!(adj.num_vertices(), 4);
+        assert_eq!(adj.num_edges(), 4);
+        assert_eq!(adj.neighbors(1).collect::<Vec<_>>(), vec![(2, ()), (3, ())]);
+        assert_eq!(adj.neighbors(10).count(), 0);
+
+        let mut tree: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+        tree.insert("root", vec!["right", "left"]);
+        tree.insert("left", vec!["root"]);
+        tree.insert("right", vec!["root"]);
+        assert_eq!(tree.out_degree("root"), 2);
+        assert_eq!(
+            tree.neighbors("root").map(|(v, _)| v).collect::<Vec<_>>(),
+            vec!["right", "left"]
+        );
+
+        let (vertices, indexed) = index_graph(&tree);
+        assert_eq!(vertices, vec!["left", "right", "root"]);
+        assert_eq!(indexed, vec![vec![2], vec![2], vec![1, 0]]);
+    }
+
+    #[test]
+    fn one_graph_for_every_algorithm() {
+        let mut graph: BTreeMap<usize, BTreeMap<usize, i64>> = BTreeMap::new();
+        for (u, v, w) in [(1, 2, 4), (1, 3, 1), (3, 2, 2), (2, 4, 5), (3, 4, 8)] {
+            graph.entry(u).or_default().insert(v, w);
+            graph.entry(v).or_default().insert(u, w);
+        }
+
+        let distances = crate::graph::dijkstra(&graph, 1);
+        assert_eq!(distances[&4], Some((2, 8)));
+
+        let mst = crate::graph::prim(&graph);
+        assert_eq!(mst.num_edges(), 6);
+
+        assert_eq!(crate::graph::kosaraju(&graph), vec![vec![1, 2, 3, 4]]);
+        assert_eq!(crate::graph::tarjan_scc(&graph).len(), 1);
+
+        let mut flow = crate::graph::DinicMaxFlow::from_graph(&graph, 1, 4);
+        assert_eq!(flow.find_maxflow(i64::MAX), 5);
+    }
+}
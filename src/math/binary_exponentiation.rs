@@ -22,6 +22,24 @@ pub fn binary_exponentiation(mut n: u64, mut p: u32) -> u64 {
     result_pow
 }
 
+// The same idea works for any associative operation, e.g. `min`, string
+// concatenation or the composition of functions: combines `count` copies of
+// `value` with `merge` in O(log(count)) merges. `count` must not be zero.
+pub fn associative_power<T: Copy>(merge: fn(T, T) -> T, mut value: T, mut count: usize) -> T {
+    let mut result = None;
+    while count > 0 {
+        if count & 1 == 1 {
+            result = Some(match result {
+                Some(result) => merge(result, value),
+                None => value,
+            });
+        }
+        count >>= 1;
+        value = merge(value, value);
+    }
+    result.expect("the power of an associative operation needs a positive count")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -47,4 +65,14 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn associative_operations() {
+        assert_eq!(associative_power(|a, b| a * b, 3u64, 21), 10460353203);
+        assert_eq!(associative_power(|a, b| a + b, 7, 1), 7);
+        assert_eq!(associative_power(i32::min, -4, 1000), -4);
+        // composing x -> 2x + 1 with itself 5 times gives x -> 32x + 31
+        let compose = |f: (u64, u64), g: (u64, u64)| (f.0 * g.0, f.1 * g.0 + g.1);
+        assert_eq!(associative_power(compose, (2, 1), 5), (32, 31));
+    }
 }
@@ -89,7 +89,7 @@ pub use self::armstrong_number::is_armstrong_number;
 pub use self::average::{mean, median, mode};
 pub use self::baby_step_giant_step::baby_step_giant_step;
 pub use self::bell_numbers::bell_number;
-pub use self::binary_exponentiation::binary_exponentiation;
+pub use self::binary_exponentiation::{associative_power, binary_exponentiation};
 pub use self::binomial_coefficient::binom;
 pub use self::catalan_numbers::init_catalan;
 pub use self::ceil::ceil;